@@ -3,6 +3,7 @@
 // use crate::js::RuntimeError;
 use crate::js::store::AsStoreRef;
 use crate::js::value::Value;
+use js_sys::Uint8Array;
 use wasm_bindgen::JsValue;
 pub use wasmer_types::{
     ExportType, ExternType, FunctionType, GlobalType, ImportType, MemoryType, Mutability,
@@ -29,6 +30,12 @@ pub fn param_from_js(ty: &ValType, js_val: &JsValue) -> Value {
         ValType::I64 => Value::I64(js_val.as_f64().unwrap() as _),
         ValType::F32 => Value::F32(js_val.as_f64().unwrap() as _),
         ValType::F64 => Value::F64(js_val.as_f64().unwrap()),
+        ValType::V128 => {
+            // Vectors are passed around as their 16 bytes, in little endian order
+            let mut bytes = [0u8; 16];
+            Uint8Array::new(js_val).copy_to(&mut bytes);
+            Value::V128(u128::from_le_bytes(bytes))
+        }
         t => unimplemented!(
             "The type `{:?}` is not yet supported in the JS Function API",
             t
@@ -43,7 +50,7 @@ impl AsJs for Value {
             Self::I64(i) => JsValue::from_f64(*i as f64),
             Self::F32(f) => JsValue::from_f64(*f as f64),
             Self::F64(f) => JsValue::from_f64(*f),
-            Self::V128(f) => JsValue::from_f64(*f as f64),
+            Self::V128(v) => Uint8Array::from(&v.to_le_bytes()[..]).into(),
             Self::FuncRef(Some(func)) => func
                 .handle
                 .get(store.as_store_ref().objects())
@@ -282,6 +282,15 @@ impl Module {
         self.artifact.serialize_to_file(path.as_ref())
     }
 
+    /// Returns the number of bytes of compiled code and data this module
+    /// keeps in memory.
+    ///
+    /// This is known from the compilation, so unlike the length of
+    /// [`Module::serialize`] it doesn't need the module to be serialized.
+    pub fn artifact_size(&self) -> usize {
+        self.artifact.size()
+    }
+
     #[cfg(feature = "compiler")]
     /// Deserializes a serialized Module binary into a `Module`.
     /// > Note: the module has to be serialized before with the `serialize` method.
@@ -340,6 +340,23 @@ fn function_new_dynamic() -> Result<(), String> {
     Ok(())
 }
 
+#[universal_test]
+fn function_new_dynamic_v128() -> Result<(), String> {
+    let mut store = Store::default();
+    let function_type = FunctionType::new(vec![Type::V128], vec![Type::V128]);
+    let function = Function::new(&mut store, &function_type, |values: &[Value]| {
+        Ok(vec![Value::V128(values[0].unwrap_v128().swap_bytes())])
+    });
+
+    let value = 0x0011_2233_4455_6677_8899_aabb_ccdd_eeff_u128;
+    let result = function
+        .call(&mut store, &[Value::V128(value)])
+        .map_err(|e| format!("{:?}", e))?;
+    assert_eq!(result.to_vec(), vec![Value::V128(value.swap_bytes())]);
+
+    Ok(())
+}
+
 #[universal_test]
 fn function_new_dynamic_env() -> Result<(), String> {
     let mut store = Store::default();
@@ -21,4 +21,25 @@ pub trait Cache {
 
     /// Store a [`Module`] into the cache with the given [`Hash`].
     fn store(&mut self, key: Hash, module: &Module) -> Result<(), Self::SerializeError>;
+
+    /// Returns statistics about how the cache has been used so far, if the
+    /// implementation keeps track of them.
+    fn metrics(&self) -> Option<CacheMetrics> {
+        None
+    }
+}
+
+/// Usage statistics of a [`Cache`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct CacheMetrics {
+    /// Number of [`Cache::load`] calls that returned a module
+    pub hits: u64,
+    /// Number of [`Cache::load`] calls that didn't find a module
+    pub misses: u64,
+    /// Number of modules added with [`Cache::store`]
+    pub insertions: u64,
+    /// Number of modules removed to make room for other modules
+    pub evictions: u64,
+    /// Total size in bytes of the modules currently in the cache
+    pub size: u64,
 }
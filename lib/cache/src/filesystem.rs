@@ -1,9 +1,11 @@
 #![cfg_attr(not(feature = "filesystem"), allow(unused))]
 use crate::cache::Cache;
 use crate::hash::Hash;
-use std::fs::{create_dir_all, File};
-use std::io::{self, Write};
+use crate::snapshot;
+use std::fs::{self, create_dir_all, File};
+use std::io::{self, Read, Write};
 use std::path::PathBuf;
+use std::str::FromStr;
 use wasmer::{DeserializeError, Module, SerializeError, Store};
 
 /// Representation of a directory that contains compiled wasm artifacts.
@@ -84,6 +86,66 @@ impl FileSystemCache {
     pub fn set_cache_extension(&mut self, ext: Option<impl ToString>) {
         self.ext = ext.map(|ext| ext.to_string());
     }
+
+    /// Writes all the modules in this cache into a single snapshot, tagged
+    /// with the target of the engine used by `store`.
+    ///
+    /// Returns the number of modules written.
+    pub fn export_snapshot<W: Write>(&self, store: &Store, writer: W) -> io::Result<usize> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+            let key = match self
+                .key_for_filename(&path.file_name().unwrap_or_default().to_string_lossy())
+            {
+                Some(key) => key,
+                None => continue,
+            };
+            entries.push((key, fs::read(&path)?));
+        }
+
+        snapshot::write_snapshot(writer, &snapshot::target_tag(store), &entries)?;
+        Ok(entries.len())
+    }
+
+    /// Stores the modules of a snapshot produced by [`Self::export_snapshot`]
+    /// in this cache.
+    ///
+    /// Snapshots produced for a different target than the one of the engine
+    /// used by `store` are ignored.
+    ///
+    /// Returns the number of modules imported.
+    pub fn import_snapshot<R: Read>(&mut self, store: &Store, reader: R) -> io::Result<usize> {
+        let (target, entries) = snapshot::read_snapshot(reader)?;
+        if target != snapshot::target_tag(store) {
+            return Ok(0);
+        }
+
+        for (key, bytes) in entries.iter() {
+            let mut file = File::create(self.path.join(self.filename_for_key(*key)))?;
+            file.write_all(bytes)?;
+        }
+        Ok(entries.len())
+    }
+
+    fn filename_for_key(&self, key: Hash) -> String {
+        if let Some(ref ext) = self.ext {
+            format!("{}.{}", key.to_string(), ext)
+        } else {
+            key.to_string()
+        }
+    }
+
+    fn key_for_filename(&self, filename: &str) -> Option<Hash> {
+        let hash = match self.ext {
+            Some(ref ext) => filename.strip_suffix(ext.as_str())?.strip_suffix('.')?,
+            None => filename,
+        };
+        Hash::from_str(hash).ok()
+    }
 }
 
 #[cfg(feature = "filesystem")]
@@ -92,22 +154,12 @@ impl Cache for FileSystemCache {
     type SerializeError = SerializeError;
 
     unsafe fn load(&self, store: &Store, key: Hash) -> Result<Module, Self::DeserializeError> {
-        let filename = if let Some(ref ext) = self.ext {
-            format!("{}.{}", key.to_string(), ext)
-        } else {
-            key.to_string()
-        };
-        let path = self.path.join(filename);
+        let path = self.path.join(self.filename_for_key(key));
         Module::deserialize_from_file(store, path)
     }
 
     fn store(&mut self, key: Hash, module: &Module) -> Result<(), Self::SerializeError> {
-        let filename = if let Some(ref ext) = self.ext {
-            format!("{}.{}", key.to_string(), ext)
-        } else {
-            key.to_string()
-        };
-        let path = self.path.join(filename);
+        let path = self.path.join(self.filename_for_key(key));
         let mut file = File::create(path)?;
 
         let buffer = module.serialize()?;
@@ -116,3 +168,54 @@ impl Cache for FileSystemCache {
         Ok(())
     }
 }
+
+#[cfg(all(test, feature = "filesystem"))]
+mod tests {
+    use super::*;
+    use wasmer_compiler_singlepass::Singlepass;
+
+    /// The smallest valid WebAssembly module
+    const EMPTY_MODULE: &[u8] = b"\0asm\x01\0\0\0";
+
+    #[test]
+    fn snapshot_warms_a_fresh_cache() {
+        let store = Store::new(Singlepass::default());
+        let module = Module::new(&store, EMPTY_MODULE).unwrap();
+        let key = Hash::generate(EMPTY_MODULE);
+
+        let source_dir = tempfile::TempDir::new().unwrap();
+        let mut source = FileSystemCache::new(source_dir.path()).unwrap();
+        source.set_cache_extension(Some("wasmu"));
+        source.store(key, &module).unwrap();
+
+        let mut snapshot = Vec::new();
+        assert_eq!(source.export_snapshot(&store, &mut snapshot).unwrap(), 1);
+
+        let target_dir = tempfile::TempDir::new().unwrap();
+        let mut target = FileSystemCache::new(target_dir.path()).unwrap();
+        target.set_cache_extension(Some("wasmu"));
+        assert!(unsafe { target.load(&store, key) }.is_err());
+        assert_eq!(target.import_snapshot(&store, &snapshot[..]).unwrap(), 1);
+        assert!(unsafe { target.load(&store, key) }.is_ok());
+    }
+
+    #[test]
+    fn snapshot_for_other_target_is_ignored() {
+        let store = Store::new(Singlepass::default());
+        let module = Module::new(&store, EMPTY_MODULE).unwrap();
+        let key = Hash::generate(EMPTY_MODULE);
+
+        let mut snapshot = Vec::new();
+        snapshot::write_snapshot(
+            &mut snapshot,
+            "some-other-target",
+            &[(key, module.serialize().unwrap().to_vec())],
+        )
+        .unwrap();
+
+        let target_dir = tempfile::TempDir::new().unwrap();
+        let mut target = FileSystemCache::new(target_dir.path()).unwrap();
+        assert_eq!(target.import_snapshot(&store, &snapshot[..]).unwrap(), 0);
+        assert!(unsafe { target.load(&store, key) }.is_err());
+    }
+}
@@ -21,11 +21,17 @@
 mod cache;
 mod filesystem;
 mod hash;
+mod memory;
+mod snapshot;
+mod tiered;
 
-pub use crate::cache::Cache;
+pub use crate::cache::{Cache, CacheMetrics};
 #[cfg(feature = "filesystem")]
 pub use crate::filesystem::FileSystemCache;
 pub use crate::hash::Hash;
+pub use crate::memory::MemoryCache;
+#[cfg(feature = "filesystem")]
+pub use crate::tiered::TieredCache;
 
 // We re-export those for convinience of users
 pub use wasmer::{DeserializeError, SerializeError};
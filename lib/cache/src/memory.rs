@@ -0,0 +1,284 @@
+use crate::cache::{Cache, CacheMetrics};
+use crate::hash::Hash;
+use std::collections::HashMap;
+use std::io;
+use std::sync::Mutex;
+use wasmer::{DeserializeError, Module, SerializeError, Store};
+
+/// An in-memory cache of compiled wasm modules with a bounded size.
+///
+/// The size of a module is the size of its compiled code and data, as
+/// reported by [`Module::artifact_size`]. When storing
+/// a module would exceed the configured budget, the least recently used
+/// modules are evicted until it fits. Evicted modules are simply reported as
+/// missing on [`Cache::load`], so callers recompile them as they would on any
+/// other cache miss.
+///
+/// Modules are kept compiled, so [`Cache::load`] hands out the module that
+/// was stored regardless of the [`Store`] passed in: a `MemoryCache` should
+/// only be shared between stores backed by the same engine.
+///
+/// # Usage
+///
+/// ```
+/// use wasmer::{Module, SerializeError};
+/// use wasmer_cache::{Cache, Hash, MemoryCache};
+///
+/// fn store_module(module: &Module, bytes: &[u8]) -> Result<(), SerializeError> {
+///     // Keep at most 64 MiB of compiled code in memory.
+///     let mut cache = MemoryCache::new(64 * 1024 * 1024);
+///
+///     // Store a module into the cache given a key
+///     cache.store(Hash::generate(bytes), module)?;
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MemoryCache {
+    inner: Mutex<MemoryCacheInner>,
+}
+
+#[derive(Debug)]
+struct MemoryCacheInner {
+    max_size: usize,
+    size: usize,
+    clock: u64,
+    entries: HashMap<Hash, MemoryCacheEntry>,
+    metrics: CacheMetrics,
+}
+
+#[derive(Debug)]
+struct MemoryCacheEntry {
+    module: Module,
+    size: usize,
+    last_used: u64,
+}
+
+impl MemoryCache {
+    /// Creates an empty cache holding at most `max_size` bytes of compiled
+    /// modules.
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            inner: Mutex::new(MemoryCacheInner {
+                max_size,
+                size: 0,
+                clock: 0,
+                entries: HashMap::new(),
+                metrics: CacheMetrics::default(),
+            }),
+        }
+    }
+
+    /// The maximum number of bytes of compiled modules this cache holds.
+    pub fn max_size(&self) -> usize {
+        self.inner.lock().unwrap().max_size
+    }
+
+    /// The number of bytes of compiled modules currently in this cache.
+    pub fn size(&self) -> usize {
+        self.inner.lock().unwrap().size
+    }
+
+    /// The number of modules currently in this cache.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+
+    /// Whether this cache holds no modules.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether a module is cached for the given key. This doesn't count as a
+    /// use of the module.
+    pub fn contains(&self, key: Hash) -> bool {
+        self.inner.lock().unwrap().entries.contains_key(&key)
+    }
+}
+
+impl MemoryCacheInner {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Evicts the least recently used modules until `additional` more bytes fit.
+    fn make_room(&mut self, additional: usize) {
+        while self.size + additional > self.max_size {
+            let lru = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| *key);
+            match lru {
+                Some(key) => {
+                    let entry = self.entries.remove(&key).unwrap();
+                    self.size -= entry.size;
+                    self.metrics.evictions += 1;
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+impl Cache for MemoryCache {
+    type DeserializeError = DeserializeError;
+    type SerializeError = SerializeError;
+
+    unsafe fn load(&self, _store: &Store, key: Hash) -> Result<Module, Self::DeserializeError> {
+        let mut inner = self.inner.lock().unwrap();
+        let now = inner.tick();
+        match inner.entries.get_mut(&key) {
+            Some(entry) => {
+                entry.last_used = now;
+                let module = entry.module.clone();
+                inner.metrics.hits += 1;
+                Ok(module)
+            }
+            None => {
+                inner.metrics.misses += 1;
+                Err(DeserializeError::Io(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("module {} is not in the memory cache", key.to_string()),
+                )))
+            }
+        }
+    }
+
+    fn store(&mut self, key: Hash, module: &Module) -> Result<(), Self::SerializeError> {
+        self.insert(key, module);
+        Ok(())
+    }
+
+    fn metrics(&self) -> Option<CacheMetrics> {
+        let inner = self.inner.lock().unwrap();
+        Some(CacheMetrics {
+            size: inner.size as u64,
+            ..inner.metrics
+        })
+    }
+}
+
+impl MemoryCache {
+    /// Same as [`Cache::store`], but only needs a shared reference since the
+    /// state is behind a lock.
+    pub(crate) fn insert(&self, key: Hash, module: &Module) {
+        let size = module.artifact_size();
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(previous) = inner.entries.remove(&key) {
+            inner.size -= previous.size;
+        }
+        if size > inner.max_size {
+            // The module would never fit, don't flush the whole cache for it
+            return;
+        }
+        inner.make_room(size);
+        let last_used = inner.tick();
+        inner.size += size;
+        inner.metrics.insertions += 1;
+        inner.entries.insert(
+            key,
+            MemoryCacheEntry {
+                module: module.clone(),
+                size,
+                last_used,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use wasmer_compiler_singlepass::Singlepass;
+
+    /// Returns a distinct module exporting a function named `name`
+    pub(crate) fn module(store: &Store, name: &str) -> (Hash, Module) {
+        // (module (func (export "<name>")))
+        let mut bytes = b"\0asm\x01\0\0\0".to_vec();
+        bytes.extend_from_slice(&[0x01, 0x04, 0x01, 0x60, 0x00, 0x00]);
+        bytes.extend_from_slice(&[0x03, 0x02, 0x01, 0x00]);
+        bytes.extend_from_slice(&[0x07, (name.len() + 4) as u8, 0x01, name.len() as u8]);
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(&[0x00, 0x00]);
+        bytes.extend_from_slice(&[0x0a, 0x04, 0x01, 0x02, 0x00, 0x0b]);
+        (Hash::generate(&bytes), Module::new(store, &bytes).unwrap())
+    }
+
+    #[test]
+    fn least_recently_used_module_is_evicted() {
+        let store = Store::new(Singlepass::default());
+        let (key_a, module_a) = module(&store, "a");
+        let (key_b, module_b) = module(&store, "b");
+        let (key_c, module_c) = module(&store, "c");
+        let size = module_a.artifact_size();
+
+        // Room for two modules, but not three
+        let mut cache = MemoryCache::new(size * 2 + size / 2);
+        cache.store(key_a, &module_a).unwrap();
+        cache.store(key_b, &module_b).unwrap();
+        assert_eq!(cache.len(), 2);
+
+        // Using `a` makes `b` the least recently used module
+        assert!(unsafe { cache.load(&store, key_a) }.is_ok());
+        cache.store(key_c, &module_c).unwrap();
+
+        assert!(cache.contains(key_a));
+        assert!(!cache.contains(key_b));
+        assert!(cache.contains(key_c));
+        assert!(cache.size() <= cache.max_size());
+
+        // An evicted module is a regular miss, and can be stored again once
+        // it has been recompiled
+        assert!(matches!(
+            unsafe { cache.load(&store, key_b) },
+            Err(DeserializeError::Io(_))
+        ));
+        let (_, recompiled) = module(&store, "b");
+        cache.store(key_b, &recompiled).unwrap();
+        assert!(unsafe { cache.load(&store, key_b) }.is_ok());
+        assert!(!cache.contains(key_a));
+    }
+
+    #[test]
+    fn metrics_are_tracked() {
+        let store = Store::new(Singlepass::default());
+        let (key_a, module_a) = module(&store, "a");
+        let (key_b, module_b) = module(&store, "b");
+        let size = module_a.artifact_size();
+
+        let mut cache = MemoryCache::new(size + size / 2);
+        assert_eq!(cache.metrics(), Some(CacheMetrics::default()));
+
+        assert!(unsafe { cache.load(&store, key_a) }.is_err());
+        cache.store(key_a, &module_a).unwrap();
+        assert!(unsafe { cache.load(&store, key_a) }.is_ok());
+        assert!(unsafe { cache.load(&store, key_a) }.is_ok());
+        cache.store(key_b, &module_b).unwrap();
+        assert!(unsafe { cache.load(&store, key_a) }.is_err());
+
+        assert_eq!(
+            cache.metrics(),
+            Some(CacheMetrics {
+                hits: 2,
+                misses: 2,
+                insertions: 2,
+                evictions: 1,
+                size: cache.size() as u64,
+            })
+        );
+    }
+
+    #[test]
+    fn module_larger_than_budget_is_not_cached() {
+        let store = Store::new(Singlepass::default());
+        let (key, module) = module(&store, "a");
+
+        let mut cache = MemoryCache::new(1);
+        cache.store(key, &module).unwrap();
+        assert!(cache.is_empty());
+        assert_eq!(cache.size(), 0);
+    }
+}
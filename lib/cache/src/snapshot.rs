@@ -0,0 +1,124 @@
+//! Snapshots bundle the compiled modules of a cache into a single file, so
+//! that a prebuilt cache can be shipped alongside an application and loaded
+//! on startup instead of compiling modules on first use.
+//!
+//! A snapshot is tagged with the target of the engine that produced it.
+//! Importing a snapshot that was produced for a different target is a no-op.
+#![cfg_attr(not(feature = "filesystem"), allow(unused))]
+
+use crate::hash::Hash;
+use std::convert::TryInto;
+use std::io::{self, Read, Write};
+use wasmer::Store;
+
+/// Magic bytes at the start of every snapshot
+const MAGIC: &[u8; 16] = b"wasmer-cache-snp";
+
+/// Version of the snapshot format
+const VERSION: u32 = 1;
+
+/// Returns the tag identifying the target the modules of `store` are
+/// compiled for.
+pub(crate) fn target_tag(store: &Store) -> String {
+    let target = store.engine().target();
+    format!("{}+{:x}", target.triple(), target.cpu_features().as_u64())
+}
+
+/// Writes a snapshot containing the given serialized modules.
+pub(crate) fn write_snapshot<W: Write>(
+    mut writer: W,
+    target: &str,
+    entries: &[(Hash, Vec<u8>)],
+) -> io::Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    writer.write_all(&(target.len() as u64).to_le_bytes())?;
+    writer.write_all(target.as_bytes())?;
+    writer.write_all(&(entries.len() as u64).to_le_bytes())?;
+    for (key, bytes) in entries {
+        writer.write_all(&key.to_array())?;
+        writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+        writer.write_all(bytes)?;
+    }
+    writer.flush()
+}
+
+/// Reads a snapshot, returning the target it was produced for and the
+/// serialized modules it contains.
+pub(crate) fn read_snapshot<R: Read>(mut reader: R) -> io::Result<(String, Vec<(Hash, Vec<u8>)>)> {
+    let mut magic = [0u8; 16];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid_data("not a wasmer cache snapshot"));
+    }
+
+    let mut version = [0u8; 4];
+    reader.read_exact(&mut version)?;
+    let version = u32::from_le_bytes(version);
+    if version != VERSION {
+        return Err(invalid_data(format!(
+            "unsupported cache snapshot version {}",
+            version
+        )));
+    }
+
+    let target = read_bytes(&mut reader)?;
+    let target = String::from_utf8(target)
+        .map_err(|_| invalid_data("cache snapshot target is not valid UTF-8"))?;
+
+    let count = read_u64(&mut reader)?;
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let mut key = [0u8; 32];
+        reader.read_exact(&mut key)?;
+        entries.push((Hash::new(key), read_bytes(&mut reader)?));
+    }
+
+    Ok((target, entries))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_bytes<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let len: usize = read_u64(reader)?
+        .try_into()
+        .map_err(|_| invalid_data("cache snapshot entry is too large"))?;
+    let mut bytes = Vec::new();
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(bytes)
+}
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_roundtrip() {
+        let entries = vec![
+            (Hash::generate(b"first"), vec![1, 2, 3]),
+            (Hash::generate(b"second"), vec![]),
+        ];
+        let mut buffer = Vec::new();
+        write_snapshot(&mut buffer, "x86_64-unknown-linux-gnu+0", &entries).unwrap();
+
+        let (target, read) = read_snapshot(&buffer[..]).unwrap();
+        assert_eq!(target, "x86_64-unknown-linux-gnu+0");
+        assert_eq!(read, entries);
+    }
+
+    #[test]
+    fn snapshot_rejects_garbage() {
+        assert!(read_snapshot(&b"definitely not a snapshot"[..]).is_err());
+    }
+}
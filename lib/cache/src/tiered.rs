@@ -0,0 +1,130 @@
+#![cfg_attr(not(feature = "filesystem"), allow(unused))]
+use crate::cache::{Cache, CacheMetrics};
+use crate::filesystem::FileSystemCache;
+use crate::hash::Hash;
+use crate::memory::MemoryCache;
+use wasmer::{DeserializeError, Module, SerializeError, Store};
+
+/// A [`MemoryCache`] in front of a [`FileSystemCache`].
+///
+/// Loads are served from memory when possible. On a miss, the module is
+/// deserialized from disk instead of being recompiled and kept in memory for
+/// the next load. Stored modules go to both tiers, so modules evicted from
+/// memory remain available on disk.
+///
+/// # Usage
+///
+/// ```
+/// use wasmer::{Module, SerializeError};
+/// use wasmer_cache::{Cache, FileSystemCache, Hash, MemoryCache, TieredCache};
+///
+/// fn store_module(module: &Module, bytes: &[u8]) -> Result<(), SerializeError> {
+///     // Keep the 64 MiB of most recently used compiled code in memory,
+///     // everything else on disk.
+///     let mut cache = TieredCache::new(
+///         MemoryCache::new(64 * 1024 * 1024),
+///         FileSystemCache::new("some/directory/goes/here")?,
+///     );
+///
+///     // Store a module into both tiers given a key
+///     cache.store(Hash::generate(bytes), module)?;
+///
+///     Ok(())
+/// }
+/// ```
+pub struct TieredCache {
+    memory: MemoryCache,
+    disk: FileSystemCache,
+}
+
+#[cfg(feature = "filesystem")]
+impl TieredCache {
+    /// Creates a cache using `memory` as the first tier and `disk` as the
+    /// second one.
+    pub fn new(memory: MemoryCache, disk: FileSystemCache) -> Self {
+        Self { memory, disk }
+    }
+
+    /// The in-memory tier of this cache.
+    pub fn memory(&self) -> &MemoryCache {
+        &self.memory
+    }
+
+    /// The on-disk tier of this cache.
+    pub fn disk(&self) -> &FileSystemCache {
+        &self.disk
+    }
+}
+
+#[cfg(feature = "filesystem")]
+impl Cache for TieredCache {
+    type DeserializeError = DeserializeError;
+    type SerializeError = SerializeError;
+
+    unsafe fn load(&self, store: &Store, key: Hash) -> Result<Module, Self::DeserializeError> {
+        if let Ok(module) = self.memory.load(store, key) {
+            return Ok(module);
+        }
+        let module = self.disk.load(store, key)?;
+        self.memory.insert(key, &module);
+        Ok(module)
+    }
+
+    fn store(&mut self, key: Hash, module: &Module) -> Result<(), Self::SerializeError> {
+        self.disk.store(key, module)?;
+        self.memory.insert(key, module);
+        Ok(())
+    }
+
+    /// The metrics of the in-memory tier.
+    fn metrics(&self) -> Option<CacheMetrics> {
+        self.memory.metrics()
+    }
+}
+
+#[cfg(all(test, feature = "filesystem"))]
+mod tests {
+    use super::*;
+    use crate::memory::tests::module;
+    use wasmer_compiler_singlepass::Singlepass;
+
+    #[test]
+    fn evicted_module_is_loaded_from_disk() {
+        let store = Store::new(Singlepass::default());
+        let (key_a, module_a) = module(&store, "a");
+        let (key_b, module_b) = module(&store, "b");
+        let size = module_a.artifact_size();
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut cache = TieredCache::new(
+            MemoryCache::new(size + size / 2),
+            FileSystemCache::new(dir.path()).unwrap(),
+        );
+        cache.store(key_a, &module_a).unwrap();
+        cache.store(key_b, &module_b).unwrap();
+        assert!(!cache.memory().contains(key_a));
+
+        // `a` was evicted from memory, but is still served without compiling
+        assert!(unsafe { cache.load(&store, key_a) }.is_ok());
+        assert!(cache.memory().contains(key_a));
+        assert!(!cache.memory().contains(key_b));
+
+        let metrics = cache.metrics().unwrap();
+        assert_eq!(metrics.misses, 1);
+        assert_eq!(metrics.evictions, 2);
+    }
+
+    #[test]
+    fn missing_module_is_a_miss_in_both_tiers() {
+        let store = Store::new(Singlepass::default());
+        let (key, _) = module(&store, "a");
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = TieredCache::new(
+            MemoryCache::new(usize::MAX),
+            FileSystemCache::new(dir.path()).unwrap(),
+        );
+        assert!(unsafe { cache.load(&store, key) }.is_err());
+        assert!(cache.memory().is_empty());
+    }
+}
@@ -13,6 +13,15 @@ pub enum Cache {
     /// Display the location of the cache
     #[clap(name = "dir")]
     Dir,
+
+    /// Remove the least recently used packages that are not currently
+    /// running until the installed packages fit in the given size
+    #[clap(name = "gc")]
+    Gc {
+        /// Maximum number of bytes the installed packages may take up
+        #[clap(long = "max-size")]
+        max_size: u64,
+    },
 }
 
 impl Cache {
@@ -25,6 +34,10 @@ impl Cache {
             Cache::Dir => {
                 self.dir()?;
             }
+            Cache::Gc { max_size } => {
+                self.gc(*max_size)
+                    .context("failed to garbage collect installed packages.")?;
+            }
         }
         Ok(())
     }
@@ -37,6 +50,17 @@ impl Cache {
         eprintln!("Wasmer cache cleaned successfully.");
         Ok(())
     }
+    fn gc(&self, max_size: u64) -> Result<()> {
+        let stats = wasmer_registry::gc_installed_packages(max_size)?;
+        for path in stats.removed.iter() {
+            eprintln!("Removed {}", path.display());
+        }
+        eprintln!(
+            "Freed {} bytes, installed packages now take up {} bytes.",
+            stats.freed, stats.remaining
+        );
+        Ok(())
+    }
     fn dir(&self) -> Result<()> {
         println!("{}", get_cache_dir().to_string_lossy());
         Ok(())
@@ -28,7 +28,8 @@ use wasi::Wasi;
 /// The options for the `wasmer run` subcommand, runs either a package, URL or a file
 #[derive(Debug, Parser, Clone, Default)]
 pub struct Run {
-    /// File to run
+    /// File to run, or `-` to read a module, artifact or WebC package from
+    /// stdin
     #[clap(name = "SOURCE", parse(try_from_str))]
     pub(crate) path: PackageSource,
     /// Options to run the file / package / URL with
@@ -52,12 +53,22 @@ pub struct RunWithoutFile {
     #[clap(long = "invoke", short = 'i')]
     pub(crate) invoke: Option<String>,
 
+    /// Print the exports of the module with their types instead of running it
+    #[clap(long = "list-exports")]
+    pub(crate) list_exports: bool,
+
     /// The command name is a string that will override the first argument passed
     /// to the wasm program. This is used in wapm to provide nicer output in
     /// help commands and error messages of the running wasm program
     #[clap(long = "command-name", hide = true)]
     pub(crate) command_name: Option<String>,
 
+    /// Run the command of a WebC package that executes the given atom. If a
+    /// command is given as well, it has to execute that atom.
+    #[cfg(feature = "webc_runner")]
+    #[clap(long = "atom")]
+    pub(crate) atom: Option<String>,
+
     /// A prehashed string, used to speed up start times by avoiding hashing the
     /// wasm module. If the specified hash is not found, Wasmer will hash the module
     /// as if no `cache-key` argument was passed.
@@ -169,7 +180,11 @@ impl RunWithPathBuf {
         }
 
         // Do we want to invoke a function?
-        if let Some(ref invoke) = self.invoke {
+        let invoke = match &self.invoke {
+            Some(invoke) => Some(invoke.clone()),
+            None => Some(self.find_entrypoint(&instance)?).filter(|name| name != "_start"),
+        };
+        if let Some(ref invoke) = invoke {
             let result = self.invoke_function(&mut store, &instance, invoke, &self.args)?;
             println!(
                 "{}",
@@ -192,18 +207,40 @@ impl RunWithPathBuf {
     }
 
     fn inner_execute(&self) -> Result<()> {
+        #[cfg(feature = "wasi")]
+        self.wasi.check_stdin()?;
         #[cfg(feature = "webc_runner")]
         {
             if let Ok(pf) = WapmContainer::new(self.path.clone()) {
+                if self.wasi.tee {
+                    bail!("--tee is not supported when running a WebC package");
+                }
+                if self.list_exports {
+                    bail!("--list-exports is not supported when running a WebC package");
+                }
                 return Self::run_container(
                     pf,
                     &self.command_name.clone().unwrap_or_default(),
+                    self.atom.as_deref(),
+                    &self.wasi,
                     &self.args,
                 )
                 .map_err(|e| anyhow!("Could not run PiritaFile: {e}"));
             }
         }
         let (mut store, module) = self.get_store_module()?;
+        if self.list_exports {
+            for export in module.exports() {
+                let (kind, ty) = match export.ty() {
+                    ExternType::Function(ty) => ("func", ty.to_string()),
+                    ExternType::Global(ty) => ("global", ty.to_string()),
+                    ExternType::Table(ty) => ("table", ty.to_string()),
+                    ExternType::Memory(ty) => ("memory", ty.to_string()),
+                };
+                println!("{} \"{}\": {}", kind, export.name(), ty);
+            }
+            return Ok(());
+        }
         #[cfg(feature = "emscripten")]
         {
             use wasmer_emscripten::{
@@ -260,7 +297,11 @@ impl RunWithPathBuf {
         #[cfg(feature = "wasi")]
         let ret = {
             use std::collections::BTreeSet;
-            use wasmer_wasi::WasiVersion;
+            use wasmer_wasi::{get_unsupported_wasi_version, WasiError, WasiVersion};
+
+            if let Some(version) = get_unsupported_wasi_version(&module) {
+                return Err(WasiError::UnsupportedWasiVersion(version).into());
+            }
 
             let wasi_versions = Wasi::get_versions(&module);
             match wasi_versions {
@@ -291,11 +332,15 @@ impl RunWithPathBuf {
                                 .map(|f| f.to_string_lossy().to_string())
                         })
                         .unwrap_or_default();
-                    let (_ctx, instance) = self
+                    let (ctx, instance) = self
                         .wasi
                         .instantiate(&mut store, &module, program_name, self.args.clone())
                         .with_context(|| "failed to instantiate WASI module")?;
-                    self.inner_module_run(store, instance)
+                    // Leave the terminal as it was, even if the program trapped
+                    let env = ctx.as_ref(&store).clone();
+                    let result = self.inner_module_run(store, instance);
+                    env.tty_restore();
+                    result
                 }
                 // not WASI
                 _ => {
@@ -340,7 +385,13 @@ impl RunWithPathBuf {
     }
 
     #[cfg(feature = "webc_runner")]
-    fn run_container(container: WapmContainer, id: &str, args: &[String]) -> Result<(), String> {
+    fn run_container(
+        container: WapmContainer,
+        id: &str,
+        atom: Option<&str>,
+        wasi: &Wasi,
+        args: &[String],
+    ) -> Result<(), String> {
         let mut result = None;
 
         #[cfg(feature = "wasi")]
@@ -349,12 +400,22 @@ impl RunWithPathBuf {
                 return r;
             }
 
+            let id = Self::command_for_atom(&container, "wasi", id, atom)?;
             let mut runner = wasmer_wasi::runners::wasi::WasiRunner::default();
             runner.set_args(args.to_vec());
+            if let Some(path) = &wasi.stdin {
+                runner.set_stdin(path.clone());
+            }
+            if let Some(path) = &wasi.stdout {
+                runner.set_stdout(path.clone());
+            }
+            if let Some(path) = &wasi.stderr {
+                runner.set_stderr(path.clone());
+            }
             result = Some(if id.is_empty() {
                 runner.run(&container).map_err(|e| format!("{e}"))
             } else {
-                runner.run_cmd(&container, id).map_err(|e| format!("{e}"))
+                runner.run_cmd(&container, &id).map_err(|e| format!("{e}"))
             });
         }
 
@@ -364,18 +425,56 @@ impl RunWithPathBuf {
                 return r;
             }
 
+            let id = Self::command_for_atom(&container, "emscripten", id, atom)?;
             let mut runner = wasmer_wasi::runners::emscripten::EmscriptenRunner::default();
             runner.set_args(args.to_vec());
             result = Some(if id.is_empty() {
                 runner.run(&container).map_err(|e| format!("{e}"))
             } else {
-                runner.run_cmd(&container, id).map_err(|e| format!("{e}"))
+                runner.run_cmd(&container, &id).map_err(|e| format!("{e}"))
             });
         }
 
         result.unwrap_or_else(|| Err("neither emscripten or wasi file".to_string()))
     }
 
+    /// Picks the command of the container to run: the one executing `atom` if
+    /// given, otherwise `id` (the entrypoint if empty)
+    #[cfg(feature = "webc_runner")]
+    fn command_for_atom(
+        container: &WapmContainer,
+        api: &str,
+        id: &str,
+        atom: Option<&str>,
+    ) -> Result<String, String> {
+        let atom = match atom {
+            Some(atom) => atom,
+            None => return Ok(id.to_string()),
+        };
+        if !id.is_empty() {
+            let command_atom = container.get_atom_name_for_command(api, id)?;
+            if command_atom != atom {
+                return Err(format!(
+                    "command {id:?} executes atom {command_atom:?}, not {atom:?}"
+                ));
+            }
+            return Ok(id.to_string());
+        }
+        container
+            .webc
+            .webc
+            .manifest
+            .commands
+            .keys()
+            .find(|command| {
+                container
+                    .get_atom_name_for_command(api, command)
+                    .map_or(false, |command_atom| command_atom == atom)
+            })
+            .cloned()
+            .ok_or_else(|| format!("no command executes atom {atom:?}"))
+    }
+
     fn get_store_module(&self) -> Result<(Store, Module)> {
         let contents = std::fs::read(self.path.clone())?;
         if wasmer_compiler::Artifact::is_deserializable(&contents) {
@@ -456,6 +555,41 @@ impl RunWithPathBuf {
         Ok(cache)
     }
 
+    /// Picks the function to run when `--invoke` isn't given: `_start`, or
+    /// else `main` or the only function exported besides `_initialize`
+    fn find_entrypoint(&self, instance: &Instance) -> Result<String> {
+        let functions = instance
+            .module()
+            .exports()
+            .functions()
+            .map(|f| f.name().to_string())
+            .filter(|name| name != "_initialize")
+            .collect::<Vec<_>>();
+        for conventional in ["_start", "main"] {
+            if functions.iter().any(|name| name == conventional) {
+                return Ok(conventional.to_string());
+            }
+        }
+        match &functions[..] {
+            [only] => Ok(only.clone()),
+            [] => {
+                self.try_find_function(instance, "_start", &[])?;
+                Ok("_start".to_string())
+            }
+            candidates => bail!(
+                "No `_start` function found in the module, and no single function to run instead. Candidates: {}.\nTry with: wasmer {} -i {} {}",
+                candidates
+                    .iter()
+                    .map(|name| format!("`{}`", name))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                self.path.display(),
+                candidates[0],
+                self.args.join(" ")
+            ),
+        }
+    }
+
     fn try_find_function(
         &self,
         instance: &Instance,
@@ -555,15 +689,32 @@ impl RunWithPathBuf {
                 )),
             })
             .collect::<Result<Vec<_>>>()?;
-        Ok(func.call(ctx, &invoke_args)?)
+        let result = func.call(ctx, &invoke_args);
+        #[cfg(feature = "wasi")]
+        return self.wasi.handle_result(result);
+        #[cfg(not(feature = "wasi"))]
+        Ok(result?)
     }
 }
 
 impl Run {
     /// Executes the `wasmer run` command
     pub fn execute(&self) -> Result<(), anyhow::Error> {
+        if self.path == PackageSource::File("-".to_string()) {
+            // The file is detected from its contents as usual, so it only
+            // has to live as long as the run
+            let piped = Self::read_stdin_to_file()?;
+            return RunWithPathBuf {
+                path: piped.path().to_path_buf(),
+                options: self.options.clone(),
+            }
+            .execute();
+        }
+
         // downloads and installs the package if necessary
         let path_to_run = self.path.download_and_get_filepath()?;
+        // keeps `wasmer cache gc` from removing the package while it runs
+        let _in_use = wasmer_registry::use_installed_package(&path_to_run)?;
         RunWithPathBuf {
             path: path_to_run,
             options: self.options.clone(),
@@ -571,6 +722,15 @@ impl Run {
         .execute()
     }
 
+    /// Reads everything piped to `wasmer run -` into a temporary file
+    fn read_stdin_to_file() -> Result<tempfile::NamedTempFile> {
+        let mut file = tempfile::NamedTempFile::new()?;
+        std::io::copy(&mut std::io::stdin().lock(), &mut file)
+            .context("could not read the module from stdin")?;
+        std::io::Write::flush(&mut file)?;
+        Ok(file)
+    }
+
     /// Create Run instance for arguments/env,
     /// assuming we're being run from a CFP binfmt interpreter.
     pub fn from_binfmt_args() -> Run {
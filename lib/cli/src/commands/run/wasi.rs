@@ -1,11 +1,18 @@
-use crate::utils::{parse_envvar, parse_mapdir};
-use anyhow::Result;
+use crate::utils::{parse_env_file, parse_envvar, parse_mapdir};
+use anyhow::{Context, Result};
 use std::collections::BTreeSet;
-use std::path::PathBuf;
-use wasmer::{AsStoreMut, FunctionEnv, Instance, Module, RuntimeError, Value};
+use std::io::{self, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use wasmer::{AsStoreMut, FunctionEnv, Instance, Module, RuntimeError};
+use wasmer_types::TrapCode;
+use wasmer_vfs::host_fs::{Stderr, Stdout};
+use wasmer_vfs::{FileDescriptor, FsError, VirtualFile};
+use wasmer_wasi::types::__WASI_STDERR_FILENO;
 use wasmer_wasi::{
     get_wasi_versions, import_object_for_all_wasi_versions, is_wasix_module,
-    wasi_import_shared_memory, WasiEnv, WasiError, WasiState, WasiVersion,
+    wasi_import_shared_memory, wasi_stub_missing_imports, SyscallCounts, WasiEnv, WasiError,
+    WasiState, WasiVersion,
 };
 
 use clap::Parser;
@@ -33,6 +40,31 @@ pub struct Wasi {
     )]
     pub(crate) env_vars: Vec<(String, String)>,
 
+    /// Load environment variables from a dotenv-style file, with `--env`
+    /// taking precedence over them
+    #[clap(long = "env-file", name = "ENV_FILE")]
+    pub(crate) env_files: Vec<PathBuf>,
+
+    /// Read the program's standard input from a file instead of the
+    /// terminal
+    #[clap(long = "stdin", name = "STDIN_FILE")]
+    pub(crate) stdin: Option<PathBuf>,
+
+    /// Write the program's standard output to a file, which is created or
+    /// truncated
+    #[clap(long = "stdout", name = "STDOUT_FILE")]
+    pub(crate) stdout: Option<PathBuf>,
+
+    /// Write the program's standard error to a file, which is created or
+    /// truncated
+    #[clap(long = "stderr", name = "STDERR_FILE")]
+    pub(crate) stderr: Option<PathBuf>,
+
+    /// Keep showing what goes to the `--stdout` and `--stderr` files on the
+    /// terminal as well
+    #[clap(long = "tee")]
+    pub(crate) tee: bool,
+
     /// Enable experimental IO devices
     #[cfg(feature = "experimental-io-devices")]
     #[cfg_attr(
@@ -48,6 +80,27 @@ pub struct Wasi {
     /// Require WASI modules to only import 1 version of WASI.
     #[clap(long = "deny-multiple-wasi-versions")]
     pub deny_multiple_wasi_versions: bool,
+
+    /// Replace the WASI functions the runtime doesn't implement with stubs
+    /// that trap when called, instead of failing to instantiate the module.
+    #[clap(long = "stub-missing-imports")]
+    pub stub_missing_imports: bool,
+
+    /// Print how many times each WASI function was called once the module
+    /// is done running.
+    #[clap(long = "count-syscalls")]
+    pub count_syscalls: bool,
+
+    /// Let the program see a terminal on its standard streams even when
+    /// they are piped, so it behaves as it would interactively.
+    #[clap(long = "pty")]
+    pub pty: bool,
+
+    #[clap(skip)]
+    syscall_counts: SyscallCounts,
+
+    #[clap(skip)]
+    stderr_tail: StderrTail,
 }
 
 #[allow(dead_code)]
@@ -87,9 +140,28 @@ impl Wasi {
         let mut wasi_state_builder = WasiState::new(program_name);
         wasi_state_builder
             .args(args)
-            .envs(self.env_vars.clone())
+            .envs(self.envs()?)
             .preopen_dirs(self.pre_opened_directories.clone())?
             .map_dirs(self.mapped_dirs.clone())?;
+        if let Some(path) = &self.stdin {
+            wasi_state_builder.stdin(Box::new(open_stdin(path)?));
+        }
+        if let Some(path) = &self.stdout {
+            let file = create_output(path, "stdout")?;
+            wasi_state_builder.stdout(if self.tee {
+                Box::new(Tee::new(file, Stdout))
+            } else {
+                Box::new(file)
+            });
+        }
+        if let Some(path) = &self.stderr {
+            let file = create_output(path, "stderr")?;
+            wasi_state_builder.stderr(if self.tee {
+                Box::new(Tee::new(file, Stderr))
+            } else {
+                Box::new(file)
+            });
+        }
 
         #[cfg(feature = "experimental-io-devices")]
         {
@@ -100,22 +172,87 @@ impl Wasi {
         }
 
         let wasi_env = wasi_state_builder.finalize(store)?;
+        if self.pty {
+            let runtime = wasi_env.data_mut(store).runtime();
+            let mut tty = runtime.tty_get();
+            tty.stdin_tty = true;
+            tty.stdout_tty = true;
+            tty.stderr_tty = true;
+            runtime.tty_set(tty);
+        }
+        {
+            // Keep what the program says last, in case it aborts
+            let state = &wasi_env.data(store).state;
+            let inodes = state.inodes.read().unwrap();
+            let stderr =
+                state
+                    .fs
+                    .swap_file(&inodes, __WASI_STDERR_FILENO, Box::new(Stderr::default()))?;
+            if let Some(stderr) = stderr {
+                let recorded = RecordTail::new(stderr, self.stderr_tail.clone());
+                state
+                    .fs
+                    .swap_file(&inodes, __WASI_STDERR_FILENO, Box::new(recorded))?;
+            }
+        }
         wasi_env.env.as_mut(store).state.fs.is_wasix.store(
             is_wasix_module(module),
             std::sync::atomic::Ordering::Release,
         );
         let mut import_object = import_object_for_all_wasi_versions(store, &wasi_env.env);
         wasi_import_shared_memory(&mut import_object, module, store);
+        if self.stub_missing_imports {
+            wasi_stub_missing_imports(&mut import_object, module, store);
+        }
+        if self.count_syscalls {
+            wasi_env
+                .data_mut(store)
+                .set_syscall_counts(self.syscall_counts.clone());
+        }
         let instance = Instance::new(store, module, &import_object)?;
         let memory = instance.exports.get_memory("memory")?;
         wasi_env.data_mut(store).set_memory(memory.clone());
         Ok((wasi_env.env, instance))
     }
 
-    /// Helper function for handling the result of a Wasi _start function.
-    pub fn handle_result(&self, result: Result<Box<[Value]>, RuntimeError>) -> Result<()> {
+    /// Gathers the environment variables from the files and `--env` flags,
+    /// the last one given winning when a variable is set more than once
+    fn envs(&self) -> Result<Vec<(String, String)>> {
+        let mut envs: Vec<(String, String)> = Vec::new();
+        for path in &self.env_files {
+            let contents = std::fs::read_to_string(path)
+                .with_context(|| format!("could not read env file {}", path.display()))?;
+            let vars = parse_env_file(&contents)
+                .with_context(|| format!("invalid env file {}", path.display()))?;
+            envs.extend(vars);
+        }
+        envs.extend(self.env_vars.iter().cloned());
+
+        let mut merged: Vec<(String, String)> = Vec::new();
+        for (key, value) in envs {
+            merged.retain(|(k, _)| *k != key);
+            merged.push((key, value));
+        }
+        Ok(merged)
+    }
+
+    /// Makes sure the file given to `--stdin` can be opened, so that a
+    /// wrong path is reported before the program starts
+    pub(crate) fn check_stdin(&self) -> Result<()> {
+        if let Some(path) = &self.stdin {
+            open_stdin(path)?;
+        }
+        Ok(())
+    }
+
+    /// Helper function for handling the result of a Wasi _start function, or
+    /// of the function given to `--invoke`.
+    pub fn handle_result<T>(&self, result: Result<T, RuntimeError>) -> Result<T> {
+        if self.count_syscalls {
+            self.print_syscall_counts();
+        }
         match result {
-            Ok(_) => Ok(()),
+            Ok(values) => Ok(values),
             Err(err) => {
                 let err: anyhow::Error = match err.downcast::<WasiError>() {
                     Ok(WasiError::Exit(exit_code)) => {
@@ -123,6 +260,13 @@ impl Wasi {
                         std::process::exit(exit_code as _);
                     }
                     Ok(err) => err.into(),
+                    Err(err) if err.clone().to_trap() == Some(TrapCode::UnreachableCodeReached) => {
+                        match self.stderr_tail.abort_message() {
+                            Some(message) => anyhow::Error::from(err)
+                                .context(format!("guest aborted: {}", message)),
+                            None => err.into(),
+                        }
+                    }
                     Err(err) => err.into(),
                 };
                 Err(err)
@@ -130,6 +274,16 @@ impl Wasi {
         }
     }
 
+    /// Prints the number of calls made to each WASI function to stderr.
+    fn print_syscall_counts(&self) {
+        let counts = self.syscall_counts.snapshot();
+        let width = counts.keys().map(|name| name.len()).max().unwrap_or(0);
+        eprintln!("WASI calls:");
+        for (name, count) in counts {
+            eprintln!("  {:width$}  {}", name, count, width = width);
+        }
+    }
+
     pub fn for_binfmt_interpreter() -> Result<Self> {
         use std::env;
         let dir = env::var_os("WASMER_BINFMT_MISC_PREOPEN")
@@ -143,3 +297,218 @@ impl Wasi {
         })
     }
 }
+
+/// Opens the file given to `--stdin` for the program to read
+fn open_stdin(path: &Path) -> Result<wasmer_vfs::host_fs::File> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("could not open stdin file {}", path.display()))?;
+    Ok(wasmer_vfs::host_fs::File::new(
+        file,
+        path.to_path_buf(),
+        true,
+        false,
+        false,
+    ))
+}
+
+/// Creates the file given to `--stdout` or `--stderr` for the program to
+/// write to
+fn create_output(path: &Path, stream: &str) -> Result<wasmer_vfs::host_fs::File> {
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("could not create {} file {}", stream, path.display()))?;
+    Ok(wasmer_vfs::host_fs::File::new(
+        file,
+        path.to_path_buf(),
+        false,
+        true,
+        false,
+    ))
+}
+
+/// An output stream of the program that is written both to a file and to
+/// the matching host stream, for `--tee`
+#[derive(Debug)]
+struct Tee<T> {
+    file: wasmer_vfs::host_fs::File,
+    host: T,
+}
+
+impl<T> Tee<T> {
+    fn new(file: wasmer_vfs::host_fs::File, host: T) -> Self {
+        Self { file, host }
+    }
+}
+
+impl<T> Read for Tee<T> {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "can not read from an output stream",
+        ))
+    }
+}
+
+impl<T> Seek for Tee<T> {
+    fn seek(&mut self, _pos: io::SeekFrom) -> io::Result<u64> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "can not seek an output stream",
+        ))
+    }
+}
+
+impl<T: Write> Write for Tee<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.file.write(buf)?;
+        self.host.write_all(&buf[..written])?;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        self.host.flush()
+    }
+}
+
+impl<T: VirtualFile + Send + Sync + 'static> VirtualFile for Tee<T> {
+    fn last_accessed(&self) -> u64 {
+        0
+    }
+
+    fn last_modified(&self) -> u64 {
+        0
+    }
+
+    fn created_time(&self) -> u64 {
+        0
+    }
+
+    fn size(&self) -> u64 {
+        0
+    }
+
+    fn set_len(&mut self, _new_size: u64) -> Result<(), FsError> {
+        Err(FsError::PermissionDenied)
+    }
+
+    fn unlink(&mut self) -> Result<(), FsError> {
+        Ok(())
+    }
+}
+
+/// The last bytes a program wrote to stderr, which is where it explains
+/// why it is about to abort
+#[derive(Debug, Clone, Default)]
+struct StderrTail(Arc<Mutex<Vec<u8>>>);
+
+impl StderrTail {
+    /// How much of the output is kept
+    const LEN: usize = 1024;
+
+    /// What Rust panics, failed C asserts and uncaught C++ exceptions print
+    /// before the program aborts
+    const ABORT_MARKERS: &'static [&'static str] =
+        &["panicked at", "Assertion failed", "terminate called"];
+
+    fn record(&self, buf: &[u8]) {
+        let mut tail = self.0.lock().unwrap();
+        tail.extend_from_slice(buf);
+        if tail.len() > Self::LEN {
+            let excess = tail.len() - Self::LEN;
+            tail.drain(..excess);
+        }
+    }
+
+    /// The last lines that were written, if they explain why the program
+    /// aborted. Other traps leave whatever the program wrote alone.
+    fn abort_message(&self) -> Option<String> {
+        let tail = self.0.lock().unwrap();
+        let text = String::from_utf8_lossy(&tail);
+        // Drop the line that was cut off at the front
+        let text = match text.split_once('\n') {
+            Some((_, rest)) if tail.len() == Self::LEN => rest,
+            _ => &*text,
+        };
+        let text = text.trim();
+        if Self::ABORT_MARKERS
+            .iter()
+            .any(|marker| text.contains(marker))
+        {
+            Some(text.to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// An output stream of the program that records the last bytes written to
+/// it in a [`StderrTail`]
+#[derive(Debug)]
+struct RecordTail {
+    inner: Box<dyn VirtualFile + Send + Sync + 'static>,
+    tail: StderrTail,
+}
+
+impl RecordTail {
+    fn new(inner: Box<dyn VirtualFile + Send + Sync + 'static>, tail: StderrTail) -> Self {
+        Self { inner, tail }
+    }
+}
+
+impl Read for RecordTail {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl Seek for RecordTail {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+impl Write for RecordTail {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.tail.record(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl VirtualFile for RecordTail {
+    fn last_accessed(&self) -> u64 {
+        self.inner.last_accessed()
+    }
+
+    fn last_modified(&self) -> u64 {
+        self.inner.last_modified()
+    }
+
+    fn created_time(&self) -> u64 {
+        self.inner.created_time()
+    }
+
+    fn size(&self) -> u64 {
+        self.inner.size()
+    }
+
+    fn set_len(&mut self, new_size: u64) -> Result<(), FsError> {
+        self.inner.set_len(new_size)
+    }
+
+    fn unlink(&mut self) -> Result<(), FsError> {
+        self.inner.unlink()
+    }
+
+    fn bytes_available_write(&self) -> Result<Option<usize>, FsError> {
+        self.inner.bytes_available_write()
+    }
+
+    fn get_fd(&self) -> Option<FileDescriptor> {
+        self.inner.get_fd()
+    }
+}
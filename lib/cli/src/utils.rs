@@ -64,9 +64,64 @@ pub fn parse_envvar(entry: &str) -> Result<(String, String)> {
     }
 }
 
+/// Parses the contents of a dotenv-style file: a `<name>=<value>` per
+/// line, with blank lines and lines starting with `#` skipped. Values can be
+/// put in single quotes, which are taken as is, or double quotes, where `\n`,
+/// `\"` and `\\` are unescaped.
+pub fn parse_env_file(contents: &str) -> Result<Vec<(String, String)>> {
+    let mut vars = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, value) = match line.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => (name.trim(), value.trim()),
+            _ => bail!(
+                "line {}: expected `<name>=<value>`; found `{}`",
+                index + 1,
+                line
+            ),
+        };
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                if value.len() < 2 || !value.ends_with(quote) {
+                    bail!(
+                        "line {}: unterminated quoted value for `{}`",
+                        index + 1,
+                        name
+                    );
+                }
+                let inner = &value[1..value.len() - 1];
+                if quote == '\'' {
+                    inner.to_string()
+                } else {
+                    let mut unescaped = String::with_capacity(inner.len());
+                    let mut chars = inner.chars();
+                    while let Some(c) = chars.next() {
+                        match (c, chars.clone().next()) {
+                            ('\\', Some('n')) => unescaped.push('\n'),
+                            ('\\', Some(c @ ('"' | '\\'))) => unescaped.push(c),
+                            (c, _) => {
+                                unescaped.push(c);
+                                continue;
+                            }
+                        }
+                        chars.next();
+                    }
+                    unescaped
+                }
+            }
+            _ => value.to_string(),
+        };
+        vars.push((name.to_string(), value));
+    }
+    Ok(vars)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::parse_envvar;
+    use super::{parse_env_file, parse_envvar};
 
     #[test]
     fn test_parse_envvar() {
@@ -89,4 +144,36 @@ mod tests {
             ("A".into(), "B=C=D".into())
         );
     }
+
+    #[test]
+    fn test_parse_env_file() {
+        let vars = parse_env_file(
+            "# settings\n\nA=B\n  C = D E \nQUOTED=\"hello world\"\nSINGLE='a \\n b'\nESCAPED=\"x\\ny \\\\n \\\"\"\nEMPTY=\nURL=a=b\n",
+        )
+        .unwrap();
+        assert_eq!(
+            vars,
+            vec![
+                ("A".into(), "B".into()),
+                ("C".into(), "D E".into()),
+                ("QUOTED".into(), "hello world".into()),
+                ("SINGLE".into(), "a \\n b".into()),
+                ("ESCAPED".into(), "x\ny \\n \"".into()),
+                ("EMPTY".into(), "".into()),
+                ("URL".into(), "a=b".into()),
+            ]
+        );
+        assert_eq!(
+            parse_env_file("A=B\n# fine\nC\n").unwrap_err().to_string(),
+            "line 3: expected `<name>=<value>`; found `C`"
+        );
+        assert_eq!(
+            parse_env_file("=B").unwrap_err().to_string(),
+            "line 1: expected `<name>=<value>`; found `=B`"
+        );
+        assert_eq!(
+            parse_env_file("A=\"B").unwrap_err().to_string(),
+            "line 1: unterminated quoted value for `A`"
+        );
+    }
 }
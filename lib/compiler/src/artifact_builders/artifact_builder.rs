@@ -75,6 +75,7 @@ impl ArtifactBuild {
             translation.module_translation_state.as_ref().unwrap(),
             translation.function_body_inputs,
         )?;
+        middlewares.finalize_module_info(&compile_info.module)?;
 
         let data_initializers = translation
             .data_initializers
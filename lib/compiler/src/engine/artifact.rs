@@ -11,7 +11,7 @@ use crate::{
     InstantiationError, RuntimeError, Tunables,
 };
 #[cfg(feature = "static-artifact-create")]
-use crate::{Compiler, FunctionBodyData, ModuleTranslationState};
+use crate::{Compiler, FunctionBodyData, ModuleMiddlewareChain, ModuleTranslationState};
 use crate::{Engine, EngineInner};
 use enumset::EnumSet;
 #[cfg(any(feature = "static-artifact-create", feature = "static-artifact-load"))]
@@ -304,6 +304,18 @@ impl Artifact {
         &self.signatures
     }
 
+    /// Returns the number of bytes of compiled functions and data
+    /// initializers this `Artifact` keeps in memory.
+    pub fn size(&self) -> usize {
+        let code: usize = self.finished_function_lengths.values().sum();
+        let data: usize = self
+            .data_initializers()
+            .iter()
+            .map(|initializer| initializer.data.len())
+            .sum();
+        code + data
+    }
+
     /// Do preinstantiation logic that is executed before instantiating
     pub fn preinstantiate(&self) -> Result<(), InstantiationError> {
         Ok(())
@@ -433,7 +445,6 @@ impl Artifact {
         let translation = environ.translate(data).map_err(CompileError::Wasm)?;
 
         // We try to apply the middleware first
-        use crate::translator::ModuleMiddlewareChain;
         let mut module = translation.module;
         let middlewares = compiler.get_middlewares();
         middlewares.apply_on_module_info(&mut module)?;
@@ -548,6 +559,12 @@ impl Artifact {
                 module_translation.as_ref().unwrap(),
                 function_body_inputs,
             )?;
+
+        // The middlewares get to check the module once all functions went through them
+        compiler
+            .get_middlewares()
+            .finalize_module_info(&metadata.compile_info.module)?;
+
         let mut obj = get_object_for_target(target_triple).map_err(to_compile_error)?;
 
         emit_data(&mut obj, WASMER_METADATA_SYMBOL, &metadata_binary, 1)
@@ -27,10 +27,20 @@ pub trait ModuleMiddleware: Debug + Send + Sync {
     fn transform_module_info(&self, _: &mut ModuleInfo) -> Result<(), MiddlewareError> {
         Ok(())
     }
+
+    /// Checks the module once all of its functions went through the middleware. This is called
+    /// after application on functions ends, so an error here still fails the compilation.
+    fn finalize_module_info(&self, _: &ModuleInfo) -> Result<(), MiddlewareError> {
+        Ok(())
+    }
 }
 
 /// A function middleware specialized for a single function.
 pub trait FunctionMiddleware<'a>: Debug {
+    /// Provide the number of bytes left in the function's body once the local count was read,
+    /// which covers the local declarations and the operators. This is called before locals_info.
+    fn body_size_hint(&mut self, _bytes_remaining: usize) {}
+
     /// Provide info on the function's locals. This is called before feed.
     fn locals_info(&mut self, _locals: &[Type]) {}
 
@@ -43,6 +53,12 @@ pub trait FunctionMiddleware<'a>: Debug {
         state.push_operator(operator);
         Ok(())
     }
+
+    /// Called once the last operator of the function (its final `End`) was read, right before
+    /// it is fed. Operators pushed here end up just before that `End`.
+    fn finalize(&mut self, _state: &mut MiddlewareReaderState<'a>) -> Result<(), MiddlewareError> {
+        Ok(())
+    }
 }
 
 /// A Middleware binary reader of the WebAssembly structures and types.
@@ -84,6 +100,9 @@ pub trait ModuleMiddlewareChain {
 
     /// Applies the chain on a `ModuleInfo` struct.
     fn apply_on_module_info(&self, module_info: &mut ModuleInfo) -> Result<(), MiddlewareError>;
+
+    /// Finalizes the chain on a `ModuleInfo` struct once its functions were compiled.
+    fn finalize_module_info(&self, module_info: &ModuleInfo) -> Result<(), MiddlewareError>;
 }
 
 impl<T: Deref<Target = dyn ModuleMiddleware>> ModuleMiddlewareChain for [T] {
@@ -104,6 +123,14 @@ impl<T: Deref<Target = dyn ModuleMiddleware>> ModuleMiddlewareChain for [T] {
         }
         Ok(())
     }
+
+    /// Finalizes the chain on a `ModuleInfo` struct once its functions were compiled.
+    fn finalize_module_info(&self, module_info: &ModuleInfo) -> Result<(), MiddlewareError> {
+        for item in self {
+            item.finalize_module_info(module_info)?;
+        }
+        Ok(())
+    }
 }
 
 impl<'a> MiddlewareReaderState<'a> {
@@ -146,6 +173,14 @@ impl<'a> MiddlewareBinaryReader<'a> {
         self.chain = stages;
     }
 
+    /// Pass the size of the rest of the function body to all middlewares
+    fn emit_body_size_hint(&mut self) {
+        let bytes_remaining = self.state.inner.bytes_remaining();
+        for middleware in &mut self.chain {
+            middleware.body_size_hint(bytes_remaining)
+        }
+    }
+
     /// Pass info about the locals of a function to all middlewares
     fn emit_locals_info(&mut self) {
         for middleware in &mut self.chain {
@@ -160,6 +195,7 @@ impl<'a> FunctionBinaryReader<'a> for MiddlewareBinaryReader<'a> {
         let total = total.map_err(from_binaryreadererror_wasmerror)?;
         self.state.local_decls = total;
         self.state.locals.reserve(total as usize);
+        self.emit_body_size_hint();
         if total == 0 {
             self.emit_locals_info();
         }
@@ -202,15 +238,21 @@ impl<'a> FunctionBinaryReader<'a> for MiddlewareBinaryReader<'a> {
 
             // Fill the initial raw operator into pending buffer.
             self.state.pending_operations.push_back(raw_op);
+            let last = self.state.inner.eof();
 
             // Run the operator through each stage.
             for stage in &mut self.chain {
                 // Take the outputs from the previous stage.
                 let pending: SmallVec<[Operator<'a>; 2]> =
                     self.state.pending_operations.drain(0..).collect();
-
-                // ...and feed them into the current stage.
-                for pending_op in pending {
+                let count = pending.len();
+
+                // ...and feed them into the current stage, letting it finalize the function
+                // before the final `End`.
+                for (i, pending_op) in pending.into_iter().enumerate() {
+                    if last && i + 1 == count {
+                        stage.finalize(&mut self.state)?;
+                    }
                     stage.feed(pending_op, &mut self.state)?;
                 }
             }
@@ -232,10 +274,83 @@ impl<'a> FunctionBinaryReader<'a> for MiddlewareBinaryReader<'a> {
     }
 
     fn eof(&self) -> bool {
-        self.state.inner.eof()
+        // Operators pushed by the middlewares may still be pending
+        self.state.pending_operations.is_empty() && self.state.inner.eof()
     }
 
     fn range(&self) -> Range {
         self.state.inner.range()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[derive(Debug)]
+    struct HintRecorder {
+        hint: Rc<Cell<Option<usize>>>,
+    }
+
+    impl<'a> FunctionMiddleware<'a> for HintRecorder {
+        fn body_size_hint(&mut self, bytes_remaining: usize) {
+            self.hint.set(Some(bytes_remaining));
+        }
+    }
+
+    #[derive(Debug)]
+    struct NopBeforeEnd;
+
+    impl<'a> FunctionMiddleware<'a> for NopBeforeEnd {
+        fn finalize(
+            &mut self,
+            state: &mut MiddlewareReaderState<'a>,
+        ) -> Result<(), MiddlewareError> {
+            state.push_operator(Operator::Nop);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn finalize_pushes_operators_before_final_end() {
+        // (block (drop (i32.const 0)))
+        let body = [0x00, 0x02, 0x40, 0x41, 0x00, 0x1a, 0x0b, 0x0b];
+        let mut reader = MiddlewareBinaryReader::new_with_offset(&body, 0);
+        reader.set_middleware_chain(vec![Box::new(NopBeforeEnd)]);
+
+        assert_eq!(reader.read_local_count().unwrap(), 0);
+        let mut operators = vec![];
+        while !reader.eof() {
+            operators.push(reader.read_operator().unwrap());
+        }
+        assert!(
+            matches!(
+                operators[..],
+                [
+                    Operator::Block { .. },
+                    Operator::I32Const { value: 0 },
+                    Operator::Drop,
+                    Operator::End,
+                    Operator::Nop,
+                    Operator::End,
+                ]
+            ),
+            "{:?}",
+            operators
+        );
+    }
+
+    #[test]
+    fn body_size_hint_is_given_after_local_count() {
+        // (local i32 i32) (drop (i32.const 0))
+        let body = [0x01, 0x02, 0x7f, 0x41, 0x00, 0x1a, 0x0b];
+        let hint = Rc::new(Cell::new(None));
+        let mut reader = MiddlewareBinaryReader::new_with_offset(&body, 0);
+        reader.set_middleware_chain(vec![Box::new(HintRecorder { hint: hint.clone() })]);
+
+        assert_eq!(reader.read_local_count().unwrap(), 1);
+        assert_eq!(hint.get(), Some(body.len() - 1));
+    }
+}
@@ -1,6 +1,8 @@
 pub mod metering;
+pub mod operator_limit;
 
 // The most commonly used symbol are exported at top level of the
 // module. Others are available via modules,
 // e.g. `wasmer_middlewares::metering::get_remaining_points`
 pub use metering::Metering;
+pub use operator_limit::OperatorLimit;
@@ -0,0 +1,155 @@
+//! `operator_limit` is a middleware for putting a limit on the number
+//! of operators in the body of each function, so that modules with
+//! pathologically large functions fail to compile instead of tying up
+//! the compiler.
+
+use wasmer::wasmparser::Operator;
+use wasmer::{
+    FunctionMiddleware, LocalFunctionIndex, MiddlewareError, MiddlewareReaderState,
+    ModuleMiddleware,
+};
+
+/// The module-level operator limit middleware.
+///
+/// Compiling a module fails with a `MiddlewareError` as soon as one of
+/// its functions has more operators than the limit. The default is
+/// unlimited.
+///
+/// The operators counted are the ones this middleware is fed, which
+/// include the operators added by the middlewares pushed before it (e.g.
+/// the accounting of `Metering`). To limit the operators of the original
+/// function bodies, `OperatorLimit` must be pushed first.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use wasmer::CompilerConfig;
+/// use wasmer_middlewares::OperatorLimit;
+///
+/// fn create_operator_limit_middleware(compiler_config: &mut dyn CompilerConfig) {
+///     // No function may have more than 100000 operators. This is pushed
+///     // before any other middleware so only the original operators count.
+///     let operator_limit = Arc::new(OperatorLimit::new(100_000));
+///     compiler_config.push_middleware(operator_limit);
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct OperatorLimit {
+    /// Maximum number of operators in a function, if any.
+    max_operators_per_function: Option<usize>,
+}
+
+/// The function-level operator limit middleware.
+#[derive(Debug)]
+pub struct FunctionOperatorLimit {
+    /// The function the operators are counted for.
+    local_function_index: LocalFunctionIndex,
+
+    /// Maximum number of operators in the function, if any.
+    max_operators: Option<usize>,
+
+    /// Number of operators seen so far.
+    operators: usize,
+}
+
+impl OperatorLimit {
+    /// Creates an `OperatorLimit` middleware allowing up to
+    /// `max_operators_per_function` operators in each function.
+    pub fn new(max_operators_per_function: usize) -> Self {
+        Self {
+            max_operators_per_function: Some(max_operators_per_function),
+        }
+    }
+}
+
+impl ModuleMiddleware for OperatorLimit {
+    /// Generates a `FunctionMiddleware` for a given function.
+    fn generate_function_middleware<'a>(
+        &self,
+        local_function_index: LocalFunctionIndex,
+    ) -> Box<dyn FunctionMiddleware<'a> + 'a> {
+        Box::new(FunctionOperatorLimit {
+            local_function_index,
+            max_operators: self.max_operators_per_function,
+            operators: 0,
+        })
+    }
+}
+
+impl<'a> FunctionMiddleware<'a> for FunctionOperatorLimit {
+    fn feed(
+        &mut self,
+        operator: Operator<'a>,
+        state: &mut MiddlewareReaderState<'a>,
+    ) -> Result<(), MiddlewareError> {
+        self.operators += 1;
+        if let Some(max_operators) = self.max_operators {
+            if self.operators > max_operators {
+                return Err(MiddlewareError::new(
+                    "operator_limit",
+                    format!(
+                        "function {} has more than {} operators",
+                        self.local_function_index.as_u32(),
+                        max_operators
+                    ),
+                ));
+            }
+        }
+
+        state.push_operator(operator);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+    use wasmer::{wat2wasm, CompilerConfig, Cranelift, EngineBuilder, Module, Store};
+    use wasmer_types::{CompileError, WasmError};
+
+    fn bytecode() -> Vec<u8> {
+        wat2wasm(
+            br#"
+            (module
+            (func $small (result i32)
+                i32.const 1)
+            (func $large (param $value i32) (result i32)
+                local.get $value
+                i32.const 1
+                i32.add
+                i32.const 2
+                i32.mul))
+            "#,
+        )
+        .unwrap()
+        .into()
+    }
+
+    fn compile(operator_limit: OperatorLimit) -> Result<Module, CompileError> {
+        let mut compiler_config = Cranelift::default();
+        compiler_config.push_middleware(Arc::new(operator_limit));
+        let store = Store::new(EngineBuilder::new(compiler_config));
+        Module::new(&store, bytecode())
+    }
+
+    #[test]
+    fn functions_within_the_limit_compile() {
+        // The end of the function counts as an operator as well
+        assert!(compile(OperatorLimit::new(6)).is_ok());
+        assert!(compile(OperatorLimit::default()).is_ok());
+    }
+
+    #[test]
+    fn functions_over_the_limit_fail_to_compile() {
+        match compile(OperatorLimit::new(5)) {
+            Err(CompileError::Wasm(WasmError::Middleware(error))) => {
+                assert_eq!(error.name, "operator_limit");
+                assert_eq!(error.message, "function 1 has more than 5 operators");
+            }
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+}
@@ -711,8 +711,6 @@ async fn install_webc_package_inner(
     url: &Url,
     checksum: &str,
 ) -> Result<(), anyhow::Error> {
-    use futures_util::StreamExt;
-
     #[cfg(test)]
     let path = get_webc_dir(test_name).ok_or_else(|| anyhow::anyhow!("no webc dir"))?;
     #[cfg(not(test))]
@@ -722,9 +720,12 @@ async fn install_webc_package_inner(
 
     let webc_path = path.join(checksum);
 
-    let mut file = std::fs::File::create(&webc_path)
-        .map_err(|e| anyhow::anyhow!("{e}"))
-        .context(anyhow::anyhow!("{}", webc_path.display()))?;
+    // Stream into a `.partial` file first: if this future is dropped (e.g. the
+    // user cancels `wasmer run`) the response stream is dropped with it, which
+    // closes the connection, and the guard removes the incomplete file so that
+    // it never shows up as an installed package.
+    let mut partial =
+        PartialDownload::create(path.join(format!("{checksum}.{PARTIAL_DOWNLOAD_EXTENSION}")))?;
 
     let client = {
         let builder = reqwest::Client::builder();
@@ -736,30 +737,210 @@ async fn install_webc_package_inner(
             .context("install_webc_package: failed to build reqwest Client")?
     };
 
-    let res = client
-        .get(url.clone())
-        .header(ACCEPT, "application/webc")
-        .send()
-        .await
-        .and_then(|response| response.error_for_status())
-        .map_err(|e| anyhow::anyhow!("{e}"))
-        .context(anyhow::anyhow!("install_webc_package: failed to GET {url}"))?;
+    download_with_retries(&client, url, &mut partial).await?;
 
-    let mut stream = res.bytes_stream();
+    partial.persist(&webc_path)?;
+
+    Ok(())
+}
+
+/// How many times a webc download is attempted before giving up
+const DOWNLOAD_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry of a failed download, doubled on every retry
+const DOWNLOAD_RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+
+/// Upper bound for the delay between two download attempts
+const DOWNLOAD_RETRY_MAX_DELAY: Duration = Duration::from_secs(5);
 
+/// Error of a single download attempt
+#[derive(Debug)]
+enum DownloadError {
+    /// The download may succeed if it is attempted again (network errors,
+    /// server errors, connection closed mid-stream, ...)
+    Transient(anyhow::Error),
+    /// Retrying won't help (client errors, local I/O errors, ...)
+    Fatal(anyhow::Error),
+}
+
+/// Downloads `url` into `partial`, retrying transient failures with a
+/// jittered exponential backoff. Retries resume the download where the
+/// previous attempt stopped if the server supports range requests.
+async fn download_with_retries(
+    client: &reqwest::Client,
+    url: &Url,
+    partial: &mut PartialDownload,
+) -> Result<(), anyhow::Error> {
+    let mut attempt = 1;
+    loop {
+        match download_attempt(client, url, partial).await {
+            Ok(()) => return Ok(()),
+            Err(DownloadError::Transient(e)) if attempt < DOWNLOAD_ATTEMPTS => {
+                let delay = download_retry_delay(attempt);
+                log::warn!(
+                    "download of {url} failed (attempt {attempt}/{DOWNLOAD_ATTEMPTS}), retrying in {delay:?}: {e:#}"
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(DownloadError::Transient(e)) | Err(DownloadError::Fatal(e)) => return Err(e),
+        }
+    }
+}
+
+async fn download_attempt(
+    client: &reqwest::Client,
+    url: &Url,
+    partial: &mut PartialDownload,
+) -> Result<(), DownloadError> {
+    use futures_util::StreamExt;
+
+    let resume_from = partial.len();
+    let mut request = client.get(url.clone()).header(ACCEPT, "application/webc");
+    if resume_from > 0 {
+        request = request.header(RANGE, format!("bytes={resume_from}-"));
+    }
+
+    let res = request.send().await.map_err(|e| {
+        DownloadError::Transient(
+            anyhow::anyhow!("{e}").context(format!("install_webc_package: failed to GET {url}")),
+        )
+    })?;
+
+    let status = res.status();
+    if !status.is_success() {
+        let e = anyhow::anyhow!("install_webc_package: failed to GET {url}: {status}");
+        return Err(if status.is_server_error() {
+            DownloadError::Transient(e)
+        } else {
+            DownloadError::Fatal(e)
+        });
+    }
+
+    // The server ignored the range request, start over
+    if resume_from > 0 && status != reqwest::StatusCode::PARTIAL_CONTENT {
+        partial.truncate().map_err(|e| {
+            DownloadError::Fatal(anyhow::anyhow!(
+                "install_webc_package: failed to truncate {}: {e}",
+                partial.path.display()
+            ))
+        })?;
+    }
+
+    let mut stream = res.bytes_stream();
     while let Some(item) = stream.next().await {
-        let item = item
+        let item = item.map_err(|e| {
+            DownloadError::Transient(
+                anyhow::anyhow!("{e}")
+                    .context(format!("install_webc_package: failed to GET {url}")),
+            )
+        })?;
+        partial.write_all(&item).map_err(|e| {
+            DownloadError::Fatal(anyhow::anyhow!(
+                "install_webc_package: failed to write chunk to {}: {e}",
+                partial.path.display()
+            ))
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Exponential backoff for the given (1-based) retry, with up to 50% of
+/// random jitter so that clients failing at the same time don't retry in
+/// lockstep.
+fn download_retry_delay(attempt: u32) -> Duration {
+    let delay = DOWNLOAD_RETRY_BASE_DELAY
+        .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+        .min(DOWNLOAD_RETRY_MAX_DELAY);
+    let jitter = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default()
+        % 1000;
+    delay + delay / 2 * jitter / 1000
+}
+
+/// File extension of webc downloads that haven't completed yet
+const PARTIAL_DOWNLOAD_EXTENSION: &str = "partial";
+
+/// A file that is being downloaded into. Unless [`PartialDownload::persist`]
+/// succeeds, the file is removed again when the guard is dropped.
+#[derive(Debug)]
+struct PartialDownload {
+    path: PathBuf,
+    file: Option<std::fs::File>,
+    len: u64,
+    persisted: bool,
+}
+
+impl PartialDownload {
+    fn create(path: PathBuf) -> Result<Self, anyhow::Error> {
+        let file = std::fs::File::create(&path)
             .map_err(|e| anyhow::anyhow!("{e}"))
-            .context(anyhow::anyhow!("install_webc_package: failed to GET {url}"))?;
-        file.write_all(&item)
+            .context(anyhow::anyhow!("{}", path.display()))?;
+        Ok(Self {
+            path,
+            file: Some(file),
+            len: 0,
+            persisted: false,
+        })
+    }
+
+    /// Number of bytes written so far
+    fn len(&self) -> u64 {
+        self.len
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        match self.file.as_mut() {
+            Some(file) => file.write_all(buf)?,
+            None => return Err(std::io::ErrorKind::BrokenPipe.into()),
+        }
+        self.len += buf.len() as u64;
+        Ok(())
+    }
+
+    /// Discards everything written so far
+    fn truncate(&mut self) -> std::io::Result<()> {
+        use std::io::Seek;
+
+        if let Some(file) = self.file.as_mut() {
+            file.set_len(0)?;
+            file.seek(std::io::SeekFrom::Start(0))?;
+        }
+        self.len = 0;
+        Ok(())
+    }
+
+    /// Flushes the file and moves it to its final location, if that fails
+    /// the file is still removed
+    fn persist(mut self, target: &Path) -> Result<(), anyhow::Error> {
+        if let Some(file) = self.file.take() {
+            file.sync_all()
+                .map_err(|e| anyhow::anyhow!("{e}"))
+                .context(anyhow::anyhow!("{}", self.path.display()))?;
+        }
+        std::fs::rename(&self.path, target)
             .map_err(|e| anyhow::anyhow!("{e}"))
             .context(anyhow::anyhow!(
-                "install_webc_package: failed to write chunk to {}",
-                webc_path.display()
+                "could not move {} to {}",
+                self.path.display(),
+                target.display()
             ))?;
+        self.persisted = true;
+        Ok(())
     }
+}
 
-    Ok(())
+impl Drop for PartialDownload {
+    fn drop(&mut self) {
+        // The file has to be closed before it can be removed on Windows
+        drop(self.file.take());
+        if !self.persisted {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
 }
 
 /// Returns a list of all installed webc packages
@@ -793,6 +974,9 @@ fn get_all_installed_webc_packages_inner(_test_name: &str) -> Vec<RemoteWebcInfo
 
     read_dir
         .filter_map(|r| Some(r.ok()?.path()))
+        .filter(|path| {
+            path.extension().and_then(|e| e.to_str()) != Some(PARTIAL_DOWNLOAD_EXTENSION)
+        })
         .filter_map(|path| {
             webc::WebCMmap::parse(
                 path,
@@ -815,6 +999,221 @@ fn get_all_installed_webc_packages_inner(_test_name: &str) -> Vec<RemoteWebcInfo
         .collect()
 }
 
+/// Result of [`gc_installed_packages`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PackageGcStats {
+    /// Packages (checkout directories or `.webc` files) that were removed
+    pub removed: Vec<PathBuf>,
+    /// Number of bytes freed
+    pub freed: u64,
+    /// Number of bytes still used by installed packages
+    pub remaining: u64,
+}
+
+/// Removes installed packages (both `.tar.gz` checkouts and `.webc` files)
+/// until they take up at most `max_size` bytes on disk, starting with the
+/// packages that were used (or installed) the longest time ago.
+///
+/// Packages that are being downloaded and packages that are currently in use
+/// (see [`use_installed_package`]) are never removed.
+pub fn gc_installed_packages(
+    #[cfg(test)] test_name: &str,
+    max_size: u64,
+) -> Result<PackageGcStats, anyhow::Error> {
+    #[cfg(test)]
+    let dirs = [get_checkouts_dir(test_name), get_webc_dir(test_name)];
+    #[cfg(not(test))]
+    let dirs = [get_checkouts_dir(), get_webc_dir()];
+
+    let mut packages = Vec::new();
+    for dir in dirs.iter().flatten() {
+        packages.extend(installed_package_entries(dir)?);
+    }
+    gc_package_entries(packages, max_size)
+}
+
+/// Marks an installed package as being in use for as long as the guard
+/// is alive, see [`use_installed_package`]
+#[derive(Debug)]
+pub struct PackageInUse {
+    lock: PathBuf,
+}
+
+impl Drop for PackageInUse {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.lock);
+    }
+}
+
+/// Records that the installed package at `path` (a checkout directory or a
+/// `.webc` file) is being used: updates its last-used timestamp and takes
+/// a lock that keeps [`gc_installed_packages`] from removing it until the
+/// returned guard is dropped.
+///
+/// Returns `None` if `path` is not an installed package.
+pub fn use_installed_package(
+    #[cfg(test)] test_name: &str,
+    path: &Path,
+) -> Result<Option<PackageInUse>, anyhow::Error> {
+    #[cfg(test)]
+    let dirs = [get_checkouts_dir(test_name), get_webc_dir(test_name)];
+    #[cfg(not(test))]
+    let dirs = [get_checkouts_dir(), get_webc_dir()];
+
+    let installed = dirs.iter().flatten().any(|dir| path.parent() == Some(dir));
+    let usage_dir = match package_usage_dir(path) {
+        Some(s) if installed => s,
+        _ => return Ok(None),
+    };
+    std::fs::create_dir_all(&usage_dir)
+        .with_context(|| anyhow::anyhow!("could not create {}", usage_dir.display()))?;
+    let last_used = usage_dir.join(LAST_USED_FILE);
+    if !last_used.exists() {
+        std::fs::write(&last_used, [])?;
+    }
+    #[cfg(not(target_os = "wasi"))]
+    filetime::set_file_mtime(&last_used, filetime::FileTime::now())?;
+
+    // one lock file per user, so that every run holds its own reference
+    static NEXT_LOCK: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    let n = NEXT_LOCK.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    let lock = usage_dir.join(format!(
+        "{}-{n}.{PACKAGE_LOCK_EXTENSION}",
+        std::process::id()
+    ));
+    std::fs::write(&lock, [])
+        .with_context(|| anyhow::anyhow!("could not create {}", lock.display()))?;
+    Ok(Some(PackageInUse { lock }))
+}
+
+const PACKAGE_USAGE_DIR: &str = "package-usage";
+const LAST_USED_FILE: &str = "last-used";
+const PACKAGE_LOCK_EXTENSION: &str = "lock";
+
+/// Directory holding the last-used timestamp and the locks of an installed
+/// package, `{wasmer_dir}/package-usage/{checkouts,webc}/{package}`
+fn package_usage_dir(package: &Path) -> Option<PathBuf> {
+    let dir = package.parent()?;
+    Some(
+        dir.parent()?
+            .join(PACKAGE_USAGE_DIR)
+            .join(dir.file_name()?)
+            .join(package.file_name()?),
+    )
+}
+
+/// Whether a lock file in a package usage directory belongs to a process
+/// that is still running. Lock files left behind by crashed processes
+/// can only be detected on Linux, elsewhere they keep the package alive
+/// until they are deleted by hand.
+fn is_live_package_lock(lock: &Path) -> bool {
+    if lock.extension().and_then(|e| e.to_str()) != Some(PACKAGE_LOCK_EXTENSION) {
+        return false;
+    }
+    #[cfg(target_os = "linux")]
+    {
+        let pid = lock
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.split('-').next());
+        if let Some(pid) = pid {
+            return Path::new("/proc").join(pid).exists();
+        }
+    }
+    true
+}
+
+/// An installed package as seen by the garbage collector
+#[derive(Debug)]
+struct InstalledPackageEntry {
+    path: PathBuf,
+    size: u64,
+    last_used: std::time::SystemTime,
+    in_use: bool,
+}
+
+/// Lists the installed packages in a checkouts or webc directory
+fn installed_package_entries(dir: &Path) -> Result<Vec<InstalledPackageEntry>, anyhow::Error> {
+    let read_dir = match std::fs::read_dir(dir) {
+        Ok(s) => s,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(anyhow::anyhow!("could not read {}: {e}", dir.display())),
+    };
+
+    let mut entries = Vec::new();
+    for entry in read_dir {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) == Some(PARTIAL_DOWNLOAD_EXTENSION) {
+            continue;
+        }
+        let metadata = std::fs::metadata(&path)
+            .with_context(|| anyhow::anyhow!("could not get metadata of {}", path.display()))?;
+        let (size, installed) = if metadata.is_dir() {
+            // install_package touches the wapm.toml of a checkout when it
+            // (re-)installs the package
+            let installed = std::fs::metadata(path.join("wapm.toml"))
+                .and_then(|m| m.modified())
+                .or_else(|_| metadata.modified())?;
+            (fs_extra::dir::get_size(&path)?, installed)
+        } else {
+            (metadata.len(), metadata.modified()?)
+        };
+
+        let usage_dir = package_usage_dir(&path);
+        let last_used = usage_dir
+            .as_ref()
+            .and_then(|dir| std::fs::metadata(dir.join(LAST_USED_FILE)).ok())
+            .and_then(|m| m.modified().ok())
+            .map_or(installed, |used| used.max(installed));
+        let in_use = usage_dir
+            .and_then(|dir| std::fs::read_dir(dir).ok())
+            .map_or(false, |mut locks| {
+                locks.any(|e| e.map_or(false, |e| is_live_package_lock(&e.path())))
+            });
+
+        entries.push(InstalledPackageEntry {
+            path,
+            size,
+            last_used,
+            in_use,
+        });
+    }
+    Ok(entries)
+}
+
+fn gc_package_entries(
+    mut packages: Vec<InstalledPackageEntry>,
+    max_size: u64,
+) -> Result<PackageGcStats, anyhow::Error> {
+    packages.sort_by_key(|p| p.last_used);
+
+    let mut stats = PackageGcStats {
+        remaining: packages.iter().map(|p| p.size).sum(),
+        ..Default::default()
+    };
+    for package in packages {
+        if stats.remaining <= max_size {
+            break;
+        }
+        if package.in_use {
+            continue;
+        }
+        let result = if package.path.is_dir() {
+            std::fs::remove_dir_all(&package.path)
+        } else {
+            std::fs::remove_file(&package.path)
+        };
+        result.with_context(|| anyhow::anyhow!("could not remove {}", package.path.display()))?;
+        if let Some(usage_dir) = package_usage_dir(&package.path) {
+            let _ = std::fs::remove_dir_all(usage_dir);
+        }
+        stats.remaining -= package.size;
+        stats.freed += package.size;
+        stats.removed.push(package.path);
+    }
+    Ok(stats)
+}
+
 /// The checksum of the webc file has a bunch of zeros at the end
 /// (it's currently encoded that way in the webc format). This function
 /// strips the zeros because otherwise the filename would become too long.
@@ -1038,6 +1437,260 @@ fn test_install_package() {
     println!("ok, done");
 }
 
+#[test]
+fn test_download_resumes_after_interruption() {
+    use std::io::BufRead;
+    use std::net::TcpListener;
+
+    const BODY: &[u8] = b"\0webc-0123456789abcdefghijklmnopqrstuvwxyz";
+    let split = BODY.len() / 2;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = Url::parse(&format!(
+        "http://{}/package.webc",
+        listener.local_addr().unwrap()
+    ))
+    .unwrap();
+
+    let server = std::thread::spawn(move || {
+        let mut range_headers = Vec::new();
+        for attempt in 0..2 {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+            let mut range = None;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = line.to_lowercase().strip_prefix("range:") {
+                    range = Some(value.trim().to_string());
+                }
+            }
+            range_headers.push(range);
+
+            let mut stream = stream;
+            if attempt == 0 {
+                // Announce the whole body, send half of it, then hang up
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n",
+                    BODY.len()
+                )
+                .unwrap();
+                stream.write_all(&BODY[..split]).unwrap();
+            } else {
+                write!(
+                    stream,
+                    "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    BODY.len() - split
+                )
+                .unwrap();
+                stream.write_all(&BODY[split..]).unwrap();
+            }
+        }
+        range_headers
+    });
+
+    let tempdir = tempdir::TempDir::new("test_download_resumes").unwrap();
+    let target_path = tempdir.path().join("package.webc");
+    let mut partial = PartialDownload::create(tempdir.path().join("package.webc.partial")).unwrap();
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(download_with_retries(
+            &reqwest::Client::new(),
+            &url,
+            &mut partial,
+        ))
+        .unwrap();
+    partial.persist(&target_path).unwrap();
+
+    let range_headers = server.join().unwrap();
+    assert_eq!(range_headers, vec![None, Some(format!("bytes={split}-"))]);
+    assert_eq!(std::fs::read(&target_path).unwrap(), BODY);
+}
+
+#[test]
+fn test_cancelled_download_is_not_cached() {
+    use futures_util::future::{select, Either};
+    use std::io::BufRead;
+    use std::net::TcpListener;
+
+    const TEST_NAME: &str = "test_cancelled_download_is_not_cached";
+    const CHECKSUM: &str = "0123456789abcdef";
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = Url::parse(&format!(
+        "http://{}/package.webc",
+        listener.local_addr().unwrap()
+    ))
+    .unwrap();
+
+    let (sent_tx, sent_rx) = std::sync::mpsc::channel();
+    let server = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line.trim().is_empty() {
+                break;
+            }
+        }
+
+        // Announce a large body and only send the start of it
+        let mut stream = stream;
+        write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: 1000000\r\n\r\n").unwrap();
+        stream.write_all(b"\0webc").unwrap();
+        sent_tx.send(()).unwrap();
+
+        // Returns whether the client closed the connection
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        let mut buf = [0; 16];
+        match reader.read(&mut buf) {
+            Ok(0) => true,
+            Ok(_) => false,
+            Err(e) => !matches!(
+                e.kind(),
+                std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+            ),
+        }
+    });
+
+    let webc_dir = get_webc_dir(TEST_NAME).unwrap();
+    let _ = std::fs::remove_dir_all(&webc_dir);
+    let partial_path = webc_dir.join(format!("{CHECKSUM}.{PARTIAL_DOWNLOAD_EXTENSION}"));
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let download = Box::pin(install_webc_package_inner(TEST_NAME, &url, CHECKSUM));
+        let sent = tokio::task::spawn_blocking(move || sent_rx.recv().unwrap());
+        match select(download, sent).await {
+            Either::Left((result, _)) => panic!("the download completed: {result:?}"),
+            Either::Right((_, download)) => {
+                assert!(partial_path.exists());
+                // Cancels the download in the middle of the body
+                drop(download);
+            }
+        }
+    });
+    drop(runtime);
+
+    assert!(server.join().unwrap(), "the connection was left open");
+    assert!(!partial_path.exists());
+    assert!(!webc_dir.join(CHECKSUM).exists());
+}
+
+#[test]
+fn test_gc_removes_least_recently_used_packages() {
+    let tempdir = tempdir::TempDir::new("test_gc_packages").unwrap();
+    let checkouts = tempdir.path().join("checkouts");
+    let webc = tempdir.path().join("webc");
+    std::fs::create_dir_all(&checkouts).unwrap();
+    std::fs::create_dir_all(&webc).unwrap();
+
+    let set_age = |path: &Path, seconds_ago: i64| {
+        let time = filetime::FileTime::from_unix_time(
+            filetime::FileTime::now().unix_seconds() - seconds_ago,
+            0,
+        );
+        filetime::set_file_mtime(path, time).unwrap();
+    };
+    let usage_dir = |path: &Path| {
+        let dir = package_usage_dir(path).unwrap();
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    };
+
+    // oldest: a 100 byte checkout that is being run right now
+    let locked_checkout = checkouts.join("aaaa@1.0.0");
+    std::fs::create_dir_all(&locked_checkout).unwrap();
+    std::fs::write(locked_checkout.join("wapm.toml"), [0; 40]).unwrap();
+    std::fs::write(locked_checkout.join("module.wasm"), [0; 60]).unwrap();
+    set_age(&locked_checkout.join("wapm.toml"), 400);
+    let lock = usage_dir(&locked_checkout).join(format!("{}-0.lock", std::process::id()));
+    std::fs::write(lock, []).unwrap();
+
+    // an old 100 byte webc that was used recently
+    let used_webc = webc.join("0123");
+    std::fs::write(&used_webc, [0; 100]).unwrap();
+    set_age(&used_webc, 300);
+    let last_used = usage_dir(&used_webc).join(LAST_USED_FILE);
+    std::fs::write(&last_used, []).unwrap();
+    set_age(&last_used, 10);
+
+    // a 100 byte webc and a 100 byte checkout that were never used
+    let old_webc = webc.join("4567");
+    std::fs::write(&old_webc, [0; 100]).unwrap();
+    set_age(&old_webc, 200);
+    let old_checkout = checkouts.join("bbbb@1.0.0");
+    std::fs::create_dir_all(&old_checkout).unwrap();
+    std::fs::write(old_checkout.join("wapm.toml"), [0; 100]).unwrap();
+    set_age(&old_checkout.join("wapm.toml"), 100);
+
+    // a download in progress
+    let downloading = webc.join("89ab.partial");
+    std::fs::write(&downloading, [0; 1000]).unwrap();
+    set_age(&downloading, 1000);
+
+    let mut packages = installed_package_entries(&checkouts).unwrap();
+    packages.extend(installed_package_entries(&webc).unwrap());
+    let stats = gc_package_entries(packages, 250).unwrap();
+
+    assert_eq!(stats.removed, vec![old_webc.clone(), old_checkout.clone()]);
+    assert_eq!(stats.freed, 200);
+    assert_eq!(stats.remaining, 200);
+    assert!(!old_webc.exists());
+    assert!(!old_checkout.exists());
+    assert!(!package_usage_dir(&old_webc).unwrap().exists());
+    assert!(locked_checkout.exists());
+    assert!(used_webc.exists());
+    assert!(downloading.exists());
+}
+
+#[test]
+fn test_partial_download_is_removed_unless_persisted() {
+    let tempdir = tempdir::TempDir::new("test_partial_download").unwrap();
+    let partial_path = tempdir.path().join("checksum.partial");
+    let target_path = tempdir.path().join("checksum");
+
+    let mut partial = PartialDownload::create(partial_path.clone()).unwrap();
+    partial.write_all(b"incomplete").unwrap();
+    assert!(partial_path.exists());
+    drop(partial);
+    assert!(!partial_path.exists());
+    assert!(!target_path.exists());
+
+    let mut partial = PartialDownload::create(partial_path.clone()).unwrap();
+    partial.write_all(b"complete").unwrap();
+    partial.persist(&target_path).unwrap();
+    assert!(!partial_path.exists());
+    assert_eq!(std::fs::read(&target_path).unwrap(), b"complete");
+}
+
+#[test]
+fn test_partial_download_is_removed_if_persisting_fails() {
+    let tempdir = tempdir::TempDir::new("test_partial_download").unwrap();
+    let partial_path = tempdir.path().join("checksum.partial");
+    // The directory of the target doesn't exist, so it can't be moved there
+    let target_path = tempdir.path().join("missing").join("checksum");
+
+    let mut partial = PartialDownload::create(partial_path.clone()).unwrap();
+    partial.write_all(b"complete").unwrap();
+    assert!(partial.persist(&target_path).is_err());
+    assert!(!partial_path.exists());
+    assert!(!target_path.exists());
+}
+
 /// A library that exposes bindings to a WAPM package.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Bindings {
@@ -15,6 +15,7 @@ compile_error!("At least the `host-fs` or the `mem-fs` feature must be enabled.
 pub mod host_fs;
 #[cfg(feature = "mem-fs")]
 pub mod mem_fs;
+pub mod null_file;
 #[cfg(feature = "static-fs")]
 pub mod static_fs;
 #[cfg(feature = "webc-fs")]
@@ -285,7 +286,7 @@ pub enum StdioMode {
     Piped,
     /// Stdio will inherit the file handlers of its parent
     Inherit,
-    /// Stdio will be dropped
+    /// Stdio will be dropped, as if it were a [`NullFile`](null_file::NullFile)
     Null,
     /// Stdio will be sent to the log handler
     Log,
@@ -0,0 +1,86 @@
+//! A file that discards everything written to it and is always empty,
+//! like `/dev/null`.
+
+use crate::{FileDescriptor, Result, VirtualFile};
+use std::io::{self, Read, Seek, Write};
+
+/// A `VirtualFile` with the semantics of `/dev/null`: writes succeed and
+/// are thrown away, and reads are always at the end of the file.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullFile;
+
+impl Read for NullFile {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Ok(0)
+    }
+}
+
+impl Seek for NullFile {
+    fn seek(&mut self, _pos: io::SeekFrom) -> io::Result<u64> {
+        Ok(0)
+    }
+}
+
+impl Write for NullFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl VirtualFile for NullFile {
+    fn last_accessed(&self) -> u64 {
+        0
+    }
+
+    fn last_modified(&self) -> u64 {
+        0
+    }
+
+    fn created_time(&self) -> u64 {
+        0
+    }
+
+    fn size(&self) -> u64 {
+        0
+    }
+
+    fn set_len(&mut self, _new_size: u64) -> Result<()> {
+        Ok(())
+    }
+
+    fn unlink(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn bytes_available(&self) -> Result<usize> {
+        Ok(0)
+    }
+
+    fn get_fd(&self) -> Option<FileDescriptor> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_writes_are_discarded() {
+        let mut file = NullFile::default();
+        assert_eq!(file.write(b"hello").unwrap(), 5);
+        file.write_all(b"world").unwrap();
+        file.flush().unwrap();
+        assert_eq!(file.size(), 0);
+
+        let mut buf = [0u8; 8];
+        assert_eq!(file.read(&mut buf).unwrap(), 0);
+        let mut contents = Vec::new();
+        assert_eq!(file.read_to_end(&mut contents).unwrap(), 0);
+        assert!(contents.is_empty());
+    }
+}
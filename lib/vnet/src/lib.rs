@@ -13,6 +13,10 @@ use thiserror::Error;
 pub use bytes::Bytes;
 pub use bytes::BytesMut;
 
+mod port_range;
+
+pub use port_range::PortRangeNetworking;
+
 pub type Result<T> = std::result::Result<T, NetworkError>;
 
 /// Socket descriptors are also file descriptors and so
@@ -35,6 +39,40 @@ pub struct IpRoute {
     pub expires_at: Option<Duration>,
 }
 
+/// Represents a network interface and the IP addresses assigned to it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NetworkInterface {
+    /// Name of the interface (e.g. `lo` or `eth0`)
+    pub name: String,
+    /// IP addresses (and their netmasks) assigned to the interface
+    pub addrs: Vec<IpCidr>,
+    /// Indicates if the interface is up
+    pub up: bool,
+    /// Indicates if this is a loopback interface
+    pub loopback: bool,
+}
+
+impl NetworkInterface {
+    /// The loopback interface with its usual IPv4 and IPv6 addresses
+    pub fn loopback() -> Self {
+        Self {
+            name: "lo".to_string(),
+            addrs: vec![
+                IpCidr {
+                    ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                    prefix: 8,
+                },
+                IpCidr {
+                    ip: IpAddr::V6(Ipv6Addr::LOCALHOST),
+                    prefix: 128,
+                },
+            ],
+            up: true,
+            loopback: true,
+        }
+    }
+}
+
 /// An implementation of virtual networking
 pub trait VirtualNetworking: fmt::Debug + Send + Sync + 'static {
     /// Establishes a web socket connection
@@ -100,6 +138,13 @@ pub trait VirtualNetworking: fmt::Debug + Send + Sync + 'static {
     /// Lists all the routes defined in the routing table for this interface
     fn route_list(&self) -> Result<Vec<IpRoute>>;
 
+    /// Lists the network interfaces that sockets can be bound to, along
+    /// with their addresses. Implementations that don't expose interfaces
+    /// only report the loopback interface.
+    fn interfaces(&self) -> Result<Vec<NetworkInterface>> {
+        Ok(vec![NetworkInterface::loopback()])
+    }
+
     /// Creates a low level socket that can read and write Ethernet packets
     /// directly to the interface
     fn bind_raw(&self) -> Result<Box<dyn VirtualRawSocket + Sync>>;
@@ -137,6 +182,19 @@ pub trait VirtualNetworking: fmt::Debug + Send + Sync + 'static {
         timeout: Option<Duration>,
     ) -> Result<Box<dyn VirtualTcpSocket + Sync>>;
 
+    /// Opens a TCP connection to a particular destination IP address and
+    /// port and secures it with TLS, the certificate of the peer is verified
+    /// against `server_name` which is also sent to the peer (SNI)
+    fn connect_tls(
+        &self,
+        _addr: SocketAddr,
+        _peer: SocketAddr,
+        _server_name: &str,
+        _timeout: Option<Duration>,
+    ) -> Result<Box<dyn VirtualTcpSocket + Sync>> {
+        Err(NetworkError::Unsupported)
+    }
+
     /// Performs DNS resolution for a specific hostname
     fn resolve(
         &self,
@@ -254,6 +312,12 @@ pub trait VirtualWebSocket: fmt::Debug + Send + Sync + 'static {
     /// Sends out a datagram or stream of bytes on this socket
     fn send(&mut self, data: Bytes) -> Result<usize>;
 
+    /// Sends out a text message on this socket, implementations that only
+    /// send binary messages do not support it
+    fn send_text(&mut self, _text: String) -> Result<usize> {
+        Err(NetworkError::Unsupported)
+    }
+
     /// FLushes all the datagrams
     fn flush(&mut self) -> Result<()>;
 
@@ -337,6 +401,26 @@ pub enum TimeType {
     Linger,
 }
 
+/// Statistics of a TCP connection as tracked by the host, any value that
+/// the host does not track is left as `None`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TcpStats {
+    /// Smoothed estimate of the round trip time
+    pub rtt: Option<Duration>,
+    /// Variance of the round trip time estimate
+    pub rtt_var: Option<Duration>,
+    /// Total number of segments that had to be retransmitted
+    pub retransmits: Option<u64>,
+    /// Number of bytes that were sent and acknowledged by the peer
+    pub bytes_sent: Option<u64>,
+    /// Number of bytes that were received from the peer
+    pub bytes_received: Option<u64>,
+    /// Number of segments that were sent
+    pub segments_sent: Option<u64>,
+    /// Number of segments that were received
+    pub segments_received: Option<u64>,
+}
+
 pub trait VirtualTcpSocket: VirtualConnectedSocket + fmt::Debug + Send + Sync + 'static {
     /// Sets the timeout for a specific action on the socket
     fn set_opt_time(&mut self, ty: TimeType, timeout: Option<Duration>) -> Result<()>;
@@ -382,6 +466,13 @@ pub trait VirtualTcpSocket: VirtualConnectedSocket + fmt::Debug + Send + Sync +
     /// Shuts down either the READER or WRITER sides of the socket
     /// connection.
     fn shutdown(&mut self, how: Shutdown) -> Result<()>;
+
+    /// Returns the statistics of this connection (e.g. round trip time
+    /// and retransmits), implementations that do not track them return
+    /// empty statistics
+    fn stats(&self) -> Result<TcpStats> {
+        Ok(TcpStats::default())
+    }
 }
 
 pub trait VirtualUdpSocket:
@@ -0,0 +1,215 @@
+use std::net::{IpAddr, SocketAddr};
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::{
+    IpCidr, IpRoute, NetworkError, NetworkInterface, Result, SocketHttpRequest, StreamSecurity,
+    VirtualIcmpSocket, VirtualNetworking, VirtualRawSocket, VirtualTcpListener, VirtualTcpSocket,
+    VirtualUdpSocket, VirtualWebSocket,
+};
+
+/// Networking implementation that confines the local ports of the sockets
+/// created by another implementation to a range of ports (e.g. so that
+/// firewall rules can be written for them)
+///
+/// Sockets that are bound to port zero, or connected without being bound,
+/// are given a free port from the range. Binding to a port outside of the
+/// range, or running out of free ports, fails with
+/// [`NetworkError::PermissionDenied`].
+#[derive(Debug)]
+pub struct PortRangeNetworking<N> {
+    inner: N,
+    ports: RangeInclusive<u16>,
+    next: AtomicUsize,
+}
+
+impl<N> PortRangeNetworking<N>
+where
+    N: VirtualNetworking,
+{
+    pub fn new(inner: N, ports: RangeInclusive<u16>) -> Self {
+        Self {
+            inner,
+            ports,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Range of local ports sockets may use
+    pub fn ports(&self) -> &RangeInclusive<u16> {
+        &self.ports
+    }
+
+    /// Runs `bind` with `addr` if its port is in the range, or with the
+    /// ports of the range that are not in use yet if `addr` has port zero
+    fn with_port<T>(
+        &self,
+        addr: SocketAddr,
+        mut bind: impl FnMut(SocketAddr) -> Result<T>,
+    ) -> Result<T> {
+        if addr.port() != 0 {
+            if !self.ports.contains(&addr.port()) {
+                return Err(NetworkError::PermissionDenied);
+            }
+            return bind(addr);
+        }
+
+        let start = *self.ports.start() as usize;
+        let len = (*self.ports.end() as usize + 1).saturating_sub(start);
+        // Spread the sockets over the range instead of always probing the
+        // same ports first
+        let offset = self.next.fetch_add(1, Ordering::Relaxed);
+        for i in 0..len {
+            let port = (start + (offset + i) % len) as u16;
+            match bind(SocketAddr::new(addr.ip(), port)) {
+                Err(NetworkError::AddressInUse) => continue,
+                ret => return ret,
+            }
+        }
+        Err(NetworkError::PermissionDenied)
+    }
+}
+
+impl<N> VirtualNetworking for PortRangeNetworking<N>
+where
+    N: VirtualNetworking,
+{
+    fn ws_connect(&self, url: &str) -> Result<Box<dyn VirtualWebSocket + Sync>> {
+        self.inner.ws_connect(url)
+    }
+
+    fn http_request(
+        &self,
+        url: &str,
+        method: &str,
+        headers: &str,
+        gzip: bool,
+    ) -> Result<SocketHttpRequest> {
+        self.inner.http_request(url, method, headers, gzip)
+    }
+
+    fn bridge(&self, network: &str, access_token: &str, security: StreamSecurity) -> Result<()> {
+        self.inner.bridge(network, access_token, security)
+    }
+
+    fn unbridge(&self) -> Result<()> {
+        self.inner.unbridge()
+    }
+
+    fn dhcp_acquire(&self) -> Result<Vec<IpAddr>> {
+        self.inner.dhcp_acquire()
+    }
+
+    fn ip_add(&self, ip: IpAddr, prefix: u8) -> Result<()> {
+        self.inner.ip_add(ip, prefix)
+    }
+
+    fn ip_remove(&self, ip: IpAddr) -> Result<()> {
+        self.inner.ip_remove(ip)
+    }
+
+    fn ip_clear(&self) -> Result<()> {
+        self.inner.ip_clear()
+    }
+
+    fn ip_list(&self) -> Result<Vec<IpCidr>> {
+        self.inner.ip_list()
+    }
+
+    fn mac(&self) -> Result<[u8; 6]> {
+        self.inner.mac()
+    }
+
+    fn gateway_set(&self, ip: IpAddr) -> Result<()> {
+        self.inner.gateway_set(ip)
+    }
+
+    fn route_add(
+        &self,
+        cidr: IpCidr,
+        via_router: IpAddr,
+        preferred_until: Option<Duration>,
+        expires_at: Option<Duration>,
+    ) -> Result<()> {
+        self.inner
+            .route_add(cidr, via_router, preferred_until, expires_at)
+    }
+
+    fn route_remove(&self, cidr: IpAddr) -> Result<()> {
+        self.inner.route_remove(cidr)
+    }
+
+    fn route_clear(&self) -> Result<()> {
+        self.inner.route_clear()
+    }
+
+    fn route_list(&self) -> Result<Vec<IpRoute>> {
+        self.inner.route_list()
+    }
+
+    fn interfaces(&self) -> Result<Vec<NetworkInterface>> {
+        self.inner.interfaces()
+    }
+
+    fn bind_raw(&self) -> Result<Box<dyn VirtualRawSocket + Sync>> {
+        self.inner.bind_raw()
+    }
+
+    fn listen_tcp(
+        &self,
+        addr: SocketAddr,
+        only_v6: bool,
+        reuse_port: bool,
+        reuse_addr: bool,
+    ) -> Result<Box<dyn VirtualTcpListener + Sync>> {
+        self.with_port(addr, |addr| {
+            self.inner.listen_tcp(addr, only_v6, reuse_port, reuse_addr)
+        })
+    }
+
+    fn bind_udp(
+        &self,
+        addr: SocketAddr,
+        reuse_port: bool,
+        reuse_addr: bool,
+    ) -> Result<Box<dyn VirtualUdpSocket + Sync>> {
+        self.with_port(addr, |addr| {
+            self.inner.bind_udp(addr, reuse_port, reuse_addr)
+        })
+    }
+
+    fn bind_icmp(&self, addr: IpAddr) -> Result<Box<dyn VirtualIcmpSocket + Sync>> {
+        self.inner.bind_icmp(addr)
+    }
+
+    fn connect_tcp(
+        &self,
+        addr: SocketAddr,
+        peer: SocketAddr,
+        timeout: Option<Duration>,
+    ) -> Result<Box<dyn VirtualTcpSocket + Sync>> {
+        self.with_port(addr, |addr| self.inner.connect_tcp(addr, peer, timeout))
+    }
+
+    fn connect_tls(
+        &self,
+        addr: SocketAddr,
+        peer: SocketAddr,
+        server_name: &str,
+        timeout: Option<Duration>,
+    ) -> Result<Box<dyn VirtualTcpSocket + Sync>> {
+        self.with_port(addr, |addr| {
+            self.inner.connect_tls(addr, peer, server_name, timeout)
+        })
+    }
+
+    fn resolve(
+        &self,
+        host: &str,
+        port: Option<u16>,
+        dns_server: Option<IpAddr>,
+    ) -> Result<Vec<IpAddr>> {
+        self.inner.resolve(host, port, dns_server)
+    }
+}
@@ -0,0 +1,72 @@
+//! Enumerates the network interfaces of the host (`getifaddrs`).
+
+use std::ffi::CStr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use wasmer_vnet::{io_err_into_net_error, IpCidr, NetworkInterface, Result};
+
+pub(crate) fn interfaces() -> Result<Vec<NetworkInterface>> {
+    let mut ifaddrs: *mut libc::ifaddrs = std::ptr::null_mut();
+    if unsafe { libc::getifaddrs(&mut ifaddrs) } != 0 {
+        return Err(io_err_into_net_error(std::io::Error::last_os_error()));
+    }
+
+    // Every address of an interface is a separate entry in the list
+    let mut interfaces: Vec<NetworkInterface> = Vec::new();
+    let mut cur = ifaddrs;
+    while let Some(entry) = unsafe { cur.as_ref() } {
+        cur = entry.ifa_next;
+
+        let name = unsafe { CStr::from_ptr(entry.ifa_name) }
+            .to_string_lossy()
+            .into_owned();
+        let index = match interfaces.iter().position(|iface| iface.name == name) {
+            Some(index) => index,
+            None => {
+                let flags = entry.ifa_flags as libc::c_int;
+                interfaces.push(NetworkInterface {
+                    name,
+                    addrs: Vec::new(),
+                    up: flags & libc::IFF_UP != 0,
+                    loopback: flags & libc::IFF_LOOPBACK != 0,
+                });
+                interfaces.len() - 1
+            }
+        };
+
+        let ip = match unsafe { read_ip(entry.ifa_addr) } {
+            Some(ip) => ip,
+            None => continue,
+        };
+        let prefix = unsafe { read_ip(entry.ifa_netmask) }
+            .map(|mask| match mask {
+                IpAddr::V4(mask) => u32::from(mask).count_ones() as u8,
+                IpAddr::V6(mask) => u128::from(mask).count_ones() as u8,
+            })
+            .unwrap_or(match ip {
+                IpAddr::V4(_) => 32,
+                IpAddr::V6(_) => 128,
+            });
+        interfaces[index].addrs.push(IpCidr { ip, prefix });
+    }
+
+    unsafe { libc::freeifaddrs(ifaddrs) };
+    Ok(interfaces)
+}
+
+/// Reads the IP address out of a socket address, if it is one
+unsafe fn read_ip(addr: *const libc::sockaddr) -> Option<IpAddr> {
+    let family = addr.as_ref()?.sa_family as libc::c_int;
+    match family {
+        libc::AF_INET => {
+            let addr = &*(addr as *const libc::sockaddr_in);
+            Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(
+                addr.sin_addr.s_addr,
+            ))))
+        }
+        libc::AF_INET6 => {
+            let addr = &*(addr as *const libc::sockaddr_in6);
+            Some(IpAddr::V6(Ipv6Addr::from(addr.sin6_addr.s6_addr)))
+        }
+        _ => None,
+    }
+}
@@ -1,10 +1,15 @@
 #![allow(unused_variables)]
 use bytes::{Bytes, BytesMut};
+use socket2::{Domain, Protocol, SockRef, Socket, Type};
 use std::io::{Read, Write};
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr};
 use std::time::Duration;
 #[allow(unused_imports, dead_code)]
 use tracing::{debug, error, info, trace, warn};
+#[cfg(unix)]
+use wasmer_vnet::NetworkInterface;
+#[cfg(target_os = "linux")]
+use wasmer_vnet::TcpStats;
 use wasmer_vnet::{
     io_err_into_net_error, IpCidr, IpRoute, NetworkError, Result, SocketHttpRequest, SocketReceive,
     SocketReceiveFrom, SocketStatus, StreamSecurity, TimeType, VirtualConnectedSocket,
@@ -12,13 +17,34 @@ use wasmer_vnet::{
     VirtualSocket, VirtualTcpListener, VirtualTcpSocket, VirtualUdpSocket, VirtualWebSocket,
 };
 
+#[cfg(unix)]
+mod interfaces;
+#[cfg(target_os = "linux")]
+mod tcp_info;
+#[cfg(feature = "tls")]
+mod tls;
+#[cfg(feature = "ws")]
+mod ws;
+
+#[cfg(feature = "tls")]
+pub use tls::{TlsNetworking, TlsTcpSocket};
+#[cfg(feature = "ws")]
+pub use ws::LocalWebSocket;
+
 #[derive(Debug, Default)]
 pub struct LocalNetworking {}
 
 #[allow(unused_variables)]
 impl VirtualNetworking for LocalNetworking {
     fn ws_connect(&self, url: &str) -> Result<Box<dyn VirtualWebSocket + Sync>> {
-        Err(NetworkError::Unsupported)
+        #[cfg(feature = "ws")]
+        {
+            Ok(Box::new(LocalWebSocket::connect(url)?))
+        }
+        #[cfg(not(feature = "ws"))]
+        {
+            Err(NetworkError::Unsupported)
+        }
     }
 
     fn http_request(
@@ -55,6 +81,15 @@ impl VirtualNetworking for LocalNetworking {
         Err(NetworkError::Unsupported)
     }
 
+    #[cfg(unix)]
+    fn ip_list(&self) -> Result<Vec<IpCidr>> {
+        Ok(interfaces::interfaces()?
+            .into_iter()
+            .flat_map(|iface| iface.addrs)
+            .collect())
+    }
+
+    #[cfg(not(unix))]
     fn ip_list(&self) -> Result<Vec<IpCidr>> {
         Err(NetworkError::Unsupported)
     }
@@ -89,6 +124,11 @@ impl VirtualNetworking for LocalNetworking {
         Err(NetworkError::Unsupported)
     }
 
+    #[cfg(unix)]
+    fn interfaces(&self) -> Result<Vec<NetworkInterface>> {
+        interfaces::interfaces()
+    }
+
     fn bind_raw(&self) -> Result<Box<dyn VirtualRawSocket + Sync>> {
         Err(NetworkError::Unsupported)
     }
@@ -127,14 +167,28 @@ impl VirtualNetworking for LocalNetworking {
 
     fn connect_tcp(
         &self,
-        _addr: SocketAddr,
+        addr: SocketAddr,
         peer: SocketAddr,
         timeout: Option<Duration>,
     ) -> Result<Box<dyn VirtualTcpSocket + Sync>> {
-        let stream = if let Some(timeout) = timeout {
-            std::net::TcpStream::connect_timeout(&peer, timeout)
+        let stream = if addr.port() == 0 && addr.ip().is_unspecified() {
+            if let Some(timeout) = timeout {
+                std::net::TcpStream::connect_timeout(&peer, timeout)
+            } else {
+                std::net::TcpStream::connect(peer)
+            }
         } else {
-            std::net::TcpStream::connect(peer)
+            // The socket has to be bound to the local address before it
+            // connects, which the standard library can't do
+            let socket = Socket::new(Domain::for_address(peer), Type::STREAM, Some(Protocol::TCP))
+                .map_err(io_err_into_net_error)?;
+            socket.bind(&addr.into()).map_err(io_err_into_net_error)?;
+            if let Some(timeout) = timeout {
+                socket.connect_timeout(&peer.into(), timeout)
+            } else {
+                socket.connect(&peer.into())
+            }
+            .map(|()| std::net::TcpStream::from(socket))
         }
         .map_err(io_err_into_net_error)?;
         let peer = stream.peer_addr().map_err(io_err_into_net_error)?;
@@ -171,6 +225,25 @@ pub struct LocalTcpListener {
     timeout: Option<Duration>,
 }
 
+/// Whether the listener is in non-blocking mode
+#[cfg(all(unix, not(feature = "wasix")))]
+fn is_nonblocking(listener: &std::net::TcpListener) -> std::io::Result<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    let flags = unsafe { libc::fcntl(listener.as_raw_fd(), libc::F_GETFL) };
+    if flags < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(flags & libc::O_NONBLOCK != 0)
+}
+
+/// Whether the listener is in non-blocking mode, which can't be checked
+/// here so the blocking mode it is created with is assumed
+#[cfg(all(not(unix), not(feature = "wasix")))]
+fn is_nonblocking(_listener: &std::net::TcpListener) -> std::io::Result<bool> {
+    Ok(false)
+}
+
 impl VirtualTcpListener for LocalTcpListener {
     fn accept(&self) -> Result<(Box<dyn VirtualTcpSocket + Sync>, SocketAddr)> {
         if let Some(timeout) = &self.timeout {
@@ -218,9 +291,45 @@ impl VirtualTcpListener for LocalTcpListener {
     #[cfg(not(feature = "wasix"))]
     fn accept_timeout(
         &self,
-        _timeout: Duration,
+        timeout: Duration,
     ) -> Result<(Box<dyn VirtualTcpSocket + Sync>, SocketAddr)> {
-        self.accept()
+        // The standard library has no accept with a timeout, so the listener
+        // is polled in non-blocking mode until the timeout elapses, after
+        // which its previous mode is restored
+        let nonblocking = is_nonblocking(&self.stream).map_err(io_err_into_net_error)?;
+        self.stream
+            .set_nonblocking(true)
+            .map_err(io_err_into_net_error)?;
+        let start = std::time::Instant::now();
+        let ret = loop {
+            match self.stream.accept() {
+                Ok(ret) => break Ok(ret),
+                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                    let elapsed = start.elapsed();
+                    if elapsed >= timeout {
+                        break Err(NetworkError::TimedOut);
+                    }
+                    std::thread::sleep((timeout - elapsed).min(Duration::from_millis(1)));
+                }
+                Err(err) => break Err(io_err_into_net_error(err)),
+            }
+        };
+        self.stream
+            .set_nonblocking(nonblocking)
+            .map_err(io_err_into_net_error)?;
+
+        // Some platforms hand out connections that inherit the non-blocking
+        // mode of the listener
+        let (sock, addr) = ret?;
+        sock.set_nonblocking(false).map_err(io_err_into_net_error)?;
+        Ok((
+            Box::new(LocalTcpStream {
+                stream: sock,
+                addr,
+                connect_timeout: None,
+            }),
+            addr,
+        ))
     }
 
     /// Sets the accept timeout
@@ -274,11 +383,7 @@ impl VirtualTcpSocket for LocalTcpStream {
                 self.connect_timeout = timeout;
                 Ok(())
             }
-            #[cfg(feature = "wasix")]
-            TimeType::Linger => self
-                .stream
-                .set_linger(timeout)
-                .map_err(io_err_into_net_error),
+            TimeType::Linger => self.set_linger(timeout),
             _ => Err(NetworkError::InvalidInput),
         }
     }
@@ -288,26 +393,36 @@ impl VirtualTcpSocket for LocalTcpStream {
             TimeType::ReadTimeout => self.stream.read_timeout().map_err(io_err_into_net_error),
             TimeType::WriteTimeout => self.stream.write_timeout().map_err(io_err_into_net_error),
             TimeType::ConnectTimeout => Ok(self.connect_timeout),
-            #[cfg(feature = "wasix")]
-            TimeType::Linger => self.stream.linger().map_err(io_err_into_net_error),
+            TimeType::Linger => self.linger(),
             _ => Err(NetworkError::InvalidInput),
         }
     }
 
     fn set_recv_buf_size(&mut self, size: usize) -> Result<()> {
-        Ok(())
+        SockRef::from(&self.stream)
+            .set_recv_buffer_size(size)
+            .map_err(io_err_into_net_error)
     }
 
+    // The operating system may adjust the requested size (e.g. Linux
+    // doubles it to leave room for bookkeeping), so this reads the value
+    // back from the socket rather than remembering what was requested
     fn recv_buf_size(&self) -> Result<usize> {
-        Err(NetworkError::Unsupported)
+        SockRef::from(&self.stream)
+            .recv_buffer_size()
+            .map_err(io_err_into_net_error)
     }
 
     fn set_send_buf_size(&mut self, size: usize) -> Result<()> {
-        Ok(())
+        SockRef::from(&self.stream)
+            .set_send_buffer_size(size)
+            .map_err(io_err_into_net_error)
     }
 
     fn send_buf_size(&self) -> Result<usize> {
-        Err(NetworkError::Unsupported)
+        SockRef::from(&self.stream)
+            .send_buffer_size()
+            .map_err(io_err_into_net_error)
     }
 
     fn set_nodelay(&mut self, nodelay: bool) -> Result<()> {
@@ -331,25 +446,24 @@ impl VirtualTcpSocket for LocalTcpStream {
     fn shutdown(&mut self, how: Shutdown) -> Result<()> {
         self.stream.shutdown(how).map_err(io_err_into_net_error)
     }
+
+    #[cfg(target_os = "linux")]
+    fn stats(&self) -> Result<TcpStats> {
+        tcp_info::tcp_stats(&self.stream)
+    }
 }
 
 impl VirtualConnectedSocket for LocalTcpStream {
     fn set_linger(&mut self, linger: Option<Duration>) -> Result<()> {
-        #[cfg(feature = "wasix")]
-        self.stream
+        SockRef::from(&self.stream)
             .set_linger(linger)
-            .map_err(io_err_into_net_error)?;
-        Ok(())
-    }
-
-    #[cfg(feature = "wasix")]
-    fn linger(&self) -> Result<Option<Duration>> {
-        self.stream.linger().map_err(io_err_into_net_error)
+            .map_err(io_err_into_net_error)
     }
 
-    #[cfg(not(feature = "wasix"))]
     fn linger(&self) -> Result<Option<Duration>> {
-        Ok(None)
+        SockRef::from(&self.stream)
+            .linger()
+            .map_err(io_err_into_net_error)
     }
 
     fn send(&mut self, data: Bytes) -> Result<usize> {
@@ -366,6 +480,7 @@ impl VirtualConnectedSocket for LocalTcpStream {
     fn recv(&mut self) -> Result<SocketReceive> {
         let buf_size = 8192;
         let mut buf = BytesMut::with_capacity(buf_size);
+        buf.resize(buf_size, 0);
         let read = self
             .stream
             .read(&mut buf[..])
@@ -380,6 +495,7 @@ impl VirtualConnectedSocket for LocalTcpStream {
     fn peek(&mut self) -> Result<SocketReceive> {
         let buf_size = 8192;
         let mut buf = BytesMut::with_capacity(buf_size);
+        buf.resize(buf_size, 0);
         let read = self
             .stream
             .peek(&mut buf[..])
@@ -507,6 +623,7 @@ impl VirtualConnectedSocket for LocalUdpSocket {
     fn recv(&mut self) -> Result<SocketReceive> {
         let buf_size = 8192;
         let mut buf = BytesMut::with_capacity(buf_size);
+        buf.resize(buf_size, 0);
         let read = self.0.recv(&mut buf[..]).map_err(io_err_into_net_error)?;
         let buf = Bytes::from(buf).slice(..read);
         Ok(SocketReceive {
@@ -518,6 +635,7 @@ impl VirtualConnectedSocket for LocalUdpSocket {
     fn peek(&mut self) -> Result<SocketReceive> {
         let buf_size = 8192;
         let mut buf = BytesMut::with_capacity(buf_size);
+        buf.resize(buf_size, 0);
         let read = self.0.peek(&mut buf[..]).map_err(io_err_into_net_error)?;
         let buf = Bytes::from(buf).slice(..read);
         Ok(SocketReceive {
@@ -537,6 +655,7 @@ impl VirtualConnectionlessSocket for LocalUdpSocket {
     fn recv_from(&mut self) -> Result<SocketReceiveFrom> {
         let buf_size = 8192;
         let mut buf = BytesMut::with_capacity(buf_size);
+        buf.resize(buf_size, 0);
         let (read, peer) = self
             .0
             .recv_from(&mut buf[..])
@@ -552,6 +671,7 @@ impl VirtualConnectionlessSocket for LocalUdpSocket {
     fn peek_from(&mut self) -> Result<SocketReceiveFrom> {
         let buf_size = 8192;
         let mut buf = BytesMut::with_capacity(buf_size);
+        buf.resize(buf_size, 0);
         let (read, peer) = self
             .0
             .peek_from(&mut buf[..])
@@ -582,3 +702,167 @@ impl VirtualSocket for LocalUdpSocket {
         Ok(SocketStatus::Opened)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmer_vnet::PortRangeNetworking;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn tcp_stats_after_transfer() {
+        let net = LocalNetworking::default();
+        let listener = net
+            .listen_tcp("127.0.0.1:0".parse().unwrap(), false, false, false)
+            .unwrap();
+        let addr = listener.addr_local().unwrap();
+        let mut client = net
+            .connect_tcp("127.0.0.1:0".parse().unwrap(), addr, None)
+            .unwrap();
+        let (mut server, _) = listener.accept().unwrap();
+
+        client.send(Bytes::from(vec![7u8; 4096])).unwrap();
+        server.send(Bytes::from_static(b"pong")).unwrap();
+
+        // The kernel acknowledges the data without the peers reading it,
+        // wait until it did so on both sides
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        let stats = loop {
+            let stats = client.stats().unwrap();
+            let transferred = stats.bytes_sent != Some(0) && stats.bytes_received != Some(0);
+            if transferred || std::time::Instant::now() > deadline {
+                break stats;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+
+        assert!(stats.rtt.is_some());
+        assert!(stats.retransmits.is_some());
+        assert_ne!(stats.bytes_sent, Some(0));
+        assert_ne!(stats.bytes_received, Some(0));
+        assert_ne!(stats.segments_sent, Some(0));
+        assert_ne!(stats.segments_received, Some(0));
+    }
+
+    #[test]
+    fn connected_udp_socket_only_talks_to_its_peer() {
+        let net = LocalNetworking::default();
+        let any = "127.0.0.1:0".parse().unwrap();
+        let mut socket = net.bind_udp(any, false, false).unwrap();
+        let mut peer = net.bind_udp(any, false, false).unwrap();
+        let mut other = net.bind_udp(any, false, false).unwrap();
+        let socket_addr = socket.addr_local().unwrap();
+        let peer_addr = peer.addr_local().unwrap();
+
+        socket.connect(peer_addr).unwrap();
+        assert_eq!(socket.addr_peer().unwrap(), Some(peer_addr));
+
+        socket.send(Bytes::from_static(b"ping")).unwrap();
+        let received = peer.recv_from().unwrap();
+        assert_eq!(&received.data[..], b"ping");
+        assert_eq!(received.addr, socket_addr);
+
+        // Datagrams from anyone but the connected peer are dropped
+        other
+            .send_to(Bytes::from_static(b"intruder"), socket_addr)
+            .unwrap();
+        peer.send_to(Bytes::from_static(b"pong"), socket_addr)
+            .unwrap();
+        assert_eq!(&socket.recv().unwrap().data[..], b"pong");
+    }
+
+    #[test]
+    fn sockets_are_bound_within_the_port_range() {
+        // Find a couple of ports that are free right now
+        let ports = {
+            let probe = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+            let port = probe.local_addr().unwrap().port();
+            port..=port.saturating_add(1)
+        };
+        let net = PortRangeNetworking::new(LocalNetworking::default(), ports.clone());
+        let any: SocketAddr = "127.0.0.1:0".parse().unwrap();
+
+        let mut bound = Vec::new();
+        let exhausted = loop {
+            match net.bind_udp(any, false, false) {
+                Ok(socket) => bound.push(socket),
+                Err(err) => break err,
+            }
+        };
+        assert!(!bound.is_empty() && bound.len() <= 2);
+        for socket in bound.iter() {
+            assert!(ports.contains(&socket.addr_local().unwrap().port()));
+        }
+        assert_eq!(exhausted, NetworkError::PermissionDenied);
+
+        // Explicitly binding outside of the range is denied
+        let outside = SocketAddr::new(any.ip(), ports.start() - 1);
+        assert_eq!(
+            net.bind_udp(outside, false, false).unwrap_err(),
+            NetworkError::PermissionDenied
+        );
+    }
+
+    #[cfg(all(unix, not(feature = "wasix")))]
+    #[test]
+    fn accept_timeout_keeps_the_blocking_mode() {
+        for nonblocking in [false, true] {
+            let stream = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            stream.set_nonblocking(nonblocking).unwrap();
+            let listener = LocalTcpListener {
+                stream,
+                timeout: None,
+            };
+
+            let err = listener
+                .accept_timeout(Duration::from_millis(10))
+                .unwrap_err();
+            assert_eq!(err, NetworkError::TimedOut);
+            assert_eq!(is_nonblocking(&listener.stream).unwrap(), nonblocking);
+        }
+    }
+
+    #[test]
+    fn interfaces_include_loopback() {
+        let interfaces = LocalNetworking::default().interfaces().unwrap();
+        let loopback = interfaces
+            .iter()
+            .find(|iface| iface.loopback)
+            .expect("no loopback interface");
+        assert!(loopback.up);
+        assert!(loopback.addrs.iter().any(|cidr| cidr.ip.is_loopback()));
+    }
+
+    #[test]
+    fn buf_sizes_report_the_effective_value() {
+        let net = LocalNetworking::default();
+        let listener = net
+            .listen_tcp("127.0.0.1:0".parse().unwrap(), false, false, false)
+            .unwrap();
+        let mut client = net
+            .connect_tcp(
+                "127.0.0.1:0".parse().unwrap(),
+                listener.addr_local().unwrap(),
+                None,
+            )
+            .unwrap();
+
+        let requested = 32 * 1024;
+        client.set_send_buf_size(requested).unwrap();
+        client.set_recv_buf_size(requested).unwrap();
+        let send_buf_size = client.send_buf_size().unwrap();
+        let recv_buf_size = client.recv_buf_size().unwrap();
+
+        // Linux doubles the requested value for its own bookkeeping
+        #[cfg(target_os = "linux")]
+        {
+            assert_eq!(send_buf_size, requested * 2);
+            assert_eq!(recv_buf_size, requested * 2);
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            assert!(send_buf_size >= requested);
+            assert!(recv_buf_size >= requested);
+        }
+    }
+}
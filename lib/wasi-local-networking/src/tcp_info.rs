@@ -0,0 +1,116 @@
+//! Reads the statistics the Linux kernel keeps for a TCP connection
+//! (`getsockopt(TCP_INFO)`).
+
+use std::mem;
+use std::os::unix::io::AsRawFd;
+use std::time::Duration;
+use wasmer_vnet::{io_err_into_net_error, Result, TcpStats};
+
+/// Leading part of `struct tcp_info` from `linux/tcp.h`, the kernel only
+/// fills in as much of it as it knows about and reports how much that was.
+#[repr(C)]
+#[derive(Default)]
+#[allow(dead_code)]
+struct TcpInfo {
+    tcpi_state: u8,
+    tcpi_ca_state: u8,
+    tcpi_retransmits: u8,
+    tcpi_probes: u8,
+    tcpi_backoff: u8,
+    tcpi_options: u8,
+    tcpi_wscale: u8,
+    tcpi_flags: u8,
+
+    tcpi_rto: u32,
+    tcpi_ato: u32,
+    tcpi_snd_mss: u32,
+    tcpi_rcv_mss: u32,
+
+    tcpi_unacked: u32,
+    tcpi_sacked: u32,
+    tcpi_lost: u32,
+    tcpi_retrans: u32,
+    tcpi_fackets: u32,
+
+    tcpi_last_data_sent: u32,
+    tcpi_last_ack_sent: u32,
+    tcpi_last_data_recv: u32,
+    tcpi_last_ack_recv: u32,
+
+    tcpi_pmtu: u32,
+    tcpi_rcv_ssthresh: u32,
+    tcpi_rtt: u32,
+    tcpi_rttvar: u32,
+    tcpi_snd_ssthresh: u32,
+    tcpi_snd_cwnd: u32,
+    tcpi_advmss: u32,
+    tcpi_reordering: u32,
+
+    tcpi_rcv_rtt: u32,
+    tcpi_rcv_space: u32,
+
+    tcpi_total_retrans: u32,
+
+    tcpi_pacing_rate: u64,
+    tcpi_max_pacing_rate: u64,
+    tcpi_bytes_acked: u64,
+    tcpi_bytes_received: u64,
+    tcpi_segs_out: u32,
+    tcpi_segs_in: u32,
+}
+
+pub(crate) fn tcp_stats(stream: &impl AsRawFd) -> Result<TcpStats> {
+    let mut info = TcpInfo::default();
+    let mut len = mem::size_of::<TcpInfo>() as libc::socklen_t;
+    let ret = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::IPPROTO_TCP,
+            libc::TCP_INFO,
+            &mut info as *mut TcpInfo as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    if ret != 0 {
+        return Err(io_err_into_net_error(std::io::Error::last_os_error()));
+    }
+
+    // Older kernels fill in less of the structure, only report the
+    // fields they actually wrote
+    let len = len as usize;
+    let has = |end: usize| len >= end;
+    let end_of_total_retrans = 104;
+    let end_of_bytes_acked = 128;
+    let end_of_bytes_received = 136;
+    let end_of_segs_in = 144;
+
+    Ok(TcpStats {
+        rtt: Some(Duration::from_micros(info.tcpi_rtt as u64)),
+        rtt_var: Some(Duration::from_micros(info.tcpi_rttvar as u64)),
+        retransmits: if has(end_of_total_retrans) {
+            Some(info.tcpi_total_retrans as u64)
+        } else {
+            None
+        },
+        bytes_sent: if has(end_of_bytes_acked) {
+            Some(info.tcpi_bytes_acked)
+        } else {
+            None
+        },
+        bytes_received: if has(end_of_bytes_received) {
+            Some(info.tcpi_bytes_received)
+        } else {
+            None
+        },
+        segments_sent: if has(end_of_segs_in) {
+            Some(info.tcpi_segs_out as u64)
+        } else {
+            None
+        },
+        segments_received: if has(end_of_segs_in) {
+            Some(info.tcpi_segs_in as u64)
+        } else {
+            None
+        },
+    })
+}
@@ -0,0 +1,422 @@
+//! Secures the TCP connections of another networking implementation with TLS.
+
+use bytes::{Buf, Bytes};
+use rustls::{
+    Certificate, ClientConfig, ClientConnection, OwnedTrustAnchor, RootCertStore, ServerName,
+};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Shutdown, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use wasmer_vnet::{
+    io_err_into_net_error, IpCidr, IpRoute, NetworkError, NetworkInterface, Result,
+    SocketHttpRequest, SocketReceive, SocketStatus, StreamSecurity, TcpStats, TimeType,
+    VirtualConnectedSocket, VirtualIcmpSocket, VirtualNetworking, VirtualRawSocket, VirtualSocket,
+    VirtualTcpListener, VirtualTcpSocket, VirtualUdpSocket, VirtualWebSocket,
+};
+
+/// Networking implementation that adds TLS connections
+/// ([`VirtualNetworking::connect_tls`]) on top of the TCP connections of
+/// another implementation, the handshake and encryption are done by the
+/// host so guests read and write plaintext.
+///
+/// The certificates of peers are always verified, by default against the
+/// Mozilla root certificates.
+pub struct TlsNetworking<N> {
+    inner: N,
+    config: Arc<ClientConfig>,
+}
+
+impl<N> fmt::Debug for TlsNetworking<N>
+where
+    N: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsNetworking")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<N> TlsNetworking<N>
+where
+    N: VirtualNetworking,
+{
+    /// Verifies peers against the Mozilla root certificates
+    pub fn new(inner: N) -> Self {
+        let mut roots = RootCertStore::empty();
+        roots.add_server_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|ta| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(
+                ta.subject,
+                ta.spki,
+                ta.name_constraints,
+            )
+        }));
+        Self::with_root_certificates(inner, roots)
+    }
+
+    /// Verifies peers against the given root certificates only
+    pub fn with_root_certificates(inner: N, roots: RootCertStore) -> Self {
+        let config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        Self {
+            inner,
+            config: Arc::new(config),
+        }
+    }
+
+    /// Verifies peers against the given DER encoded root certificate only
+    pub fn with_root_certificate(inner: N, der: &[u8]) -> Result<Self> {
+        let mut roots = RootCertStore::empty();
+        roots
+            .add(&Certificate(der.to_vec()))
+            .map_err(|_| NetworkError::InvalidInput)?;
+        Ok(Self::with_root_certificates(inner, roots))
+    }
+}
+
+impl<N> VirtualNetworking for TlsNetworking<N>
+where
+    N: VirtualNetworking,
+{
+    fn ws_connect(&self, url: &str) -> Result<Box<dyn VirtualWebSocket + Sync>> {
+        self.inner.ws_connect(url)
+    }
+
+    fn http_request(
+        &self,
+        url: &str,
+        method: &str,
+        headers: &str,
+        gzip: bool,
+    ) -> Result<SocketHttpRequest> {
+        self.inner.http_request(url, method, headers, gzip)
+    }
+
+    fn bridge(&self, network: &str, access_token: &str, security: StreamSecurity) -> Result<()> {
+        self.inner.bridge(network, access_token, security)
+    }
+
+    fn unbridge(&self) -> Result<()> {
+        self.inner.unbridge()
+    }
+
+    fn dhcp_acquire(&self) -> Result<Vec<IpAddr>> {
+        self.inner.dhcp_acquire()
+    }
+
+    fn ip_add(&self, ip: IpAddr, prefix: u8) -> Result<()> {
+        self.inner.ip_add(ip, prefix)
+    }
+
+    fn ip_remove(&self, ip: IpAddr) -> Result<()> {
+        self.inner.ip_remove(ip)
+    }
+
+    fn ip_clear(&self) -> Result<()> {
+        self.inner.ip_clear()
+    }
+
+    fn ip_list(&self) -> Result<Vec<IpCidr>> {
+        self.inner.ip_list()
+    }
+
+    fn mac(&self) -> Result<[u8; 6]> {
+        self.inner.mac()
+    }
+
+    fn gateway_set(&self, ip: IpAddr) -> Result<()> {
+        self.inner.gateway_set(ip)
+    }
+
+    fn route_add(
+        &self,
+        cidr: IpCidr,
+        via_router: IpAddr,
+        preferred_until: Option<Duration>,
+        expires_at: Option<Duration>,
+    ) -> Result<()> {
+        self.inner
+            .route_add(cidr, via_router, preferred_until, expires_at)
+    }
+
+    fn route_remove(&self, cidr: IpAddr) -> Result<()> {
+        self.inner.route_remove(cidr)
+    }
+
+    fn route_clear(&self) -> Result<()> {
+        self.inner.route_clear()
+    }
+
+    fn route_list(&self) -> Result<Vec<IpRoute>> {
+        self.inner.route_list()
+    }
+
+    fn interfaces(&self) -> Result<Vec<NetworkInterface>> {
+        self.inner.interfaces()
+    }
+
+    fn bind_raw(&self) -> Result<Box<dyn VirtualRawSocket + Sync>> {
+        self.inner.bind_raw()
+    }
+
+    fn listen_tcp(
+        &self,
+        addr: SocketAddr,
+        only_v6: bool,
+        reuse_port: bool,
+        reuse_addr: bool,
+    ) -> Result<Box<dyn VirtualTcpListener + Sync>> {
+        self.inner.listen_tcp(addr, only_v6, reuse_port, reuse_addr)
+    }
+
+    fn bind_udp(
+        &self,
+        addr: SocketAddr,
+        reuse_port: bool,
+        reuse_addr: bool,
+    ) -> Result<Box<dyn VirtualUdpSocket + Sync>> {
+        self.inner.bind_udp(addr, reuse_port, reuse_addr)
+    }
+
+    fn bind_icmp(&self, addr: IpAddr) -> Result<Box<dyn VirtualIcmpSocket + Sync>> {
+        self.inner.bind_icmp(addr)
+    }
+
+    fn connect_tcp(
+        &self,
+        addr: SocketAddr,
+        peer: SocketAddr,
+        timeout: Option<Duration>,
+    ) -> Result<Box<dyn VirtualTcpSocket + Sync>> {
+        self.inner.connect_tcp(addr, peer, timeout)
+    }
+
+    fn connect_tls(
+        &self,
+        addr: SocketAddr,
+        peer: SocketAddr,
+        server_name: &str,
+        timeout: Option<Duration>,
+    ) -> Result<Box<dyn VirtualTcpSocket + Sync>> {
+        let server_name =
+            ServerName::try_from(server_name).map_err(|_| NetworkError::InvalidInput)?;
+        let conn = ClientConnection::new(self.config.clone(), server_name)
+            .map_err(|_| NetworkError::InvalidInput)?;
+        let socket = self.inner.connect_tcp(addr, peer, timeout)?;
+
+        let mut socket = TlsTcpSocket {
+            socket,
+            conn,
+            received: Bytes::new(),
+        };
+        socket.handshake()?;
+        Ok(Box::new(socket))
+    }
+
+    fn resolve(
+        &self,
+        host: &str,
+        port: Option<u16>,
+        dns_server: Option<IpAddr>,
+    ) -> Result<Vec<IpAddr>> {
+        self.inner.resolve(host, port, dns_server)
+    }
+}
+
+/// A TCP connection secured with TLS, data is encrypted and decrypted as it
+/// passes through
+pub struct TlsTcpSocket {
+    socket: Box<dyn VirtualTcpSocket + Sync>,
+    conn: ClientConnection,
+    /// Encrypted data received from the socket that TLS hasn't read yet
+    received: Bytes,
+}
+
+impl fmt::Debug for TlsTcpSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsTcpSocket")
+            .field("socket", &self.socket)
+            .finish()
+    }
+}
+
+/// Lets TLS read and write the encrypted data through the socket
+struct SocketIo<'a> {
+    socket: &'a mut Box<dyn VirtualTcpSocket + Sync>,
+    received: &'a mut Bytes,
+}
+
+impl Read for SocketIo<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.received.is_empty() {
+            *self.received = self
+                .socket
+                .recv()
+                .map_err(wasmer_vnet::net_error_into_io_err)?
+                .data;
+        }
+        let read = buf.len().min(self.received.len());
+        buf[..read].copy_from_slice(&self.received[..read]);
+        self.received.advance(read);
+        Ok(read)
+    }
+}
+
+impl Write for SocketIo<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.socket
+            .send(Bytes::copy_from_slice(buf))
+            .map_err(wasmer_vnet::net_error_into_io_err)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        VirtualConnectedSocket::flush(&mut **self.socket)
+            .map_err(wasmer_vnet::net_error_into_io_err)
+    }
+}
+
+impl TlsTcpSocket {
+    fn handshake(&mut self) -> Result<()> {
+        let mut io = SocketIo {
+            socket: &mut self.socket,
+            received: &mut self.received,
+        };
+        while self.conn.is_handshaking() {
+            self.conn
+                .complete_io(&mut io)
+                .map_err(io_err_into_net_error)?;
+        }
+        Ok(())
+    }
+
+    fn stream(&mut self) -> (&mut ClientConnection, SocketIo<'_>) {
+        (
+            &mut self.conn,
+            SocketIo {
+                socket: &mut self.socket,
+                received: &mut self.received,
+            },
+        )
+    }
+}
+
+impl VirtualTcpSocket for TlsTcpSocket {
+    fn set_opt_time(&mut self, ty: TimeType, timeout: Option<Duration>) -> Result<()> {
+        self.socket.set_opt_time(ty, timeout)
+    }
+
+    fn opt_time(&self, ty: TimeType) -> Result<Option<Duration>> {
+        self.socket.opt_time(ty)
+    }
+
+    fn set_recv_buf_size(&mut self, size: usize) -> Result<()> {
+        self.socket.set_recv_buf_size(size)
+    }
+
+    fn recv_buf_size(&self) -> Result<usize> {
+        self.socket.recv_buf_size()
+    }
+
+    fn set_send_buf_size(&mut self, size: usize) -> Result<()> {
+        self.socket.set_send_buf_size(size)
+    }
+
+    fn send_buf_size(&self) -> Result<usize> {
+        self.socket.send_buf_size()
+    }
+
+    fn set_nodelay(&mut self, nodelay: bool) -> Result<()> {
+        self.socket.set_nodelay(nodelay)
+    }
+
+    fn nodelay(&self) -> Result<bool> {
+        self.socket.nodelay()
+    }
+
+    fn addr_peer(&self) -> Result<SocketAddr> {
+        self.socket.addr_peer()
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        VirtualConnectedSocket::flush(self)
+    }
+
+    fn shutdown(&mut self, how: Shutdown) -> Result<()> {
+        if how != Shutdown::Read {
+            // Let the peer know that no more data follows
+            self.conn.send_close_notify();
+            VirtualConnectedSocket::flush(self)?;
+        }
+        self.socket.shutdown(how)
+    }
+
+    fn stats(&self) -> Result<TcpStats> {
+        self.socket.stats()
+    }
+}
+
+impl VirtualConnectedSocket for TlsTcpSocket {
+    fn set_linger(&mut self, linger: Option<Duration>) -> Result<()> {
+        self.socket.set_linger(linger)
+    }
+
+    fn linger(&self) -> Result<Option<Duration>> {
+        self.socket.linger()
+    }
+
+    fn send(&mut self, data: Bytes) -> Result<usize> {
+        let (conn, mut io) = self.stream();
+        rustls::Stream::new(conn, &mut io)
+            .write_all(&data[..])
+            .map(|_| data.len())
+            .map_err(io_err_into_net_error)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        let (conn, mut io) = self.stream();
+        rustls::Stream::new(conn, &mut io)
+            .flush()
+            .map_err(io_err_into_net_error)
+    }
+
+    fn recv(&mut self) -> Result<SocketReceive> {
+        let buf_size = 8192;
+        let mut buf = vec![0u8; buf_size];
+        let (conn, mut io) = self.stream();
+        let read = rustls::Stream::new(conn, &mut io)
+            .read(&mut buf[..])
+            .map_err(io_err_into_net_error)?;
+        buf.truncate(read);
+        Ok(SocketReceive {
+            data: Bytes::from(buf),
+            truncated: read == buf_size,
+        })
+    }
+
+    fn peek(&mut self) -> Result<SocketReceive> {
+        Err(NetworkError::Unsupported)
+    }
+}
+
+impl VirtualSocket for TlsTcpSocket {
+    fn set_ttl(&mut self, ttl: u32) -> Result<()> {
+        self.socket.set_ttl(ttl)
+    }
+
+    fn ttl(&self) -> Result<u32> {
+        self.socket.ttl()
+    }
+
+    fn addr_local(&self) -> Result<SocketAddr> {
+        self.socket.addr_local()
+    }
+
+    fn status(&self) -> Result<SocketStatus> {
+        self.socket.status()
+    }
+}
@@ -0,0 +1,104 @@
+//! Web socket client connections made from the host with `tungstenite`.
+//!
+//! The rest of the local networking is blocking and there is no tokio
+//! runtime to drive `tokio-tungstenite`, so the synchronous `tungstenite`
+//! is used instead.
+
+use bytes::Bytes;
+use std::fmt;
+use std::net::TcpStream;
+use tungstenite::error::UrlError;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::Message;
+use wasmer_vnet::{io_err_into_net_error, NetworkError, Result, SocketReceive, VirtualWebSocket};
+
+/// Web socket connected to a server by the host
+///
+/// Text and binary messages are both received as their bytes, what is sent
+/// goes out as binary messages unless it is sent as text. Pings from the
+/// server are answered the next time the socket is read from, and dropping
+/// the socket closes the connection cleanly.
+pub struct LocalWebSocket {
+    socket: tungstenite::WebSocket<MaybeTlsStream<TcpStream>>,
+}
+
+impl LocalWebSocket {
+    /// Connects to the web socket server at `url` (e.g. `ws://host:port/path`)
+    pub fn connect(url: &str) -> Result<Self> {
+        let (socket, _) = tungstenite::connect(url).map_err(ws_err_into_net_error)?;
+        Ok(Self { socket })
+    }
+}
+
+impl fmt::Debug for LocalWebSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalWebSocket").finish()
+    }
+}
+
+impl VirtualWebSocket for LocalWebSocket {
+    fn send(&mut self, data: Bytes) -> Result<usize> {
+        let len = data.len();
+        self.socket
+            .write_message(Message::Binary(data.to_vec()))
+            .map_err(ws_err_into_net_error)?;
+        Ok(len)
+    }
+
+    fn send_text(&mut self, text: String) -> Result<usize> {
+        let len = text.len();
+        self.socket
+            .write_message(Message::Text(text))
+            .map_err(ws_err_into_net_error)?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.socket.write_pending().map_err(ws_err_into_net_error)
+    }
+
+    fn recv(&mut self) -> Result<SocketReceive> {
+        loop {
+            let data = match self.socket.read_message() {
+                Ok(Message::Text(text)) => Bytes::from(text),
+                Ok(Message::Binary(data)) => Bytes::from(data),
+                // Once the server closed the connection there is nothing
+                // left to read, the reply to its close frame is sent right
+                // away so the server doesn't wait for it
+                Ok(Message::Close(_)) => {
+                    let _ = self.socket.write_pending();
+                    Bytes::new()
+                }
+                Err(tungstenite::Error::ConnectionClosed)
+                | Err(tungstenite::Error::AlreadyClosed) => Bytes::new(),
+                // Pings and pongs are handled by `tungstenite` itself
+                Ok(_) => continue,
+                Err(err) => return Err(ws_err_into_net_error(err)),
+            };
+            return Ok(SocketReceive {
+                data,
+                truncated: false,
+            });
+        }
+    }
+}
+
+impl Drop for LocalWebSocket {
+    fn drop(&mut self) {
+        let _ = self.socket.close(None);
+        let _ = self.socket.write_pending();
+    }
+}
+
+fn ws_err_into_net_error(err: tungstenite::Error) -> NetworkError {
+    use tungstenite::Error;
+    match err {
+        Error::Io(err) => io_err_into_net_error(err),
+        Error::ConnectionClosed | Error::AlreadyClosed => NetworkError::ConnectionReset,
+        Error::Url(UrlError::TlsFeatureNotEnabled) => NetworkError::Unsupported,
+        Error::Url(_) => NetworkError::InvalidInput,
+        Error::Capacity(_) | Error::Protocol(_) | Error::Utf8 => NetworkError::InvalidData,
+        Error::Http(_) | Error::HttpFormat(_) => NetworkError::ConnectionRefused,
+        _ => NetworkError::UnknownError,
+    }
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "tls")]
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::thread;
+use wasmer_vnet::{Bytes, NetworkError, VirtualNetworking};
+use wasmer_wasi_local_networking::{LocalNetworking, TlsNetworking};
+
+// A CA and a certificate for `localhost` signed by it, generated with openssl
+const CA_CERT: &[u8] = include_bytes!("tls/ca.der");
+const LOCALHOST_CERT: &[u8] = include_bytes!("tls/localhost.der");
+const LOCALHOST_KEY: &[u8] = include_bytes!("tls/localhost.key.der");
+
+/// Starts a TLS server that echoes back everything it receives on one
+/// connection
+fn echo_server() -> std::net::SocketAddr {
+    let config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![rustls::Certificate(LOCALHOST_CERT.to_vec())],
+            rustls::PrivateKey(LOCALHOST_KEY.to_vec()),
+        )
+        .unwrap();
+    let config = Arc::new(config);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut conn = rustls::ServerConnection::new(config).unwrap();
+        let mut tls = rustls::Stream::new(&mut conn, &mut stream);
+        let mut buf = [0u8; 1024];
+        loop {
+            match tls.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    if tls.write_all(&buf[..n]).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+    addr
+}
+
+#[test]
+fn tls_round_trip() {
+    let peer = echo_server();
+    let net = TlsNetworking::with_root_certificate(LocalNetworking::default(), CA_CERT).unwrap();
+
+    let mut socket = net
+        .connect_tls("127.0.0.1:0".parse().unwrap(), peer, "localhost", None)
+        .unwrap();
+    socket.send(Bytes::from_static(b"hello over tls")).unwrap();
+
+    let mut received = Vec::new();
+    while received.len() < b"hello over tls".len() {
+        let data = socket.recv().unwrap().data;
+        assert!(!data.is_empty(), "connection closed early");
+        received.extend_from_slice(&data);
+    }
+    assert_eq!(&received[..], b"hello over tls");
+}
+
+#[test]
+fn tls_verifies_the_server_certificate() {
+    let peer = echo_server();
+    // The test CA isn't trusted by default
+    let net = TlsNetworking::new(LocalNetworking::default());
+
+    let err = net
+        .connect_tls("127.0.0.1:0".parse().unwrap(), peer, "localhost", None)
+        .unwrap_err();
+    assert_ne!(err, NetworkError::Unsupported);
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "ws")]
+
+use std::net::TcpListener;
+use std::thread;
+use tungstenite::Message;
+use wasmer_vnet::{Bytes, VirtualNetworking};
+use wasmer_wasi_local_networking::LocalNetworking;
+
+/// Starts a web socket server that greets the client, pings it and then
+/// echoes back everything it receives until it is told "bye", text messages
+/// are echoed back with a "text: " prefix. The server thread returns
+/// whether the ping was answered.
+fn echo_server() -> (String, thread::JoinHandle<bool>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}/", listener.local_addr().unwrap());
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut ws = tungstenite::accept(stream).unwrap();
+        ws.write_message(Message::Ping(b"ping".to_vec())).unwrap();
+        ws.write_message(Message::Text("welcome".to_string()))
+            .unwrap();
+        let mut ponged = false;
+        loop {
+            match ws.read_message() {
+                Ok(Message::Pong(data)) => ponged = data == b"ping",
+                Ok(Message::Binary(data)) if data == b"bye" => ws.close(None).unwrap(),
+                Ok(Message::Text(text)) => ws
+                    .write_message(Message::Text(format!("text: {}", text)))
+                    .unwrap(),
+                Ok(msg @ Message::Binary(_)) => ws.write_message(msg).unwrap(),
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+        ponged
+    });
+    (url, server)
+}
+
+#[test]
+fn ws_round_trip() {
+    let (url, server) = echo_server();
+    let mut socket = LocalNetworking::default().ws_connect(&url).unwrap();
+
+    // Text messages arrive as their bytes
+    assert_eq!(&socket.recv().unwrap().data[..], b"welcome");
+
+    // Bytes go out as binary messages and text as text messages
+    socket.send(Bytes::from_static(b"hello over ws")).unwrap();
+    socket.flush().unwrap();
+    assert_eq!(&socket.recv().unwrap().data[..], b"hello over ws");
+    socket.send_text("hello in text".to_string()).unwrap();
+    socket.flush().unwrap();
+    assert_eq!(&socket.recv().unwrap().data[..], b"text: hello in text");
+
+    // The server closes the connection cleanly
+    socket.send(Bytes::from_static(b"bye")).unwrap();
+    assert!(socket.recv().unwrap().data.is_empty());
+    assert!(server.join().unwrap());
+}
@@ -203,7 +203,7 @@ pub mod net {
     use crate::wasi::Addressfamily;
     use wasmer_derive::ValueType;
 
-    use crate::wasi::OptionTimestamp;
+    use crate::wasi::{OptionTimestamp, Timestamp};
 
     pub use crate::wasi::{
         AddrUnspec, AddrUnspecPort, CidrUnspec, HttpHandles, HttpStatus, RiFlags, RoFlags, SdFlags,
@@ -324,6 +324,43 @@ pub mod net {
         pub expires_at: OptionTimestamp,
     }
 
+    /// An address of a network interface, interfaces without addresses
+    /// have a single entry with an unspecified address
+    #[derive(Debug, Copy, Clone, ValueType)]
+    #[repr(C)]
+    pub struct __wasi_ifaddr_t {
+        /// Name of the interface, padded with zeros
+        pub name: [u8; 16],
+        pub flags: u8,
+        pub addr: __wasi_cidr_t,
+    }
+
+    pub const __WASI_IFF_UP: u8 = 1 << 0;
+    pub const __WASI_IFF_LOOPBACK: u8 = 1 << 1;
+
+    /// Statistics of a TCP connection, `fields` tells which of the values
+    /// were provided by the host
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, ValueType)]
+    #[repr(C)]
+    pub struct __wasi_tcp_stats_t {
+        pub fields: u32,
+        pub rtt: Timestamp,
+        pub rtt_var: Timestamp,
+        pub retransmits: u64,
+        pub bytes_sent: u64,
+        pub bytes_received: u64,
+        pub segments_sent: u64,
+        pub segments_received: u64,
+    }
+
+    pub const __WASI_TCP_STATS_RTT: u32 = 1 << 0;
+    pub const __WASI_TCP_STATS_RTT_VAR: u32 = 1 << 1;
+    pub const __WASI_TCP_STATS_RETRANSMITS: u32 = 1 << 2;
+    pub const __WASI_TCP_STATS_BYTES_SENT: u32 = 1 << 3;
+    pub const __WASI_TCP_STATS_BYTES_RECEIVED: u32 = 1 << 4;
+    pub const __WASI_TCP_STATS_SEGMENTS_SENT: u32 = 1 << 5;
+    pub const __WASI_TCP_STATS_SEGMENTS_RECEIVED: u32 = 1 << 6;
+
     pub const __WASI_SOCK_RECV_INPUT_PEEK: RiFlags = 1 << 0;
     pub const __WASI_SOCK_RECV_INPUT_WAITALL: RiFlags = 1 << 1;
     pub const __WASI_SOCK_RECV_INPUT_DATA_TRUNCATED: RiFlags = 1 << 2;
@@ -35,6 +35,7 @@ compile_error!(
 
 #[macro_use]
 mod macros;
+pub mod net;
 mod runtime;
 mod state;
 mod syscalls;
@@ -47,14 +48,18 @@ pub mod runners;
 use crate::syscalls::*;
 
 pub use crate::state::{
-    Fd, Pipe, Stderr, Stdin, Stdout, WasiFs, WasiInodes, WasiState, WasiStateBuilder,
-    WasiStateCreationError, ALL_RIGHTS, VIRTUAL_ROOT_FD,
+    Fd, Pipe, SocketInfo, Stderr, Stdin, Stdout, WasiFs, WasiInodes, WasiSocketState, WasiState,
+    WasiStateBuilder, WasiStateCreationError, ALL_RIGHTS, VIRTUAL_ROOT_FD,
 };
 pub use crate::syscalls::types;
 #[cfg(feature = "wasix")]
 pub use crate::utils::is_wasix_module;
 pub use crate::utils::wasi_import_shared_memory;
-pub use crate::utils::{get_wasi_version, get_wasi_versions, is_wasi_module, WasiVersion};
+pub use crate::utils::wasi_stub_missing_imports;
+pub use crate::utils::{
+    get_unsupported_wasi_version, get_wasi_version, get_wasi_versions, is_wasi_module,
+    module_wasi_profile, WasiCapability, WasiProfile, WasiVersion,
+};
 
 pub use wasmer_vbus::{UnsupportedVirtualBus, VirtualBus};
 #[deprecated(since = "2.1.0", note = "Please use `wasmer_vfs::FsError`")]
@@ -70,14 +75,17 @@ use thiserror::Error;
 use tracing::trace;
 use wasmer::{
     imports, namespace, AsStoreMut, AsStoreRef, ExportError, Exports, Function, FunctionEnv,
-    Imports, Instance, Memory, Memory32, MemoryAccessError, MemorySize, MemoryView, Module,
-    TypedFunction,
+    FunctionEnvMut, Imports, Instance, Memory, Memory32, MemoryAccessError, MemorySize, MemoryView,
+    Module, TypedFunction,
 };
-use wasmer_wasi_types::wasi::{BusErrno, Errno, Snapshot0Clockid};
+use wasmer_wasi_types::wasi::{BusErrno, Errno, Signal, Snapshot0Clockid};
 
 pub use runtime::{
-    PluggableRuntimeImplementation, WasiRuntimeImplementation, WasiThreadError, WasiTtyState,
+    PluggableRuntimeImplementation, WasiBuffering, WasiLogRecord, WasiLogSink, WasiOutputSink,
+    WasiRuntimeImplementation, WasiThreadError, WasiTtyClear, WasiTtyMode, WasiTtyState,
+    OUTPUT_BLOCK_SIZE,
 };
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{mpsc, Arc, Mutex, RwLockReadGuard, RwLockWriteGuard};
 use std::time::Duration;
 
@@ -89,6 +97,8 @@ pub enum WasiError {
     Exit(syscalls::types::__wasi_exitcode_t),
     #[error("The WASI version could not be determined")]
     UnknownWasiVersion,
+    #[error("this module targets {0}, which this runtime build doesn't support")]
+    UnsupportedWasiVersion(&'static str),
 }
 
 /// Represents the ID of a WASI thread
@@ -121,6 +131,74 @@ impl From<WasiBusProcessId> for u32 {
     }
 }
 
+/// Number of calls made to each WASI function, shared by all the threads
+/// of a program (see [`WasiEnv::set_syscall_counts`])
+#[derive(Debug, Clone, Default)]
+pub struct SyscallCounts {
+    counts: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl SyscallCounts {
+    /// Counts a call to the WASI function `name`
+    pub fn record(&self, name: &str) {
+        let mut counts = self.counts.lock().unwrap();
+        match counts.get_mut(name) {
+            Some(count) => *count += 1,
+            None => {
+                counts.insert(name.to_string(), 1);
+            }
+        }
+    }
+
+    /// Number of calls made so far to each WASI function that was called
+    pub fn snapshot(&self) -> BTreeMap<String, u64> {
+        let counts = self.counts.lock().unwrap();
+        counts.iter().map(|(k, v)| (k.clone(), *v)).collect()
+    }
+}
+
+/// Errors the WASI functions of a program are forced to fail with instead
+/// of running, shared by all its threads (see [`WasiEnv::set_faults`])
+#[derive(Debug, Clone, Default)]
+pub struct WasiFaults {
+    state: Arc<Mutex<WasiFaultsState>>,
+}
+
+#[derive(Debug, Default)]
+struct WasiFaultsState {
+    /// Errors to return, by function and by call number
+    faults: HashMap<String, HashMap<u64, Errno>>,
+    /// Calls made so far to the functions that have faults
+    calls: HashMap<String, u64>,
+}
+
+impl WasiFaults {
+    /// Makes the `nth` call (counting from 1) to the WASI function `name`
+    /// fail with `errno`
+    pub fn fail_nth(&self, name: &str, nth: u64, errno: Errno) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .faults
+            .entry(name.to_string())
+            .or_default()
+            .insert(nth, errno);
+    }
+
+    /// Counts a call to the WASI function `name` and returns the error it
+    /// must fail with, if any
+    fn call(&self, name: &str) -> Option<Errno> {
+        let mut state = self.state.lock().unwrap();
+        let calls = state.calls.entry(name.to_string()).or_default();
+        *calls += 1;
+        let nth = *calls;
+        state
+            .faults
+            .get(name)
+            .and_then(|faults| faults.get(&nth))
+            .copied()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct WasiThread {
     /// ID of this thread
@@ -132,6 +210,10 @@ pub struct WasiThread {
     join: Arc<Mutex<mpsc::Receiver<()>>>,
 }
 
+/// Exit code `thread_join_v2` reports for threads that panicked, or that the
+/// runtime dropped without running, rather than returning normally
+pub const THREAD_ABORTED_EXIT_CODE: syscalls::types::__wasi_exitcode_t = 134;
+
 impl WasiThread {
     /// Waits for the thread to exit (false = timeout)
     pub fn join(&self, timeout: Duration) -> bool {
@@ -162,12 +244,24 @@ impl WasiFunctionEnv {
         store: &mut impl AsStoreMut,
         module: &Module,
     ) -> Result<Imports, WasiError> {
+        if let Some(version) = get_unsupported_wasi_version(module) {
+            return Err(WasiError::UnsupportedWasiVersion(version));
+        }
         let wasi_version = get_wasi_version(module, false).ok_or(WasiError::UnknownWasiVersion)?;
-        Ok(generate_import_object_from_env(
-            store,
-            &self.env,
-            wasi_version,
-        ))
+        match wasi_version {
+            // Modules built for `wasm32-wasi`/`wasm32-wasip1` import
+            // `wasi_snapshot_preview1`, while older toolchains used
+            // `wasi_unstable`. Objects built with both can end up linked into
+            // the same module, so both names are provided.
+            WasiVersion::Snapshot0 | WasiVersion::Snapshot1 => {
+                Ok(import_object_for_all_wasi_versions(store, &self.env))
+            }
+            _ => Ok(generate_import_object_from_env(
+                store,
+                &self.env,
+                wasi_version,
+            )),
+        }
     }
 
     pub fn data_mut<'a>(&'a self, store: &'a mut impl AsStoreMut) -> &'a mut WasiEnv {
@@ -208,6 +302,9 @@ impl WasiFunctionEnv {
         store: &mut impl AsStoreMut,
         module: &Module,
     ) -> Result<Imports, WasiError> {
+        if let Some(version) = get_unsupported_wasi_version(module) {
+            return Err(WasiError::UnsupportedWasiVersion(version));
+        }
         let wasi_versions =
             get_wasi_versions(module, false).ok_or(WasiError::UnknownWasiVersion)?;
 
@@ -257,6 +354,16 @@ pub struct WasiEnv {
     pub state: Arc<WasiState>,
     /// Implementation of the WASI runtime.
     pub(crate) runtime: Arc<dyn WasiRuntimeImplementation + Send + Sync + 'static>,
+    /// Calls made to the WASI functions (only when they are counted)
+    syscall_counts: Option<SyscallCounts>,
+    /// Errors the WASI functions fail with (only when they are injected)
+    faults: Option<WasiFaults>,
+    /// How long `sock_accept` waits on sockets without an accept timeout
+    default_accept_timeout: Option<Duration>,
+    /// Whether the program may open raw sockets
+    raw_sockets_allowed: bool,
+    /// Which of stdin, stdout and stderr were redirected for this program
+    redirected_stdio: [bool; 3],
 }
 
 impl WasiEnv {
@@ -272,6 +379,11 @@ impl WasiEnv {
             malloc: None,
             free: None,
             runtime: Arc::new(PluggableRuntimeImplementation::default()),
+            syscall_counts: None,
+            faults: None,
+            default_accept_timeout: None,
+            raw_sockets_allowed: false,
+            redirected_stdio: [false; 3],
         }
     }
 
@@ -288,16 +400,231 @@ impl WasiEnv {
         self.runtime = Arc::new(runtime);
     }
 
+    /// Calls made to the WASI functions, if they are being counted
+    pub fn syscall_counts(&self) -> Option<&SyscallCounts> {
+        self.syscall_counts.as_ref()
+    }
+
+    /// Makes this environment count the WASI calls into `counts`, e.g. to
+    /// collect them in a place the host can read after the run
+    ///
+    /// The counts are aggregated per function, which is much cheaper than
+    /// tracing every call.
+    pub fn set_syscall_counts(&mut self, counts: SyscallCounts) {
+        self.syscall_counts = Some(counts);
+    }
+
+    /// Called by every WASI function before it runs, returns the error the
+    /// call must fail with instead of running, if any
+    fn enter_syscall(&self, name: &str) -> Option<Errno> {
+        if let Some(counts) = &self.syscall_counts {
+            counts.record(name);
+        }
+        self.faults.as_ref().and_then(|faults| faults.call(name))
+    }
+
+    /// Errors the WASI functions are forced to fail with, if they are
+    /// being injected
+    pub fn faults(&self) -> Option<&WasiFaults> {
+        self.faults.as_ref()
+    }
+
+    /// Makes this environment inject the errors scheduled in `faults`
+    ///
+    /// Only the functions that return an error code can fail this way, the
+    /// others run as usual.
+    pub fn set_faults(&mut self, faults: WasiFaults) {
+        self.faults = Some(faults);
+    }
+
+    /// How long accepting a connection waits on sockets that have no accept
+    /// timeout of their own (`None` waits forever)
+    pub fn default_accept_timeout(&self) -> Option<Duration> {
+        self.default_accept_timeout
+    }
+
+    /// Sets how long accepting a connection waits on sockets that have no
+    /// accept timeout of their own
+    pub fn set_default_accept_timeout(&mut self, timeout: Option<Duration>) {
+        self.default_accept_timeout = timeout;
+    }
+
+    /// Whether the program may open raw sockets
+    pub fn raw_sockets_allowed(&self) -> bool {
+        self.raw_sockets_allowed
+    }
+
+    /// Sets whether the program may open raw sockets
+    pub fn set_raw_sockets_allowed(&mut self, allowed: bool) {
+        self.raw_sockets_allowed = allowed;
+    }
+
+    /// Marks which of stdin, stdout and stderr were redirected, these are
+    /// no terminals for this program whatever the runtime says. Other
+    /// programs sharing the runtime are not affected.
+    pub fn set_redirected_stdio(&mut self, stdin: bool, stdout: bool, stderr: bool) {
+        self.redirected_stdio = [stdin, stdout, stderr];
+    }
+
+    /// State of the TTY as this program sees it, where the standard streams
+    /// that were redirected are no terminals
+    pub fn tty_get(&self) -> WasiTtyState {
+        let mut tty = self.runtime.tty_get();
+        tty.stdin_tty &= !self.redirected_stdio[0];
+        tty.stdout_tty &= !self.redirected_stdio[1];
+        tty.stderr_tty &= !self.redirected_stdio[2];
+        tty
+    }
+
+    /// Updates the state of the TTY, the runtime keeps its own view of
+    /// whether the standard streams that were redirected are terminals
+    pub fn tty_set(&self, mut tty: WasiTtyState) {
+        let current = self.runtime.tty_get();
+        if self.redirected_stdio[0] {
+            tty.stdin_tty = current.stdin_tty;
+        }
+        if self.redirected_stdio[1] {
+            tty.stdout_tty = current.stdout_tty;
+        }
+        if self.redirected_stdio[2] {
+            tty.stderr_tty = current.stderr_tty;
+        }
+        self.runtime.tty_set(tty);
+    }
+
+    /// Sends a signal to the process, the blocking calls that can be
+    /// interrupted (such as `sock_recv()`, `thread_sleep()` and
+    /// `thread_spin()`) give up with `Errno::Intr`. A signal that is sent
+    /// again before it was taken stays pending only once.
+    pub fn signal(&self, sig: Signal) {
+        self.state.signals.lock().unwrap().push(sig);
+    }
+
+    /// Changes the size of the terminal the program sees, in columns and
+    /// rows, and lets the program know with `Signal::Sigwinch`
+    pub fn tty_resize(&self, cols: u32, rows: u32) {
+        let mut tty = self.runtime.tty_get();
+        tty.cols = cols;
+        tty.rows = rows;
+        self.runtime.tty_set(tty);
+        self.signal(Signal::Sigwinch);
+    }
+
+    /// Puts the TTY into raw mode, remembering the state it was in so that
+    /// [`WasiEnv::tty_restore`] can put it back
+    pub fn tty_raw_mode(&self) {
+        let mut before = self.state.tty_before_raw.lock().unwrap();
+        if before.is_none() {
+            *before = Some(self.runtime.tty_get());
+        }
+        self.runtime.tty_raw_mode();
+    }
+
+    /// Whether the standard output of the program is a terminal. Escape
+    /// codes and echoed input are left out when it isn't, as they would
+    /// only garble the output.
+    fn stdout_is_tty(&self) -> bool {
+        self.tty_get().stdout_tty
+    }
+
+    /// Clears part of the terminal, if the standard output is one
+    pub fn tty_clear(&self, clear: WasiTtyClear) -> std::io::Result<()> {
+        if !self.stdout_is_tty() {
+            return Ok(());
+        }
+        self.runtime.tty_clear(clear)
+    }
+
+    /// Moves the cursor of the terminal, if the standard output is one
+    pub fn tty_move_cursor(&self, row: u32, col: u32) -> std::io::Result<()> {
+        if !self.stdout_is_tty() {
+            return Ok(());
+        }
+        self.runtime.tty_move_cursor(row, col)
+    }
+
+    /// Turns a mode of the terminal on or off, keeping track of the modes
+    /// that are on so that [`WasiEnv::tty_restore`] can turn them off.
+    /// Nothing happens if the standard output is not a terminal.
+    pub fn tty_set_mode(&self, mode: WasiTtyMode, enabled: bool) -> std::io::Result<()> {
+        if !self.stdout_is_tty() {
+            return Ok(());
+        }
+        let mut modes = self.state.tty_modes.lock().unwrap();
+        self.runtime.tty_set_mode(mode, enabled)?;
+        if enabled {
+            modes.insert(mode);
+        } else {
+            modes.remove(&mode);
+        }
+        Ok(())
+    }
+
+    /// Puts the TTY back into the state it was in before the program put it
+    /// into raw mode, if it did, and turns off the modes the program turned
+    /// on. This is meant to be called once the program is done, however it
+    /// ended (including traps).
+    pub fn tty_restore(&self) {
+        if let Some(before) = self.state.tty_before_raw.lock().unwrap().take() {
+            self.runtime.tty_set(before);
+        }
+        let modes = std::mem::take(&mut *self.state.tty_modes.lock().unwrap());
+        for mode in modes.into_iter().rev() {
+            let _ = self.runtime.tty_set_mode(mode, false);
+        }
+        let _ = self.runtime.flush();
+    }
+
+    /// Feeds input to the program through its standard input as if it was
+    /// typed on the terminal, control characters and escape sequences
+    /// included. The input is echoed to the standard output if it is a
+    /// terminal, unless echo is off, as it is in raw mode. The standard input
+    /// has to be something that can be written to, such as a [`Pipe`].
+    pub fn tty_input(&self, data: &[u8]) -> Result<(), FsError> {
+        let mut stdin = self.state.stdin()?.ok_or(FsError::NoDevice)?;
+        std::io::Write::write_all(&mut stdin, data)?;
+        if self.runtime.tty_get().echo && self.stdout_is_tty() {
+            self.runtime.stdout(data)?;
+            self.runtime.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Takes the oldest signal that was sent to the process, if any. Signals
+    /// stay pending after they interrupted a blocking call, until they are
+    /// taken.
+    pub fn take_signal(&self) -> Option<Signal> {
+        self.state.signals.lock().unwrap().take()
+    }
+
+    /// Whether a blocking call should give up with `Errno::Intr` because a
+    /// signal was sent to the process. Every signal interrupts one call.
+    pub(crate) fn interrupted(&self) -> bool {
+        self.state.signals.lock().unwrap().interrupt()
+    }
+
     /// Returns the current thread ID
     pub fn current_thread_id(&self) -> WasiThreadId {
         self.id
     }
 
+    /// Passes a line the program logged on to the runtime, along with the
+    /// process and thread it came from (see [`WasiLogRecord::parse`])
+    pub fn log(&self, line: &str) {
+        let record = WasiLogRecord::parse(self.runtime.getpid(), self.id, line);
+        self.runtime.log(&record);
+    }
+
     /// Creates a new thread only this wasi environment
-    pub fn new_thread(&self) -> WasiThread {
+    pub fn new_thread(&self) -> Result<WasiThread, WasiThreadError> {
         let (tx, rx) = mpsc::channel();
 
         let mut guard = self.state.threading.lock().unwrap();
+        if let Some(max_threads) = guard.max_threads {
+            if guard.threads.len() >= max_threads {
+                return Err(WasiThreadError::TooManyThreads);
+            }
+        }
 
         guard.thread_seed += 1;
         let next_id: WasiThreadId = guard.thread_seed.into();
@@ -309,7 +636,7 @@ impl WasiEnv {
         };
 
         guard.threads.insert(thread.id, thread.clone());
-        thread
+        Ok(thread)
     }
 
     /// Copy the lazy reference so that when it's initialized during the
@@ -354,6 +681,24 @@ impl WasiEnv {
         Ok(())
     }
 
+    /// Describes the sockets the program has open, ordered by descriptor
+    pub fn socket_snapshot(&self) -> Vec<SocketInfo> {
+        let inodes = self.state.inodes.read().unwrap();
+        let fd_map = self.state.fs.fd_map.read().unwrap();
+        let mut sockets = fd_map
+            .iter()
+            .filter_map(|(fd, entry)| {
+                let guard = inodes.arena.get(entry.inode)?.read();
+                match guard.deref() {
+                    state::Kind::Socket { socket } => Some(socket.info(*fd)),
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+        sockets.sort_by_key(|socket| socket.fd);
+        sockets
+    }
+
     /// Accesses the virtual networking implementation
     pub fn net(&self) -> &(dyn VirtualNetworking) {
         self.runtime.networking()
@@ -441,53 +786,140 @@ pub fn generate_import_object_from_env(
     }
 }
 
+/// WASI functions that can be registered with a hook that runs before them,
+/// implemented for every arity the WASI functions have
+trait Syscall<Args, Ret> {
+    /// The function that runs the hook and then the WASI function
+    type Hooked;
+
+    fn hooked(self, name: &'static str) -> Self::Hooked;
+}
+
+/// Values returned by the WASI functions
+trait SyscallReturn: Sized {
+    /// The value returned by a call that fails with `errno`, if the function
+    /// can fail with an error code
+    fn from_fault(errno: Errno) -> Option<Self>;
+}
+
+impl SyscallReturn for Errno {
+    fn from_fault(errno: Errno) -> Option<Self> {
+        Some(errno)
+    }
+}
+
+impl SyscallReturn for Result<Errno, WasiError> {
+    fn from_fault(errno: Errno) -> Option<Self> {
+        Some(Ok(errno))
+    }
+}
+
+impl SyscallReturn for Result<(), WasiError> {
+    fn from_fault(_errno: Errno) -> Option<Self> {
+        None
+    }
+}
+
+impl SyscallReturn for BusErrno {
+    fn from_fault(_errno: Errno) -> Option<Self> {
+        None
+    }
+}
+
+macro_rules! impl_syscall {
+    ( $( $x:ident ),* ) => {
+        impl<$( $x, )* Ret, F> Syscall<( $( $x, )* ), Ret> for F
+        where
+            F: Fn(FunctionEnvMut<'_, WasiEnv>, $( $x ),*) -> Ret + Send + Sync + 'static,
+            Ret: SyscallReturn,
+        {
+            type Hooked = Box<dyn Fn(FunctionEnvMut<'_, WasiEnv>, $( $x ),*) -> Ret + Send + Sync>;
+
+            #[allow(non_snake_case)]
+            fn hooked(self, name: &'static str) -> Self::Hooked {
+                Box::new(move |ctx, $( $x ),*| {
+                    if let Some(ret) = ctx.data().enter_syscall(name).and_then(Ret::from_fault) {
+                        return ret;
+                    }
+                    self(ctx, $( $x ),*)
+                })
+            }
+        }
+    };
+}
+
+impl_syscall!();
+impl_syscall!(A1);
+impl_syscall!(A1, A2);
+impl_syscall!(A1, A2, A3);
+impl_syscall!(A1, A2, A3, A4);
+impl_syscall!(A1, A2, A3, A4, A5);
+impl_syscall!(A1, A2, A3, A4, A5, A6);
+impl_syscall!(A1, A2, A3, A4, A5, A6, A7);
+impl_syscall!(A1, A2, A3, A4, A5, A6, A7, A8);
+impl_syscall!(A1, A2, A3, A4, A5, A6, A7, A8, A9);
+impl_syscall!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10);
+impl_syscall!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11);
+impl_syscall!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12);
+impl_syscall!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13);
+
+/// Makes the WASI function `func` go through [`WasiEnv::enter_syscall`]
+/// before it runs, so the calls are counted and faulted on the typed call
+/// path itself
+fn hook<Args, Ret, F>(name: &'static str, func: F) -> F::Hooked
+where
+    F: Syscall<Args, Ret>,
+{
+    func.hooked(name)
+}
+
 fn wasi_unstable_exports(mut store: &mut impl AsStoreMut, env: &FunctionEnv<WasiEnv>) -> Exports {
     let namespace = namespace! {
-        "args_get" => Function::new_typed_with_env(&mut store, env, args_get::<Memory32>),
-        "args_sizes_get" => Function::new_typed_with_env(&mut store, env, args_sizes_get::<Memory32>),
-        "clock_res_get" => Function::new_typed_with_env(&mut store, env, clock_res_get::<Memory32>),
-        "clock_time_get" => Function::new_typed_with_env(&mut store, env, clock_time_get::<Memory32>),
-        "environ_get" => Function::new_typed_with_env(&mut store, env, environ_get::<Memory32>),
-        "environ_sizes_get" => Function::new_typed_with_env(&mut store, env, environ_sizes_get::<Memory32>),
-        "fd_advise" => Function::new_typed_with_env(&mut store, env, fd_advise),
-        "fd_allocate" => Function::new_typed_with_env(&mut store, env, fd_allocate),
-        "fd_close" => Function::new_typed_with_env(&mut store, env, fd_close),
-        "fd_datasync" => Function::new_typed_with_env(&mut store, env, fd_datasync),
-        "fd_fdstat_get" => Function::new_typed_with_env(&mut store, env, fd_fdstat_get::<Memory32>),
-        "fd_fdstat_set_flags" => Function::new_typed_with_env(&mut store, env, fd_fdstat_set_flags),
-        "fd_fdstat_set_rights" => Function::new_typed_with_env(&mut store, env, fd_fdstat_set_rights),
-        "fd_filestat_get" => Function::new_typed_with_env(&mut store, env, legacy::snapshot0::fd_filestat_get),
-        "fd_filestat_set_size" => Function::new_typed_with_env(&mut store, env, fd_filestat_set_size),
-        "fd_filestat_set_times" => Function::new_typed_with_env(&mut store, env, fd_filestat_set_times),
-        "fd_pread" => Function::new_typed_with_env(&mut store, env, fd_pread::<Memory32>),
-        "fd_prestat_get" => Function::new_typed_with_env(&mut store, env, fd_prestat_get::<Memory32>),
-        "fd_prestat_dir_name" => Function::new_typed_with_env(&mut store, env, fd_prestat_dir_name::<Memory32>),
-        "fd_pwrite" => Function::new_typed_with_env(&mut store, env, fd_pwrite::<Memory32>),
-        "fd_read" => Function::new_typed_with_env(&mut store, env, fd_read::<Memory32>),
-        "fd_readdir" => Function::new_typed_with_env(&mut store, env, fd_readdir::<Memory32>),
-        "fd_renumber" => Function::new_typed_with_env(&mut store, env, fd_renumber),
-        "fd_seek" => Function::new_typed_with_env(&mut store, env, legacy::snapshot0::fd_seek),
-        "fd_sync" => Function::new_typed_with_env(&mut store, env, fd_sync),
-        "fd_tell" => Function::new_typed_with_env(&mut store, env, fd_tell::<Memory32>),
-        "fd_write" => Function::new_typed_with_env(&mut store, env, fd_write::<Memory32>),
-        "path_create_directory" => Function::new_typed_with_env(&mut store, env, path_create_directory::<Memory32>),
-        "path_filestat_get" => Function::new_typed_with_env(&mut store, env, legacy::snapshot0::path_filestat_get),
-        "path_filestat_set_times" => Function::new_typed_with_env(&mut store, env, path_filestat_set_times::<Memory32>),
-        "path_link" => Function::new_typed_with_env(&mut store, env, path_link::<Memory32>),
-        "path_open" => Function::new_typed_with_env(&mut store, env, path_open::<Memory32>),
-        "path_readlink" => Function::new_typed_with_env(&mut store, env, path_readlink::<Memory32>),
-        "path_remove_directory" => Function::new_typed_with_env(&mut store, env, path_remove_directory::<Memory32>),
-        "path_rename" => Function::new_typed_with_env(&mut store, env, path_rename::<Memory32>),
-        "path_symlink" => Function::new_typed_with_env(&mut store, env, path_symlink::<Memory32>),
-        "path_unlink_file" => Function::new_typed_with_env(&mut store, env, path_unlink_file::<Memory32>),
-        "poll_oneoff" => Function::new_typed_with_env(&mut store, env, legacy::snapshot0::poll_oneoff),
-        "proc_exit" => Function::new_typed_with_env(&mut store, env, proc_exit),
-        "proc_raise" => Function::new_typed_with_env(&mut store, env, proc_raise),
-        "random_get" => Function::new_typed_with_env(&mut store, env, random_get::<Memory32>),
-        "sched_yield" => Function::new_typed_with_env(&mut store, env, sched_yield),
-        "sock_recv" => Function::new_typed_with_env(&mut store, env, sock_recv::<Memory32>),
-        "sock_send" => Function::new_typed_with_env(&mut store, env, sock_send::<Memory32>),
-        "sock_shutdown" => Function::new_typed_with_env(&mut store, env, sock_shutdown),
+        "args_get" => Function::new_typed_with_env(&mut store, env, hook("args_get", args_get::<Memory32>)),
+        "args_sizes_get" => Function::new_typed_with_env(&mut store, env, hook("args_sizes_get", args_sizes_get::<Memory32>)),
+        "clock_res_get" => Function::new_typed_with_env(&mut store, env, hook("clock_res_get", clock_res_get::<Memory32>)),
+        "clock_time_get" => Function::new_typed_with_env(&mut store, env, hook("clock_time_get", clock_time_get::<Memory32>)),
+        "environ_get" => Function::new_typed_with_env(&mut store, env, hook("environ_get", environ_get::<Memory32>)),
+        "environ_sizes_get" => Function::new_typed_with_env(&mut store, env, hook("environ_sizes_get", environ_sizes_get::<Memory32>)),
+        "fd_advise" => Function::new_typed_with_env(&mut store, env, hook("fd_advise", fd_advise)),
+        "fd_allocate" => Function::new_typed_with_env(&mut store, env, hook("fd_allocate", fd_allocate)),
+        "fd_close" => Function::new_typed_with_env(&mut store, env, hook("fd_close", fd_close)),
+        "fd_datasync" => Function::new_typed_with_env(&mut store, env, hook("fd_datasync", fd_datasync)),
+        "fd_fdstat_get" => Function::new_typed_with_env(&mut store, env, hook("fd_fdstat_get", fd_fdstat_get::<Memory32>)),
+        "fd_fdstat_set_flags" => Function::new_typed_with_env(&mut store, env, hook("fd_fdstat_set_flags", fd_fdstat_set_flags)),
+        "fd_fdstat_set_rights" => Function::new_typed_with_env(&mut store, env, hook("fd_fdstat_set_rights", fd_fdstat_set_rights)),
+        "fd_filestat_get" => Function::new_typed_with_env(&mut store, env, hook("fd_filestat_get", legacy::snapshot0::fd_filestat_get)),
+        "fd_filestat_set_size" => Function::new_typed_with_env(&mut store, env, hook("fd_filestat_set_size", fd_filestat_set_size)),
+        "fd_filestat_set_times" => Function::new_typed_with_env(&mut store, env, hook("fd_filestat_set_times", fd_filestat_set_times)),
+        "fd_pread" => Function::new_typed_with_env(&mut store, env, hook("fd_pread", fd_pread::<Memory32>)),
+        "fd_prestat_get" => Function::new_typed_with_env(&mut store, env, hook("fd_prestat_get", fd_prestat_get::<Memory32>)),
+        "fd_prestat_dir_name" => Function::new_typed_with_env(&mut store, env, hook("fd_prestat_dir_name", fd_prestat_dir_name::<Memory32>)),
+        "fd_pwrite" => Function::new_typed_with_env(&mut store, env, hook("fd_pwrite", fd_pwrite::<Memory32>)),
+        "fd_read" => Function::new_typed_with_env(&mut store, env, hook("fd_read", fd_read::<Memory32>)),
+        "fd_readdir" => Function::new_typed_with_env(&mut store, env, hook("fd_readdir", fd_readdir::<Memory32>)),
+        "fd_renumber" => Function::new_typed_with_env(&mut store, env, hook("fd_renumber", fd_renumber)),
+        "fd_seek" => Function::new_typed_with_env(&mut store, env, hook("fd_seek", legacy::snapshot0::fd_seek)),
+        "fd_sync" => Function::new_typed_with_env(&mut store, env, hook("fd_sync", fd_sync)),
+        "fd_tell" => Function::new_typed_with_env(&mut store, env, hook("fd_tell", fd_tell::<Memory32>)),
+        "fd_write" => Function::new_typed_with_env(&mut store, env, hook("fd_write", fd_write::<Memory32>)),
+        "path_create_directory" => Function::new_typed_with_env(&mut store, env, hook("path_create_directory", path_create_directory::<Memory32>)),
+        "path_filestat_get" => Function::new_typed_with_env(&mut store, env, hook("path_filestat_get", legacy::snapshot0::path_filestat_get)),
+        "path_filestat_set_times" => Function::new_typed_with_env(&mut store, env, hook("path_filestat_set_times", path_filestat_set_times::<Memory32>)),
+        "path_link" => Function::new_typed_with_env(&mut store, env, hook("path_link", path_link::<Memory32>)),
+        "path_open" => Function::new_typed_with_env(&mut store, env, hook("path_open", path_open::<Memory32>)),
+        "path_readlink" => Function::new_typed_with_env(&mut store, env, hook("path_readlink", path_readlink::<Memory32>)),
+        "path_remove_directory" => Function::new_typed_with_env(&mut store, env, hook("path_remove_directory", path_remove_directory::<Memory32>)),
+        "path_rename" => Function::new_typed_with_env(&mut store, env, hook("path_rename", path_rename::<Memory32>)),
+        "path_symlink" => Function::new_typed_with_env(&mut store, env, hook("path_symlink", path_symlink::<Memory32>)),
+        "path_unlink_file" => Function::new_typed_with_env(&mut store, env, hook("path_unlink_file", path_unlink_file::<Memory32>)),
+        "poll_oneoff" => Function::new_typed_with_env(&mut store, env, hook("poll_oneoff", legacy::snapshot0::poll_oneoff)),
+        "proc_exit" => Function::new_typed_with_env(&mut store, env, hook("proc_exit", proc_exit)),
+        "proc_raise" => Function::new_typed_with_env(&mut store, env, hook("proc_raise", proc_raise)),
+        "random_get" => Function::new_typed_with_env(&mut store, env, hook("random_get", random_get::<Memory32>)),
+        "sched_yield" => Function::new_typed_with_env(&mut store, env, hook("sched_yield", sched_yield)),
+        "sock_recv" => Function::new_typed_with_env(&mut store, env, hook("sock_recv", sock_recv::<Memory32>)),
+        "sock_send" => Function::new_typed_with_env(&mut store, env, hook("sock_send", sock_send::<Memory32>)),
+        "sock_shutdown" => Function::new_typed_with_env(&mut store, env, hook("sock_shutdown", sock_shutdown)),
     };
     namespace
 }
@@ -497,51 +929,51 @@ fn wasi_snapshot_preview1_exports(
     env: &FunctionEnv<WasiEnv>,
 ) -> Exports {
     let namespace = namespace! {
-        "args_get" => Function::new_typed_with_env(&mut store, env, args_get::<Memory32>),
-        "args_sizes_get" => Function::new_typed_with_env(&mut store, env, args_sizes_get::<Memory32>),
-        "clock_res_get" => Function::new_typed_with_env(&mut store, env, clock_res_get::<Memory32>),
-        "clock_time_get" => Function::new_typed_with_env(&mut store, env, clock_time_get::<Memory32>),
-        "environ_get" => Function::new_typed_with_env(&mut store, env, environ_get::<Memory32>),
-        "environ_sizes_get" => Function::new_typed_with_env(&mut store, env, environ_sizes_get::<Memory32>),
-        "fd_advise" => Function::new_typed_with_env(&mut store, env, fd_advise),
-        "fd_allocate" => Function::new_typed_with_env(&mut store, env, fd_allocate),
-        "fd_close" => Function::new_typed_with_env(&mut store, env, fd_close),
-        "fd_datasync" => Function::new_typed_with_env(&mut store, env, fd_datasync),
-        "fd_fdstat_get" => Function::new_typed_with_env(&mut store, env, fd_fdstat_get::<Memory32>),
-        "fd_fdstat_set_flags" => Function::new_typed_with_env(&mut store, env, fd_fdstat_set_flags),
-        "fd_fdstat_set_rights" => Function::new_typed_with_env(&mut store, env, fd_fdstat_set_rights),
-        "fd_filestat_get" => Function::new_typed_with_env(&mut store, env, fd_filestat_get::<Memory32>),
-        "fd_filestat_set_size" => Function::new_typed_with_env(&mut store, env, fd_filestat_set_size),
-        "fd_filestat_set_times" => Function::new_typed_with_env(&mut store, env, fd_filestat_set_times),
-        "fd_pread" => Function::new_typed_with_env(&mut store, env, fd_pread::<Memory32>),
-        "fd_prestat_get" => Function::new_typed_with_env(&mut store, env, fd_prestat_get::<Memory32>),
-        "fd_prestat_dir_name" => Function::new_typed_with_env(&mut store, env, fd_prestat_dir_name::<Memory32>),
-        "fd_pwrite" => Function::new_typed_with_env(&mut store, env, fd_pwrite::<Memory32>),
-        "fd_read" => Function::new_typed_with_env(&mut store, env, fd_read::<Memory32>),
-        "fd_readdir" => Function::new_typed_with_env(&mut store, env, fd_readdir::<Memory32>),
-        "fd_renumber" => Function::new_typed_with_env(&mut store, env, fd_renumber),
-        "fd_seek" => Function::new_typed_with_env(&mut store, env, fd_seek::<Memory32>),
-        "fd_sync" => Function::new_typed_with_env(&mut store, env, fd_sync),
-        "fd_tell" => Function::new_typed_with_env(&mut store, env, fd_tell::<Memory32>),
-        "fd_write" => Function::new_typed_with_env(&mut store, env, fd_write::<Memory32>),
-        "path_create_directory" => Function::new_typed_with_env(&mut store, env, path_create_directory::<Memory32>),
-        "path_filestat_get" => Function::new_typed_with_env(&mut store, env, path_filestat_get::<Memory32>),
-        "path_filestat_set_times" => Function::new_typed_with_env(&mut store, env, path_filestat_set_times::<Memory32>),
-        "path_link" => Function::new_typed_with_env(&mut store, env, path_link::<Memory32>),
-        "path_open" => Function::new_typed_with_env(&mut store, env, path_open::<Memory32>),
-        "path_readlink" => Function::new_typed_with_env(&mut store, env, path_readlink::<Memory32>),
-        "path_remove_directory" => Function::new_typed_with_env(&mut store, env, path_remove_directory::<Memory32>),
-        "path_rename" => Function::new_typed_with_env(&mut store, env, path_rename::<Memory32>),
-        "path_symlink" => Function::new_typed_with_env(&mut store, env, path_symlink::<Memory32>),
-        "path_unlink_file" => Function::new_typed_with_env(&mut store, env, path_unlink_file::<Memory32>),
-        "poll_oneoff" => Function::new_typed_with_env(&mut store, env, poll_oneoff::<Memory32>),
-        "proc_exit" => Function::new_typed_with_env(&mut store, env, proc_exit),
-        "proc_raise" => Function::new_typed_with_env(&mut store, env, proc_raise),
-        "random_get" => Function::new_typed_with_env(&mut store, env, random_get::<Memory32>),
-        "sched_yield" => Function::new_typed_with_env(&mut store, env, sched_yield),
-        "sock_recv" => Function::new_typed_with_env(&mut store, env, sock_recv::<Memory32>),
-        "sock_send" => Function::new_typed_with_env(&mut store, env, sock_send::<Memory32>),
-        "sock_shutdown" => Function::new_typed_with_env(&mut store, env, sock_shutdown),
+        "args_get" => Function::new_typed_with_env(&mut store, env, hook("args_get", args_get::<Memory32>)),
+        "args_sizes_get" => Function::new_typed_with_env(&mut store, env, hook("args_sizes_get", args_sizes_get::<Memory32>)),
+        "clock_res_get" => Function::new_typed_with_env(&mut store, env, hook("clock_res_get", clock_res_get::<Memory32>)),
+        "clock_time_get" => Function::new_typed_with_env(&mut store, env, hook("clock_time_get", clock_time_get::<Memory32>)),
+        "environ_get" => Function::new_typed_with_env(&mut store, env, hook("environ_get", environ_get::<Memory32>)),
+        "environ_sizes_get" => Function::new_typed_with_env(&mut store, env, hook("environ_sizes_get", environ_sizes_get::<Memory32>)),
+        "fd_advise" => Function::new_typed_with_env(&mut store, env, hook("fd_advise", fd_advise)),
+        "fd_allocate" => Function::new_typed_with_env(&mut store, env, hook("fd_allocate", fd_allocate)),
+        "fd_close" => Function::new_typed_with_env(&mut store, env, hook("fd_close", fd_close)),
+        "fd_datasync" => Function::new_typed_with_env(&mut store, env, hook("fd_datasync", fd_datasync)),
+        "fd_fdstat_get" => Function::new_typed_with_env(&mut store, env, hook("fd_fdstat_get", fd_fdstat_get::<Memory32>)),
+        "fd_fdstat_set_flags" => Function::new_typed_with_env(&mut store, env, hook("fd_fdstat_set_flags", fd_fdstat_set_flags)),
+        "fd_fdstat_set_rights" => Function::new_typed_with_env(&mut store, env, hook("fd_fdstat_set_rights", fd_fdstat_set_rights)),
+        "fd_filestat_get" => Function::new_typed_with_env(&mut store, env, hook("fd_filestat_get", fd_filestat_get::<Memory32>)),
+        "fd_filestat_set_size" => Function::new_typed_with_env(&mut store, env, hook("fd_filestat_set_size", fd_filestat_set_size)),
+        "fd_filestat_set_times" => Function::new_typed_with_env(&mut store, env, hook("fd_filestat_set_times", fd_filestat_set_times)),
+        "fd_pread" => Function::new_typed_with_env(&mut store, env, hook("fd_pread", fd_pread::<Memory32>)),
+        "fd_prestat_get" => Function::new_typed_with_env(&mut store, env, hook("fd_prestat_get", fd_prestat_get::<Memory32>)),
+        "fd_prestat_dir_name" => Function::new_typed_with_env(&mut store, env, hook("fd_prestat_dir_name", fd_prestat_dir_name::<Memory32>)),
+        "fd_pwrite" => Function::new_typed_with_env(&mut store, env, hook("fd_pwrite", fd_pwrite::<Memory32>)),
+        "fd_read" => Function::new_typed_with_env(&mut store, env, hook("fd_read", fd_read::<Memory32>)),
+        "fd_readdir" => Function::new_typed_with_env(&mut store, env, hook("fd_readdir", fd_readdir::<Memory32>)),
+        "fd_renumber" => Function::new_typed_with_env(&mut store, env, hook("fd_renumber", fd_renumber)),
+        "fd_seek" => Function::new_typed_with_env(&mut store, env, hook("fd_seek", fd_seek::<Memory32>)),
+        "fd_sync" => Function::new_typed_with_env(&mut store, env, hook("fd_sync", fd_sync)),
+        "fd_tell" => Function::new_typed_with_env(&mut store, env, hook("fd_tell", fd_tell::<Memory32>)),
+        "fd_write" => Function::new_typed_with_env(&mut store, env, hook("fd_write", fd_write::<Memory32>)),
+        "path_create_directory" => Function::new_typed_with_env(&mut store, env, hook("path_create_directory", path_create_directory::<Memory32>)),
+        "path_filestat_get" => Function::new_typed_with_env(&mut store, env, hook("path_filestat_get", path_filestat_get::<Memory32>)),
+        "path_filestat_set_times" => Function::new_typed_with_env(&mut store, env, hook("path_filestat_set_times", path_filestat_set_times::<Memory32>)),
+        "path_link" => Function::new_typed_with_env(&mut store, env, hook("path_link", path_link::<Memory32>)),
+        "path_open" => Function::new_typed_with_env(&mut store, env, hook("path_open", path_open::<Memory32>)),
+        "path_readlink" => Function::new_typed_with_env(&mut store, env, hook("path_readlink", path_readlink::<Memory32>)),
+        "path_remove_directory" => Function::new_typed_with_env(&mut store, env, hook("path_remove_directory", path_remove_directory::<Memory32>)),
+        "path_rename" => Function::new_typed_with_env(&mut store, env, hook("path_rename", path_rename::<Memory32>)),
+        "path_symlink" => Function::new_typed_with_env(&mut store, env, hook("path_symlink", path_symlink::<Memory32>)),
+        "path_unlink_file" => Function::new_typed_with_env(&mut store, env, hook("path_unlink_file", path_unlink_file::<Memory32>)),
+        "poll_oneoff" => Function::new_typed_with_env(&mut store, env, hook("poll_oneoff", poll_oneoff::<Memory32>)),
+        "proc_exit" => Function::new_typed_with_env(&mut store, env, hook("proc_exit", proc_exit)),
+        "proc_raise" => Function::new_typed_with_env(&mut store, env, hook("proc_raise", proc_raise)),
+        "random_get" => Function::new_typed_with_env(&mut store, env, hook("random_get", random_get::<Memory32>)),
+        "sched_yield" => Function::new_typed_with_env(&mut store, env, hook("sched_yield", sched_yield)),
+        "sock_recv" => Function::new_typed_with_env(&mut store, env, hook("sock_recv", sock_recv::<Memory32>)),
+        "sock_send" => Function::new_typed_with_env(&mut store, env, hook("sock_send", sock_send::<Memory32>)),
+        "sock_shutdown" => Function::new_typed_with_env(&mut store, env, hook("sock_shutdown", sock_shutdown)),
     };
     namespace
 }
@@ -587,113 +1019,129 @@ fn generate_import_object_wasix32_v1(
     use self::wasix32::*;
     imports! {
         "wasix_32v1" => {
-            "args_get" => Function::new_typed_with_env(&mut store, env, args_get),
-            "args_sizes_get" => Function::new_typed_with_env(&mut store, env, args_sizes_get),
-            "clock_res_get" => Function::new_typed_with_env(&mut store, env, clock_res_get),
-            "clock_time_get" => Function::new_typed_with_env(&mut store, env, clock_time_get),
-            "environ_get" => Function::new_typed_with_env(&mut store, env, environ_get),
-            "environ_sizes_get" => Function::new_typed_with_env(&mut store, env, environ_sizes_get),
-            "fd_advise" => Function::new_typed_with_env(&mut store, env, fd_advise),
-            "fd_allocate" => Function::new_typed_with_env(&mut store, env, fd_allocate),
-            "fd_close" => Function::new_typed_with_env(&mut store, env, fd_close),
-            "fd_datasync" => Function::new_typed_with_env(&mut store, env, fd_datasync),
-            "fd_fdstat_get" => Function::new_typed_with_env(&mut store, env, fd_fdstat_get),
-            "fd_fdstat_set_flags" => Function::new_typed_with_env(&mut store, env, fd_fdstat_set_flags),
-            "fd_fdstat_set_rights" => Function::new_typed_with_env(&mut store, env, fd_fdstat_set_rights),
-            "fd_filestat_get" => Function::new_typed_with_env(&mut store, env, fd_filestat_get),
-            "fd_filestat_set_size" => Function::new_typed_with_env(&mut store, env, fd_filestat_set_size),
-            "fd_filestat_set_times" => Function::new_typed_with_env(&mut store, env, fd_filestat_set_times),
-            "fd_pread" => Function::new_typed_with_env(&mut store, env, fd_pread),
-            "fd_prestat_get" => Function::new_typed_with_env(&mut store, env, fd_prestat_get),
-            "fd_prestat_dir_name" => Function::new_typed_with_env(&mut store, env, fd_prestat_dir_name),
-            "fd_pwrite" => Function::new_typed_with_env(&mut store, env, fd_pwrite),
-            "fd_read" => Function::new_typed_with_env(&mut store, env, fd_read),
-            "fd_readdir" => Function::new_typed_with_env(&mut store, env, fd_readdir),
-            "fd_renumber" => Function::new_typed_with_env(&mut store, env, fd_renumber),
-            "fd_dup" => Function::new_typed_with_env(&mut store, env, fd_dup),
-            "fd_event" => Function::new_typed_with_env(&mut store, env, fd_event),
-            "fd_seek" => Function::new_typed_with_env(&mut store, env, fd_seek),
-            "fd_sync" => Function::new_typed_with_env(&mut store, env, fd_sync),
-            "fd_tell" => Function::new_typed_with_env(&mut store, env, fd_tell),
-            "fd_write" => Function::new_typed_with_env(&mut store, env, fd_write),
-            "fd_pipe" => Function::new_typed_with_env(&mut store, env, fd_pipe),
-            "path_create_directory" => Function::new_typed_with_env(&mut store, env, path_create_directory),
-            "path_filestat_get" => Function::new_typed_with_env(&mut store, env, path_filestat_get),
-            "path_filestat_set_times" => Function::new_typed_with_env(&mut store, env, path_filestat_set_times),
-            "path_link" => Function::new_typed_with_env(&mut store, env, path_link),
-            "path_open" => Function::new_typed_with_env(&mut store, env, path_open),
-            "path_readlink" => Function::new_typed_with_env(&mut store, env, path_readlink),
-            "path_remove_directory" => Function::new_typed_with_env(&mut store, env, path_remove_directory),
-            "path_rename" => Function::new_typed_with_env(&mut store, env, path_rename),
-            "path_symlink" => Function::new_typed_with_env(&mut store, env, path_symlink),
-            "path_unlink_file" => Function::new_typed_with_env(&mut store, env, path_unlink_file),
-            "poll_oneoff" => Function::new_typed_with_env(&mut store, env, poll_oneoff),
-            "proc_exit" => Function::new_typed_with_env(&mut store, env, proc_exit),
-            "proc_raise" => Function::new_typed_with_env(&mut store, env, proc_raise),
-            "random_get" => Function::new_typed_with_env(&mut store, env, random_get),
-            "tty_get" => Function::new_typed_with_env(&mut store, env, tty_get),
-            "tty_set" => Function::new_typed_with_env(&mut store, env, tty_set),
-            "getcwd" => Function::new_typed_with_env(&mut store, env, getcwd),
-            "chdir" => Function::new_typed_with_env(&mut store, env, chdir),
-            "thread_spawn" => Function::new_typed_with_env(&mut store, env, thread_spawn),
-            "thread_sleep" => Function::new_typed_with_env(&mut store, env, thread_sleep),
-            "thread_id" => Function::new_typed_with_env(&mut store, env, thread_id),
-            "thread_join" => Function::new_typed_with_env(&mut store, env, thread_join),
-            "thread_parallelism" => Function::new_typed_with_env(&mut store, env, thread_parallelism),
-            "thread_exit" => Function::new_typed_with_env(&mut store, env, thread_exit),
-            "sched_yield" => Function::new_typed_with_env(&mut store, env, sched_yield),
-            "getpid" => Function::new_typed_with_env(&mut store, env, getpid),
-            "process_spawn" => Function::new_typed_with_env(&mut store, env, process_spawn),
-            "bus_open_local" => Function::new_typed_with_env(&mut store, env, bus_open_local),
-            "bus_open_remote" => Function::new_typed_with_env(&mut store, env, bus_open_remote),
-            "bus_close" => Function::new_typed_with_env(&mut store, env, bus_close),
-            "bus_call" => Function::new_typed_with_env(&mut store, env, bus_call),
-            "bus_subcall" => Function::new_typed_with_env(&mut store, env, bus_subcall),
-            "bus_poll" => Function::new_typed_with_env(&mut store, env, bus_poll),
-            "call_reply" => Function::new_typed_with_env(&mut store, env, call_reply),
-            "call_fault" => Function::new_typed_with_env(&mut store, env, call_fault),
-            "call_close" => Function::new_typed_with_env(&mut store, env, call_close),
-            "ws_connect" => Function::new_typed_with_env(&mut store, env, ws_connect),
-            "http_request" => Function::new_typed_with_env(&mut store, env, http_request),
-            "http_status" => Function::new_typed_with_env(&mut store, env, http_status),
-            "port_bridge" => Function::new_typed_with_env(&mut store, env, port_bridge),
-            "port_unbridge" => Function::new_typed_with_env(&mut store, env, port_unbridge),
-            "port_dhcp_acquire" => Function::new_typed_with_env(&mut store, env, port_dhcp_acquire),
-            "port_addr_add" => Function::new_typed_with_env(&mut store, env, port_addr_add),
-            "port_addr_remove" => Function::new_typed_with_env(&mut store, env, port_addr_remove),
-            "port_addr_clear" => Function::new_typed_with_env(&mut store, env, port_addr_clear),
-            "port_addr_list" => Function::new_typed_with_env(&mut store, env, port_addr_list),
-            "port_mac" => Function::new_typed_with_env(&mut store, env, port_mac),
-            "port_gateway_set" => Function::new_typed_with_env(&mut store, env, port_gateway_set),
-            "port_route_add" => Function::new_typed_with_env(&mut store, env, port_route_add),
-            "port_route_remove" => Function::new_typed_with_env(&mut store, env, port_route_remove),
-            "port_route_clear" => Function::new_typed_with_env(&mut store, env, port_route_clear),
-            "port_route_list" => Function::new_typed_with_env(&mut store, env, port_route_list),
-            "sock_status" => Function::new_typed_with_env(&mut store, env, sock_status),
-            "sock_addr_local" => Function::new_typed_with_env(&mut store, env, sock_addr_local),
-            "sock_addr_peer" => Function::new_typed_with_env(&mut store, env, sock_addr_peer),
-            "sock_open" => Function::new_typed_with_env(&mut store, env, sock_open),
-            "sock_set_opt_flag" => Function::new_typed_with_env(&mut store, env, sock_set_opt_flag),
-            "sock_get_opt_flag" => Function::new_typed_with_env(&mut store, env, sock_get_opt_flag),
-            "sock_set_opt_time" => Function::new_typed_with_env(&mut store, env, sock_set_opt_time),
-            "sock_get_opt_time" => Function::new_typed_with_env(&mut store, env, sock_get_opt_time),
-            "sock_set_opt_size" => Function::new_typed_with_env(&mut store, env, sock_set_opt_size),
-            "sock_get_opt_size" => Function::new_typed_with_env(&mut store, env, sock_get_opt_size),
-            "sock_join_multicast_v4" => Function::new_typed_with_env(&mut store, env, sock_join_multicast_v4),
-            "sock_leave_multicast_v4" => Function::new_typed_with_env(&mut store, env, sock_leave_multicast_v4),
-            "sock_join_multicast_v6" => Function::new_typed_with_env(&mut store, env, sock_join_multicast_v6),
-            "sock_leave_multicast_v6" => Function::new_typed_with_env(&mut store, env, sock_leave_multicast_v6),
-            "sock_bind" => Function::new_typed_with_env(&mut store, env, sock_bind),
-            "sock_listen" => Function::new_typed_with_env(&mut store, env, sock_listen),
-            "sock_accept" => Function::new_typed_with_env(&mut store, env, sock_accept),
-            "sock_connect" => Function::new_typed_with_env(&mut store, env, sock_connect),
-            "sock_recv" => Function::new_typed_with_env(&mut store, env, sock_recv),
-            "sock_recv_from" => Function::new_typed_with_env(&mut store, env, sock_recv_from),
-            "sock_send" => Function::new_typed_with_env(&mut store, env, sock_send),
-            "sock_send_to" => Function::new_typed_with_env(&mut store, env, sock_send_to),
-            "sock_send_file" => Function::new_typed_with_env(&mut store, env, sock_send_file),
-            "sock_shutdown" => Function::new_typed_with_env(&mut store, env, sock_shutdown),
-            "resolve" => Function::new_typed_with_env(&mut store, env, resolve),
+            "args_get" => Function::new_typed_with_env(&mut store, env, hook("args_get", args_get)),
+            "args_sizes_get" => Function::new_typed_with_env(&mut store, env, hook("args_sizes_get", args_sizes_get)),
+            "clock_res_get" => Function::new_typed_with_env(&mut store, env, hook("clock_res_get", clock_res_get)),
+            "clock_time_get" => Function::new_typed_with_env(&mut store, env, hook("clock_time_get", clock_time_get)),
+            "environ_get" => Function::new_typed_with_env(&mut store, env, hook("environ_get", environ_get)),
+            "environ_sizes_get" => Function::new_typed_with_env(&mut store, env, hook("environ_sizes_get", environ_sizes_get)),
+            "fd_advise" => Function::new_typed_with_env(&mut store, env, hook("fd_advise", fd_advise)),
+            "fd_allocate" => Function::new_typed_with_env(&mut store, env, hook("fd_allocate", fd_allocate)),
+            "fd_close" => Function::new_typed_with_env(&mut store, env, hook("fd_close", fd_close)),
+            "fd_datasync" => Function::new_typed_with_env(&mut store, env, hook("fd_datasync", fd_datasync)),
+            "fd_fdstat_get" => Function::new_typed_with_env(&mut store, env, hook("fd_fdstat_get", fd_fdstat_get)),
+            "fd_fdstat_set_flags" => Function::new_typed_with_env(&mut store, env, hook("fd_fdstat_set_flags", fd_fdstat_set_flags)),
+            "fd_fdstat_set_rights" => Function::new_typed_with_env(&mut store, env, hook("fd_fdstat_set_rights", fd_fdstat_set_rights)),
+            "fd_filestat_get" => Function::new_typed_with_env(&mut store, env, hook("fd_filestat_get", fd_filestat_get)),
+            "fd_filestat_set_size" => Function::new_typed_with_env(&mut store, env, hook("fd_filestat_set_size", fd_filestat_set_size)),
+            "fd_filestat_set_times" => Function::new_typed_with_env(&mut store, env, hook("fd_filestat_set_times", fd_filestat_set_times)),
+            "fd_pread" => Function::new_typed_with_env(&mut store, env, hook("fd_pread", fd_pread)),
+            "fd_prestat_get" => Function::new_typed_with_env(&mut store, env, hook("fd_prestat_get", fd_prestat_get)),
+            "fd_prestat_dir_name" => Function::new_typed_with_env(&mut store, env, hook("fd_prestat_dir_name", fd_prestat_dir_name)),
+            "fd_pwrite" => Function::new_typed_with_env(&mut store, env, hook("fd_pwrite", fd_pwrite)),
+            "fd_read" => Function::new_typed_with_env(&mut store, env, hook("fd_read", fd_read)),
+            "fd_readdir" => Function::new_typed_with_env(&mut store, env, hook("fd_readdir", fd_readdir)),
+            "fd_renumber" => Function::new_typed_with_env(&mut store, env, hook("fd_renumber", fd_renumber)),
+            "fd_dup" => Function::new_typed_with_env(&mut store, env, hook("fd_dup", fd_dup)),
+            "fd_event" => Function::new_typed_with_env(&mut store, env, hook("fd_event", fd_event)),
+            "fd_seek" => Function::new_typed_with_env(&mut store, env, hook("fd_seek", fd_seek)),
+            "fd_sync" => Function::new_typed_with_env(&mut store, env, hook("fd_sync", fd_sync)),
+            "fd_tell" => Function::new_typed_with_env(&mut store, env, hook("fd_tell", fd_tell)),
+            "fd_write" => Function::new_typed_with_env(&mut store, env, hook("fd_write", fd_write)),
+            "fd_pipe" => Function::new_typed_with_env(&mut store, env, hook("fd_pipe", fd_pipe)),
+            "path_create_directory" => Function::new_typed_with_env(&mut store, env, hook("path_create_directory", path_create_directory)),
+            "path_filestat_get" => Function::new_typed_with_env(&mut store, env, hook("path_filestat_get", path_filestat_get)),
+            "path_filestat_set_times" => Function::new_typed_with_env(&mut store, env, hook("path_filestat_set_times", path_filestat_set_times)),
+            "path_link" => Function::new_typed_with_env(&mut store, env, hook("path_link", path_link)),
+            "path_open" => Function::new_typed_with_env(&mut store, env, hook("path_open", path_open)),
+            "path_readlink" => Function::new_typed_with_env(&mut store, env, hook("path_readlink", path_readlink)),
+            "path_remove_directory" => Function::new_typed_with_env(&mut store, env, hook("path_remove_directory", path_remove_directory)),
+            "path_rename" => Function::new_typed_with_env(&mut store, env, hook("path_rename", path_rename)),
+            "path_symlink" => Function::new_typed_with_env(&mut store, env, hook("path_symlink", path_symlink)),
+            "path_unlink_file" => Function::new_typed_with_env(&mut store, env, hook("path_unlink_file", path_unlink_file)),
+            "poll_oneoff" => Function::new_typed_with_env(&mut store, env, hook("poll_oneoff", poll_oneoff)),
+            "proc_exit" => Function::new_typed_with_env(&mut store, env, hook("proc_exit", proc_exit)),
+            "proc_raise" => Function::new_typed_with_env(&mut store, env, hook("proc_raise", proc_raise)),
+            "proc_log" => Function::new_typed_with_env(&mut store, env, hook("proc_log", proc_log)),
+            "random_get" => Function::new_typed_with_env(&mut store, env, hook("random_get", random_get)),
+            "tty_get" => Function::new_typed_with_env(&mut store, env, hook("tty_get", tty_get)),
+            "tty_set" => Function::new_typed_with_env(&mut store, env, hook("tty_set", tty_set)),
+            "tty_set_raw" => Function::new_typed_with_env(&mut store, env, hook("tty_set_raw", tty_set_raw)),
+            "tty_set_mode" => Function::new_typed_with_env(&mut store, env, hook("tty_set_mode", tty_set_mode)),
+            "tty_clear" => Function::new_typed_with_env(&mut store, env, hook("tty_clear", tty_clear)),
+            "tty_move_cursor" => Function::new_typed_with_env(&mut store, env, hook("tty_move_cursor", tty_move_cursor)),
+            "stdio_flush" => Function::new_typed_with_env(&mut store, env, hook("stdio_flush", stdio_flush)),
+            "getcwd" => Function::new_typed_with_env(&mut store, env, hook("getcwd", getcwd)),
+            "chdir" => Function::new_typed_with_env(&mut store, env, hook("chdir", chdir)),
+            "thread_spawn" => Function::new_typed_with_env(&mut store, env, hook("thread_spawn", thread_spawn)),
+            "thread_sleep" => Function::new_typed_with_env(&mut store, env, hook("thread_sleep", thread_sleep)),
+            "thread_spin" => Function::new_typed_with_env(&mut store, env, hook("thread_spin", thread_spin)),
+            "thread_id" => Function::new_typed_with_env(&mut store, env, hook("thread_id", thread_id)),
+            "thread_join" => Function::new_typed_with_env(&mut store, env, hook("thread_join", thread_join)),
+            "thread_join_v2" => Function::new_typed_with_env(&mut store, env, hook("thread_join_v2", thread_join_v2)),
+            "thread_detach" => Function::new_typed_with_env(&mut store, env, hook("thread_detach", thread_detach)),
+            "thread_parallelism" => Function::new_typed_with_env(&mut store, env, hook("thread_parallelism", thread_parallelism)),
+            "thread_affinity_set" => Function::new_typed_with_env(&mut store, env, hook("thread_affinity_set", thread_affinity_set)),
+            "thread_affinity_get" => Function::new_typed_with_env(&mut store, env, hook("thread_affinity_get", thread_affinity_get)),
+            "futex_wait" => Function::new_typed_with_env(&mut store, env, hook("futex_wait", futex_wait)),
+            "futex_wake" => Function::new_typed_with_env(&mut store, env, hook("futex_wake", futex_wake)),
+            "thread_exit" => Function::new_typed_with_env(&mut store, env, hook("thread_exit", thread_exit)),
+            "sched_yield" => Function::new_typed_with_env(&mut store, env, hook("sched_yield", sched_yield)),
+            "getpid" => Function::new_typed_with_env(&mut store, env, hook("getpid", getpid)),
+            "process_spawn" => Function::new_typed_with_env(&mut store, env, hook("process_spawn", process_spawn)),
+            "bus_open_local" => Function::new_typed_with_env(&mut store, env, hook("bus_open_local", bus_open_local)),
+            "bus_open_remote" => Function::new_typed_with_env(&mut store, env, hook("bus_open_remote", bus_open_remote)),
+            "bus_close" => Function::new_typed_with_env(&mut store, env, hook("bus_close", bus_close)),
+            "bus_call" => Function::new_typed_with_env(&mut store, env, hook("bus_call", bus_call)),
+            "bus_subcall" => Function::new_typed_with_env(&mut store, env, hook("bus_subcall", bus_subcall)),
+            "bus_poll" => Function::new_typed_with_env(&mut store, env, hook("bus_poll", bus_poll)),
+            "call_reply" => Function::new_typed_with_env(&mut store, env, hook("call_reply", call_reply)),
+            "call_fault" => Function::new_typed_with_env(&mut store, env, hook("call_fault", call_fault)),
+            "call_close" => Function::new_typed_with_env(&mut store, env, hook("call_close", call_close)),
+            "ws_connect" => Function::new_typed_with_env(&mut store, env, hook("ws_connect", ws_connect)),
+            "http_request" => Function::new_typed_with_env(&mut store, env, hook("http_request", http_request)),
+            "http_status" => Function::new_typed_with_env(&mut store, env, hook("http_status", http_status)),
+            "port_bridge" => Function::new_typed_with_env(&mut store, env, hook("port_bridge", port_bridge)),
+            "port_unbridge" => Function::new_typed_with_env(&mut store, env, hook("port_unbridge", port_unbridge)),
+            "port_dhcp_acquire" => Function::new_typed_with_env(&mut store, env, hook("port_dhcp_acquire", port_dhcp_acquire)),
+            "port_addr_add" => Function::new_typed_with_env(&mut store, env, hook("port_addr_add", port_addr_add)),
+            "port_addr_remove" => Function::new_typed_with_env(&mut store, env, hook("port_addr_remove", port_addr_remove)),
+            "port_addr_clear" => Function::new_typed_with_env(&mut store, env, hook("port_addr_clear", port_addr_clear)),
+            "port_addr_list" => Function::new_typed_with_env(&mut store, env, hook("port_addr_list", port_addr_list)),
+            "port_ifaddr_list" => Function::new_typed_with_env(&mut store, env, hook("port_ifaddr_list", port_ifaddr_list)),
+            "port_mac" => Function::new_typed_with_env(&mut store, env, hook("port_mac", port_mac)),
+            "port_gateway_set" => Function::new_typed_with_env(&mut store, env, hook("port_gateway_set", port_gateway_set)),
+            "port_route_add" => Function::new_typed_with_env(&mut store, env, hook("port_route_add", port_route_add)),
+            "port_route_remove" => Function::new_typed_with_env(&mut store, env, hook("port_route_remove", port_route_remove)),
+            "port_route_clear" => Function::new_typed_with_env(&mut store, env, hook("port_route_clear", port_route_clear)),
+            "port_route_list" => Function::new_typed_with_env(&mut store, env, hook("port_route_list", port_route_list)),
+            "sock_status" => Function::new_typed_with_env(&mut store, env, hook("sock_status", sock_status)),
+            "sock_addr_local" => Function::new_typed_with_env(&mut store, env, hook("sock_addr_local", sock_addr_local)),
+            "sock_addr_peer" => Function::new_typed_with_env(&mut store, env, hook("sock_addr_peer", sock_addr_peer)),
+            "sock_open" => Function::new_typed_with_env(&mut store, env, hook("sock_open", sock_open)),
+            "sock_set_opt_flag" => Function::new_typed_with_env(&mut store, env, hook("sock_set_opt_flag", sock_set_opt_flag)),
+            "sock_get_opt_flag" => Function::new_typed_with_env(&mut store, env, hook("sock_get_opt_flag", sock_get_opt_flag)),
+            "sock_set_opt_time" => Function::new_typed_with_env(&mut store, env, hook("sock_set_opt_time", sock_set_opt_time)),
+            "sock_get_opt_time" => Function::new_typed_with_env(&mut store, env, hook("sock_get_opt_time", sock_get_opt_time)),
+            "sock_get_tcp_stats" => Function::new_typed_with_env(&mut store, env, hook("sock_get_tcp_stats", sock_get_tcp_stats)),
+            "sock_set_opt_size" => Function::new_typed_with_env(&mut store, env, hook("sock_set_opt_size", sock_set_opt_size)),
+            "sock_get_opt_size" => Function::new_typed_with_env(&mut store, env, hook("sock_get_opt_size", sock_get_opt_size)),
+            "sock_join_multicast_v4" => Function::new_typed_with_env(&mut store, env, hook("sock_join_multicast_v4", sock_join_multicast_v4)),
+            "sock_leave_multicast_v4" => Function::new_typed_with_env(&mut store, env, hook("sock_leave_multicast_v4", sock_leave_multicast_v4)),
+            "sock_join_multicast_v6" => Function::new_typed_with_env(&mut store, env, hook("sock_join_multicast_v6", sock_join_multicast_v6)),
+            "sock_leave_multicast_v6" => Function::new_typed_with_env(&mut store, env, hook("sock_leave_multicast_v6", sock_leave_multicast_v6)),
+            "sock_bind" => Function::new_typed_with_env(&mut store, env, hook("sock_bind", sock_bind)),
+            "sock_listen" => Function::new_typed_with_env(&mut store, env, hook("sock_listen", sock_listen)),
+            "sock_accept" => Function::new_typed_with_env(&mut store, env, hook("sock_accept", sock_accept)),
+            "sock_connect" => Function::new_typed_with_env(&mut store, env, hook("sock_connect", sock_connect)),
+            "sock_connect_tls" => Function::new_typed_with_env(&mut store, env, hook("sock_connect_tls", sock_connect_tls)),
+            "sock_recv" => Function::new_typed_with_env(&mut store, env, hook("sock_recv", sock_recv)),
+            "sock_recv_from" => Function::new_typed_with_env(&mut store, env, hook("sock_recv_from", sock_recv_from)),
+            "sock_send" => Function::new_typed_with_env(&mut store, env, hook("sock_send", sock_send)),
+            "sock_send_to" => Function::new_typed_with_env(&mut store, env, hook("sock_send_to", sock_send_to)),
+            "sock_send_file" => Function::new_typed_with_env(&mut store, env, hook("sock_send_file", sock_send_file)),
+            "sock_shutdown" => Function::new_typed_with_env(&mut store, env, hook("sock_shutdown", sock_shutdown)),
+            "resolve" => Function::new_typed_with_env(&mut store, env, hook("resolve", resolve)),
         }
     }
 }
@@ -706,113 +1154,129 @@ fn generate_import_object_wasix64_v1(
     use self::wasix64::*;
     imports! {
         "wasix_64v1" => {
-            "args_get" => Function::new_typed_with_env(&mut store, env, args_get),
-            "args_sizes_get" => Function::new_typed_with_env(&mut store, env, args_sizes_get),
-            "clock_res_get" => Function::new_typed_with_env(&mut store, env, clock_res_get),
-            "clock_time_get" => Function::new_typed_with_env(&mut store, env, clock_time_get),
-            "environ_get" => Function::new_typed_with_env(&mut store, env, environ_get),
-            "environ_sizes_get" => Function::new_typed_with_env(&mut store, env, environ_sizes_get),
-            "fd_advise" => Function::new_typed_with_env(&mut store, env, fd_advise),
-            "fd_allocate" => Function::new_typed_with_env(&mut store, env, fd_allocate),
-            "fd_close" => Function::new_typed_with_env(&mut store, env, fd_close),
-            "fd_datasync" => Function::new_typed_with_env(&mut store, env, fd_datasync),
-            "fd_fdstat_get" => Function::new_typed_with_env(&mut store, env, fd_fdstat_get),
-            "fd_fdstat_set_flags" => Function::new_typed_with_env(&mut store, env, fd_fdstat_set_flags),
-            "fd_fdstat_set_rights" => Function::new_typed_with_env(&mut store, env, fd_fdstat_set_rights),
-            "fd_filestat_get" => Function::new_typed_with_env(&mut store, env, fd_filestat_get),
-            "fd_filestat_set_size" => Function::new_typed_with_env(&mut store, env, fd_filestat_set_size),
-            "fd_filestat_set_times" => Function::new_typed_with_env(&mut store, env, fd_filestat_set_times),
-            "fd_pread" => Function::new_typed_with_env(&mut store, env, fd_pread),
-            "fd_prestat_get" => Function::new_typed_with_env(&mut store, env, fd_prestat_get),
-            "fd_prestat_dir_name" => Function::new_typed_with_env(&mut store, env, fd_prestat_dir_name),
-            "fd_pwrite" => Function::new_typed_with_env(&mut store, env, fd_pwrite),
-            "fd_read" => Function::new_typed_with_env(&mut store, env, fd_read),
-            "fd_readdir" => Function::new_typed_with_env(&mut store, env, fd_readdir),
-            "fd_renumber" => Function::new_typed_with_env(&mut store, env, fd_renumber),
-            "fd_dup" => Function::new_typed_with_env(&mut store, env, fd_dup),
-            "fd_event" => Function::new_typed_with_env(&mut store, env, fd_event),
-            "fd_seek" => Function::new_typed_with_env(&mut store, env, fd_seek),
-            "fd_sync" => Function::new_typed_with_env(&mut store, env, fd_sync),
-            "fd_tell" => Function::new_typed_with_env(&mut store, env, fd_tell),
-            "fd_write" => Function::new_typed_with_env(&mut store, env, fd_write),
-            "fd_pipe" => Function::new_typed_with_env(&mut store, env, fd_pipe),
-            "path_create_directory" => Function::new_typed_with_env(&mut store, env, path_create_directory),
-            "path_filestat_get" => Function::new_typed_with_env(&mut store, env, path_filestat_get),
-            "path_filestat_set_times" => Function::new_typed_with_env(&mut store, env, path_filestat_set_times),
-            "path_link" => Function::new_typed_with_env(&mut store, env, path_link),
-            "path_open" => Function::new_typed_with_env(&mut store, env, path_open),
-            "path_readlink" => Function::new_typed_with_env(&mut store, env, path_readlink),
-            "path_remove_directory" => Function::new_typed_with_env(&mut store, env, path_remove_directory),
-            "path_rename" => Function::new_typed_with_env(&mut store, env, path_rename),
-            "path_symlink" => Function::new_typed_with_env(&mut store, env, path_symlink),
-            "path_unlink_file" => Function::new_typed_with_env(&mut store, env, path_unlink_file),
-            "poll_oneoff" => Function::new_typed_with_env(&mut store, env, poll_oneoff),
-            "proc_exit" => Function::new_typed_with_env(&mut store, env, proc_exit),
-            "proc_raise" => Function::new_typed_with_env(&mut store, env, proc_raise),
-            "random_get" => Function::new_typed_with_env(&mut store, env, random_get),
-            "tty_get" => Function::new_typed_with_env(&mut store, env, tty_get),
-            "tty_set" => Function::new_typed_with_env(&mut store, env, tty_set),
-            "getcwd" => Function::new_typed_with_env(&mut store, env, getcwd),
-            "chdir" => Function::new_typed_with_env(&mut store, env, chdir),
-            "thread_spawn" => Function::new_typed_with_env(&mut store, env, thread_spawn),
-            "thread_sleep" => Function::new_typed_with_env(&mut store, env, thread_sleep),
-            "thread_id" => Function::new_typed_with_env(&mut store, env, thread_id),
-            "thread_join" => Function::new_typed_with_env(&mut store, env, thread_join),
-            "thread_parallelism" => Function::new_typed_with_env(&mut store, env, thread_parallelism),
-            "thread_exit" => Function::new_typed_with_env(&mut store, env, thread_exit),
-            "sched_yield" => Function::new_typed_with_env(&mut store, env, sched_yield),
-            "getpid" => Function::new_typed_with_env(&mut store, env, getpid),
-            "process_spawn" => Function::new_typed_with_env(&mut store, env, process_spawn),
-            "bus_open_local" => Function::new_typed_with_env(&mut store, env, bus_open_local),
-            "bus_open_remote" => Function::new_typed_with_env(&mut store, env, bus_open_remote),
-            "bus_close" => Function::new_typed_with_env(&mut store, env, bus_close),
-            "bus_call" => Function::new_typed_with_env(&mut store, env, bus_call),
-            "bus_subcall" => Function::new_typed_with_env(&mut store, env, bus_subcall),
-            "bus_poll" => Function::new_typed_with_env(&mut store, env, bus_poll),
-            "call_reply" => Function::new_typed_with_env(&mut store, env, call_reply),
-            "call_fault" => Function::new_typed_with_env(&mut store, env, call_fault),
-            "call_close" => Function::new_typed_with_env(&mut store, env, call_close),
-            "ws_connect" => Function::new_typed_with_env(&mut store, env, ws_connect),
-            "http_request" => Function::new_typed_with_env(&mut store, env, http_request),
-            "http_status" => Function::new_typed_with_env(&mut store, env, http_status),
-            "port_bridge" => Function::new_typed_with_env(&mut store, env, port_bridge),
-            "port_unbridge" => Function::new_typed_with_env(&mut store, env, port_unbridge),
-            "port_dhcp_acquire" => Function::new_typed_with_env(&mut store, env, port_dhcp_acquire),
-            "port_addr_add" => Function::new_typed_with_env(&mut store, env, port_addr_add),
-            "port_addr_remove" => Function::new_typed_with_env(&mut store, env, port_addr_remove),
-            "port_addr_clear" => Function::new_typed_with_env(&mut store, env, port_addr_clear),
-            "port_addr_list" => Function::new_typed_with_env(&mut store, env, port_addr_list),
-            "port_mac" => Function::new_typed_with_env(&mut store, env, port_mac),
-            "port_gateway_set" => Function::new_typed_with_env(&mut store, env, port_gateway_set),
-            "port_route_add" => Function::new_typed_with_env(&mut store, env, port_route_add),
-            "port_route_remove" => Function::new_typed_with_env(&mut store, env, port_route_remove),
-            "port_route_clear" => Function::new_typed_with_env(&mut store, env, port_route_clear),
-            "port_route_list" => Function::new_typed_with_env(&mut store, env, port_route_list),
-            "sock_status" => Function::new_typed_with_env(&mut store, env, sock_status),
-            "sock_addr_local" => Function::new_typed_with_env(&mut store, env, sock_addr_local),
-            "sock_addr_peer" => Function::new_typed_with_env(&mut store, env, sock_addr_peer),
-            "sock_open" => Function::new_typed_with_env(&mut store, env, sock_open),
-            "sock_set_opt_flag" => Function::new_typed_with_env(&mut store, env, sock_set_opt_flag),
-            "sock_get_opt_flag" => Function::new_typed_with_env(&mut store, env, sock_get_opt_flag),
-            "sock_set_opt_time" => Function::new_typed_with_env(&mut store, env, sock_set_opt_time),
-            "sock_get_opt_time" => Function::new_typed_with_env(&mut store, env, sock_get_opt_time),
-            "sock_set_opt_size" => Function::new_typed_with_env(&mut store, env, sock_set_opt_size),
-            "sock_get_opt_size" => Function::new_typed_with_env(&mut store, env, sock_get_opt_size),
-            "sock_join_multicast_v4" => Function::new_typed_with_env(&mut store, env, sock_join_multicast_v4),
-            "sock_leave_multicast_v4" => Function::new_typed_with_env(&mut store, env, sock_leave_multicast_v4),
-            "sock_join_multicast_v6" => Function::new_typed_with_env(&mut store, env, sock_join_multicast_v6),
-            "sock_leave_multicast_v6" => Function::new_typed_with_env(&mut store, env, sock_leave_multicast_v6),
-            "sock_bind" => Function::new_typed_with_env(&mut store, env, sock_bind),
-            "sock_listen" => Function::new_typed_with_env(&mut store, env, sock_listen),
-            "sock_accept" => Function::new_typed_with_env(&mut store, env, sock_accept),
-            "sock_connect" => Function::new_typed_with_env(&mut store, env, sock_connect),
-            "sock_recv" => Function::new_typed_with_env(&mut store, env, sock_recv),
-            "sock_recv_from" => Function::new_typed_with_env(&mut store, env, sock_recv_from),
-            "sock_send" => Function::new_typed_with_env(&mut store, env, sock_send),
-            "sock_send_to" => Function::new_typed_with_env(&mut store, env, sock_send_to),
-            "sock_send_file" => Function::new_typed_with_env(&mut store, env, sock_send_file),
-            "sock_shutdown" => Function::new_typed_with_env(&mut store, env, sock_shutdown),
-            "resolve" => Function::new_typed_with_env(&mut store, env, resolve),
+            "args_get" => Function::new_typed_with_env(&mut store, env, hook("args_get", args_get)),
+            "args_sizes_get" => Function::new_typed_with_env(&mut store, env, hook("args_sizes_get", args_sizes_get)),
+            "clock_res_get" => Function::new_typed_with_env(&mut store, env, hook("clock_res_get", clock_res_get)),
+            "clock_time_get" => Function::new_typed_with_env(&mut store, env, hook("clock_time_get", clock_time_get)),
+            "environ_get" => Function::new_typed_with_env(&mut store, env, hook("environ_get", environ_get)),
+            "environ_sizes_get" => Function::new_typed_with_env(&mut store, env, hook("environ_sizes_get", environ_sizes_get)),
+            "fd_advise" => Function::new_typed_with_env(&mut store, env, hook("fd_advise", fd_advise)),
+            "fd_allocate" => Function::new_typed_with_env(&mut store, env, hook("fd_allocate", fd_allocate)),
+            "fd_close" => Function::new_typed_with_env(&mut store, env, hook("fd_close", fd_close)),
+            "fd_datasync" => Function::new_typed_with_env(&mut store, env, hook("fd_datasync", fd_datasync)),
+            "fd_fdstat_get" => Function::new_typed_with_env(&mut store, env, hook("fd_fdstat_get", fd_fdstat_get)),
+            "fd_fdstat_set_flags" => Function::new_typed_with_env(&mut store, env, hook("fd_fdstat_set_flags", fd_fdstat_set_flags)),
+            "fd_fdstat_set_rights" => Function::new_typed_with_env(&mut store, env, hook("fd_fdstat_set_rights", fd_fdstat_set_rights)),
+            "fd_filestat_get" => Function::new_typed_with_env(&mut store, env, hook("fd_filestat_get", fd_filestat_get)),
+            "fd_filestat_set_size" => Function::new_typed_with_env(&mut store, env, hook("fd_filestat_set_size", fd_filestat_set_size)),
+            "fd_filestat_set_times" => Function::new_typed_with_env(&mut store, env, hook("fd_filestat_set_times", fd_filestat_set_times)),
+            "fd_pread" => Function::new_typed_with_env(&mut store, env, hook("fd_pread", fd_pread)),
+            "fd_prestat_get" => Function::new_typed_with_env(&mut store, env, hook("fd_prestat_get", fd_prestat_get)),
+            "fd_prestat_dir_name" => Function::new_typed_with_env(&mut store, env, hook("fd_prestat_dir_name", fd_prestat_dir_name)),
+            "fd_pwrite" => Function::new_typed_with_env(&mut store, env, hook("fd_pwrite", fd_pwrite)),
+            "fd_read" => Function::new_typed_with_env(&mut store, env, hook("fd_read", fd_read)),
+            "fd_readdir" => Function::new_typed_with_env(&mut store, env, hook("fd_readdir", fd_readdir)),
+            "fd_renumber" => Function::new_typed_with_env(&mut store, env, hook("fd_renumber", fd_renumber)),
+            "fd_dup" => Function::new_typed_with_env(&mut store, env, hook("fd_dup", fd_dup)),
+            "fd_event" => Function::new_typed_with_env(&mut store, env, hook("fd_event", fd_event)),
+            "fd_seek" => Function::new_typed_with_env(&mut store, env, hook("fd_seek", fd_seek)),
+            "fd_sync" => Function::new_typed_with_env(&mut store, env, hook("fd_sync", fd_sync)),
+            "fd_tell" => Function::new_typed_with_env(&mut store, env, hook("fd_tell", fd_tell)),
+            "fd_write" => Function::new_typed_with_env(&mut store, env, hook("fd_write", fd_write)),
+            "fd_pipe" => Function::new_typed_with_env(&mut store, env, hook("fd_pipe", fd_pipe)),
+            "path_create_directory" => Function::new_typed_with_env(&mut store, env, hook("path_create_directory", path_create_directory)),
+            "path_filestat_get" => Function::new_typed_with_env(&mut store, env, hook("path_filestat_get", path_filestat_get)),
+            "path_filestat_set_times" => Function::new_typed_with_env(&mut store, env, hook("path_filestat_set_times", path_filestat_set_times)),
+            "path_link" => Function::new_typed_with_env(&mut store, env, hook("path_link", path_link)),
+            "path_open" => Function::new_typed_with_env(&mut store, env, hook("path_open", path_open)),
+            "path_readlink" => Function::new_typed_with_env(&mut store, env, hook("path_readlink", path_readlink)),
+            "path_remove_directory" => Function::new_typed_with_env(&mut store, env, hook("path_remove_directory", path_remove_directory)),
+            "path_rename" => Function::new_typed_with_env(&mut store, env, hook("path_rename", path_rename)),
+            "path_symlink" => Function::new_typed_with_env(&mut store, env, hook("path_symlink", path_symlink)),
+            "path_unlink_file" => Function::new_typed_with_env(&mut store, env, hook("path_unlink_file", path_unlink_file)),
+            "poll_oneoff" => Function::new_typed_with_env(&mut store, env, hook("poll_oneoff", poll_oneoff)),
+            "proc_exit" => Function::new_typed_with_env(&mut store, env, hook("proc_exit", proc_exit)),
+            "proc_raise" => Function::new_typed_with_env(&mut store, env, hook("proc_raise", proc_raise)),
+            "proc_log" => Function::new_typed_with_env(&mut store, env, hook("proc_log", proc_log)),
+            "random_get" => Function::new_typed_with_env(&mut store, env, hook("random_get", random_get)),
+            "tty_get" => Function::new_typed_with_env(&mut store, env, hook("tty_get", tty_get)),
+            "tty_set" => Function::new_typed_with_env(&mut store, env, hook("tty_set", tty_set)),
+            "tty_set_raw" => Function::new_typed_with_env(&mut store, env, hook("tty_set_raw", tty_set_raw)),
+            "tty_set_mode" => Function::new_typed_with_env(&mut store, env, hook("tty_set_mode", tty_set_mode)),
+            "tty_clear" => Function::new_typed_with_env(&mut store, env, hook("tty_clear", tty_clear)),
+            "tty_move_cursor" => Function::new_typed_with_env(&mut store, env, hook("tty_move_cursor", tty_move_cursor)),
+            "stdio_flush" => Function::new_typed_with_env(&mut store, env, hook("stdio_flush", stdio_flush)),
+            "getcwd" => Function::new_typed_with_env(&mut store, env, hook("getcwd", getcwd)),
+            "chdir" => Function::new_typed_with_env(&mut store, env, hook("chdir", chdir)),
+            "thread_spawn" => Function::new_typed_with_env(&mut store, env, hook("thread_spawn", thread_spawn)),
+            "thread_sleep" => Function::new_typed_with_env(&mut store, env, hook("thread_sleep", thread_sleep)),
+            "thread_spin" => Function::new_typed_with_env(&mut store, env, hook("thread_spin", thread_spin)),
+            "thread_id" => Function::new_typed_with_env(&mut store, env, hook("thread_id", thread_id)),
+            "thread_join" => Function::new_typed_with_env(&mut store, env, hook("thread_join", thread_join)),
+            "thread_join_v2" => Function::new_typed_with_env(&mut store, env, hook("thread_join_v2", thread_join_v2)),
+            "thread_detach" => Function::new_typed_with_env(&mut store, env, hook("thread_detach", thread_detach)),
+            "thread_parallelism" => Function::new_typed_with_env(&mut store, env, hook("thread_parallelism", thread_parallelism)),
+            "thread_affinity_set" => Function::new_typed_with_env(&mut store, env, hook("thread_affinity_set", thread_affinity_set)),
+            "thread_affinity_get" => Function::new_typed_with_env(&mut store, env, hook("thread_affinity_get", thread_affinity_get)),
+            "futex_wait" => Function::new_typed_with_env(&mut store, env, hook("futex_wait", futex_wait)),
+            "futex_wake" => Function::new_typed_with_env(&mut store, env, hook("futex_wake", futex_wake)),
+            "thread_exit" => Function::new_typed_with_env(&mut store, env, hook("thread_exit", thread_exit)),
+            "sched_yield" => Function::new_typed_with_env(&mut store, env, hook("sched_yield", sched_yield)),
+            "getpid" => Function::new_typed_with_env(&mut store, env, hook("getpid", getpid)),
+            "process_spawn" => Function::new_typed_with_env(&mut store, env, hook("process_spawn", process_spawn)),
+            "bus_open_local" => Function::new_typed_with_env(&mut store, env, hook("bus_open_local", bus_open_local)),
+            "bus_open_remote" => Function::new_typed_with_env(&mut store, env, hook("bus_open_remote", bus_open_remote)),
+            "bus_close" => Function::new_typed_with_env(&mut store, env, hook("bus_close", bus_close)),
+            "bus_call" => Function::new_typed_with_env(&mut store, env, hook("bus_call", bus_call)),
+            "bus_subcall" => Function::new_typed_with_env(&mut store, env, hook("bus_subcall", bus_subcall)),
+            "bus_poll" => Function::new_typed_with_env(&mut store, env, hook("bus_poll", bus_poll)),
+            "call_reply" => Function::new_typed_with_env(&mut store, env, hook("call_reply", call_reply)),
+            "call_fault" => Function::new_typed_with_env(&mut store, env, hook("call_fault", call_fault)),
+            "call_close" => Function::new_typed_with_env(&mut store, env, hook("call_close", call_close)),
+            "ws_connect" => Function::new_typed_with_env(&mut store, env, hook("ws_connect", ws_connect)),
+            "http_request" => Function::new_typed_with_env(&mut store, env, hook("http_request", http_request)),
+            "http_status" => Function::new_typed_with_env(&mut store, env, hook("http_status", http_status)),
+            "port_bridge" => Function::new_typed_with_env(&mut store, env, hook("port_bridge", port_bridge)),
+            "port_unbridge" => Function::new_typed_with_env(&mut store, env, hook("port_unbridge", port_unbridge)),
+            "port_dhcp_acquire" => Function::new_typed_with_env(&mut store, env, hook("port_dhcp_acquire", port_dhcp_acquire)),
+            "port_addr_add" => Function::new_typed_with_env(&mut store, env, hook("port_addr_add", port_addr_add)),
+            "port_addr_remove" => Function::new_typed_with_env(&mut store, env, hook("port_addr_remove", port_addr_remove)),
+            "port_addr_clear" => Function::new_typed_with_env(&mut store, env, hook("port_addr_clear", port_addr_clear)),
+            "port_addr_list" => Function::new_typed_with_env(&mut store, env, hook("port_addr_list", port_addr_list)),
+            "port_ifaddr_list" => Function::new_typed_with_env(&mut store, env, hook("port_ifaddr_list", port_ifaddr_list)),
+            "port_mac" => Function::new_typed_with_env(&mut store, env, hook("port_mac", port_mac)),
+            "port_gateway_set" => Function::new_typed_with_env(&mut store, env, hook("port_gateway_set", port_gateway_set)),
+            "port_route_add" => Function::new_typed_with_env(&mut store, env, hook("port_route_add", port_route_add)),
+            "port_route_remove" => Function::new_typed_with_env(&mut store, env, hook("port_route_remove", port_route_remove)),
+            "port_route_clear" => Function::new_typed_with_env(&mut store, env, hook("port_route_clear", port_route_clear)),
+            "port_route_list" => Function::new_typed_with_env(&mut store, env, hook("port_route_list", port_route_list)),
+            "sock_status" => Function::new_typed_with_env(&mut store, env, hook("sock_status", sock_status)),
+            "sock_addr_local" => Function::new_typed_with_env(&mut store, env, hook("sock_addr_local", sock_addr_local)),
+            "sock_addr_peer" => Function::new_typed_with_env(&mut store, env, hook("sock_addr_peer", sock_addr_peer)),
+            "sock_open" => Function::new_typed_with_env(&mut store, env, hook("sock_open", sock_open)),
+            "sock_set_opt_flag" => Function::new_typed_with_env(&mut store, env, hook("sock_set_opt_flag", sock_set_opt_flag)),
+            "sock_get_opt_flag" => Function::new_typed_with_env(&mut store, env, hook("sock_get_opt_flag", sock_get_opt_flag)),
+            "sock_set_opt_time" => Function::new_typed_with_env(&mut store, env, hook("sock_set_opt_time", sock_set_opt_time)),
+            "sock_get_opt_time" => Function::new_typed_with_env(&mut store, env, hook("sock_get_opt_time", sock_get_opt_time)),
+            "sock_get_tcp_stats" => Function::new_typed_with_env(&mut store, env, hook("sock_get_tcp_stats", sock_get_tcp_stats)),
+            "sock_set_opt_size" => Function::new_typed_with_env(&mut store, env, hook("sock_set_opt_size", sock_set_opt_size)),
+            "sock_get_opt_size" => Function::new_typed_with_env(&mut store, env, hook("sock_get_opt_size", sock_get_opt_size)),
+            "sock_join_multicast_v4" => Function::new_typed_with_env(&mut store, env, hook("sock_join_multicast_v4", sock_join_multicast_v4)),
+            "sock_leave_multicast_v4" => Function::new_typed_with_env(&mut store, env, hook("sock_leave_multicast_v4", sock_leave_multicast_v4)),
+            "sock_join_multicast_v6" => Function::new_typed_with_env(&mut store, env, hook("sock_join_multicast_v6", sock_join_multicast_v6)),
+            "sock_leave_multicast_v6" => Function::new_typed_with_env(&mut store, env, hook("sock_leave_multicast_v6", sock_leave_multicast_v6)),
+            "sock_bind" => Function::new_typed_with_env(&mut store, env, hook("sock_bind", sock_bind)),
+            "sock_listen" => Function::new_typed_with_env(&mut store, env, hook("sock_listen", sock_listen)),
+            "sock_accept" => Function::new_typed_with_env(&mut store, env, hook("sock_accept", sock_accept)),
+            "sock_connect" => Function::new_typed_with_env(&mut store, env, hook("sock_connect", sock_connect)),
+            "sock_connect_tls" => Function::new_typed_with_env(&mut store, env, hook("sock_connect_tls", sock_connect_tls)),
+            "sock_recv" => Function::new_typed_with_env(&mut store, env, hook("sock_recv", sock_recv)),
+            "sock_recv_from" => Function::new_typed_with_env(&mut store, env, hook("sock_recv_from", sock_recv_from)),
+            "sock_send" => Function::new_typed_with_env(&mut store, env, hook("sock_send", sock_send)),
+            "sock_send_to" => Function::new_typed_with_env(&mut store, env, hook("sock_send_to", sock_send_to)),
+            "sock_send_file" => Function::new_typed_with_env(&mut store, env, hook("sock_send_file", sock_send_file)),
+            "sock_shutdown" => Function::new_typed_with_env(&mut store, env, hook("sock_shutdown", sock_shutdown)),
+            "resolve" => Function::new_typed_with_env(&mut store, env, hook("resolve", resolve)),
         }
     }
 }
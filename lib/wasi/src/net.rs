@@ -0,0 +1,274 @@
+//! Conversions between the host's network types and the types WASIX
+//! programs use in memory.
+//!
+//! The port and the address octets are stored in network byte order. The
+//! WASI address types have no room for the IPv6 flow information or scope
+//! id, so both are dropped when converting to them and read back as zero.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6};
+
+use crate::syscalls::types::{
+    __wasi_addr_port_t, __wasi_addr_port_u, __wasi_addr_t, __wasi_addr_u, __wasi_cidr_t,
+    __wasi_cidr_u, __wasi_ifaddr_t, __wasi_tcp_stats_t, __WASI_IFF_LOOPBACK, __WASI_IFF_UP,
+    __WASI_TCP_STATS_BYTES_RECEIVED, __WASI_TCP_STATS_BYTES_SENT, __WASI_TCP_STATS_RETRANSMITS,
+    __WASI_TCP_STATS_RTT, __WASI_TCP_STATS_RTT_VAR, __WASI_TCP_STATS_SEGMENTS_RECEIVED,
+    __WASI_TCP_STATS_SEGMENTS_SENT,
+};
+use wasmer_vnet::{IpCidr, NetworkInterface, TcpStats};
+use wasmer_wasi_types::wasi::{Addressfamily, Errno};
+
+/// Converts an IP address into its WASI representation
+pub fn ip_to_wasi(ip: IpAddr) -> __wasi_addr_t {
+    let mut octs = [0u8; 16];
+    let tag = match ip {
+        IpAddr::V4(ip) => {
+            octs[..4].copy_from_slice(&ip.octets());
+            Addressfamily::Inet4
+        }
+        IpAddr::V6(ip) => {
+            octs.copy_from_slice(&ip.octets());
+            Addressfamily::Inet6
+        }
+    };
+    __wasi_addr_t {
+        tag,
+        u: __wasi_addr_u { octs },
+    }
+}
+
+/// Reads an IP address out of its WASI representation
+///
+/// Fails with `Errno::Inval` when the address family is neither IPv4 nor IPv6.
+pub fn ip_from_wasi(addr: &__wasi_addr_t) -> Result<IpAddr, Errno> {
+    let o = addr.u.octs;
+    Ok(match addr.tag {
+        Addressfamily::Inet4 => IpAddr::V4(Ipv4Addr::new(o[0], o[1], o[2], o[3])),
+        Addressfamily::Inet6 => IpAddr::V6(Ipv6Addr::from(o)),
+        _ => return Err(Errno::Inval),
+    })
+}
+
+/// Converts an IP address and its prefix length into their WASI
+/// representation
+pub fn cidr_to_wasi(cidr: IpCidr) -> __wasi_cidr_t {
+    let mut octs = [0u8; 17];
+    let tag = match cidr.ip {
+        IpAddr::V4(ip) => {
+            octs[..4].copy_from_slice(&ip.octets());
+            octs[4] = cidr.prefix;
+            Addressfamily::Inet4
+        }
+        IpAddr::V6(ip) => {
+            octs[..16].copy_from_slice(&ip.octets());
+            octs[16] = cidr.prefix;
+            Addressfamily::Inet6
+        }
+    };
+    __wasi_cidr_t {
+        tag,
+        u: __wasi_cidr_u { octs },
+    }
+}
+
+/// Converts network interfaces into their WASI representation, which
+/// like `getifaddrs` has an entry for every address of an interface
+///
+/// Names longer than 15 bytes are cut off so that they stay terminated.
+pub fn interfaces_to_wasi(interfaces: &[NetworkInterface]) -> Vec<__wasi_ifaddr_t> {
+    let mut ifaddrs = Vec::new();
+    for iface in interfaces {
+        let mut name = [0u8; 16];
+        let len = iface.name.len().min(name.len() - 1);
+        name[..len].copy_from_slice(&iface.name.as_bytes()[..len]);
+        let mut flags = 0;
+        if iface.up {
+            flags |= __WASI_IFF_UP;
+        }
+        if iface.loopback {
+            flags |= __WASI_IFF_LOOPBACK;
+        }
+        let entry = |addr| __wasi_ifaddr_t { name, flags, addr };
+
+        if iface.addrs.is_empty() {
+            ifaddrs.push(entry(__wasi_cidr_t {
+                tag: Addressfamily::Unspec,
+                u: __wasi_cidr_u { octs: [0; 17] },
+            }));
+        }
+        ifaddrs.extend(iface.addrs.iter().map(|cidr| entry(cidr_to_wasi(*cidr))));
+    }
+    ifaddrs
+}
+
+/// Converts a socket address into its WASI representation
+///
+/// The flow information and scope id of IPv6 addresses are not carried over.
+pub fn socket_addr_to_wasi(addr: SocketAddr) -> __wasi_addr_port_t {
+    let mut octs = [0u8; 18];
+    octs[..2].copy_from_slice(&addr.port().to_be_bytes());
+    let tag = match addr {
+        SocketAddr::V4(addr) => {
+            octs[2..6].copy_from_slice(&addr.ip().octets());
+            Addressfamily::Inet4
+        }
+        SocketAddr::V6(addr) => {
+            octs[2..].copy_from_slice(&addr.ip().octets());
+            Addressfamily::Inet6
+        }
+    };
+    __wasi_addr_port_t {
+        tag,
+        u: __wasi_addr_port_u { octs },
+    }
+}
+
+/// Reads a socket address out of its WASI representation
+///
+/// Fails with `Errno::Inval` when the address family is neither IPv4 nor IPv6.
+pub fn socket_addr_from_wasi(addr: &__wasi_addr_port_t) -> Result<SocketAddr, Errno> {
+    let o = addr.u.octs;
+    let port = u16::from_be_bytes([o[0], o[1]]);
+    Ok(match addr.tag {
+        Addressfamily::Inet4 => {
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::new(o[2], o[3], o[4], o[5])), port)
+        }
+        Addressfamily::Inet6 => {
+            let mut ip = [0u8; 16];
+            ip.copy_from_slice(&o[2..]);
+            SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::from(ip), port, 0, 0))
+        }
+        _ => return Err(Errno::Inval),
+    })
+}
+
+/// Converts the statistics of a TCP connection into their WASI
+/// representation, values the host did not provide are left out of
+/// `fields` and zeroed
+pub fn tcp_stats_to_wasi(stats: &TcpStats) -> __wasi_tcp_stats_t {
+    let mut fields = 0;
+    let mut field = |value: Option<u64>, flag: u32| match value {
+        Some(value) => {
+            fields |= flag;
+            value
+        }
+        None => 0,
+    };
+    let nanos = |time: Option<std::time::Duration>| time.map(|time| time.as_nanos() as u64);
+    let rtt = field(nanos(stats.rtt), __WASI_TCP_STATS_RTT);
+    let rtt_var = field(nanos(stats.rtt_var), __WASI_TCP_STATS_RTT_VAR);
+    let retransmits = field(stats.retransmits, __WASI_TCP_STATS_RETRANSMITS);
+    let bytes_sent = field(stats.bytes_sent, __WASI_TCP_STATS_BYTES_SENT);
+    let bytes_received = field(stats.bytes_received, __WASI_TCP_STATS_BYTES_RECEIVED);
+    let segments_sent = field(stats.segments_sent, __WASI_TCP_STATS_SEGMENTS_SENT);
+    let segments_received = field(stats.segments_received, __WASI_TCP_STATS_SEGMENTS_RECEIVED);
+    __wasi_tcp_stats_t {
+        fields,
+        rtt,
+        rtt_var,
+        retransmits,
+        bytes_sent,
+        bytes_received,
+        segments_sent,
+        segments_received,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_socket_addr_v4_round_trip() {
+        let addr: SocketAddr = "192.168.1.20:8080".parse().unwrap();
+        let wasi = socket_addr_to_wasi(addr);
+        assert_eq!(wasi.tag, Addressfamily::Inet4);
+        assert_eq!(&wasi.u.octs[..6], &[0x1f, 0x90, 192, 168, 1, 20]);
+        assert_eq!(socket_addr_from_wasi(&wasi), Ok(addr));
+    }
+
+    #[test]
+    fn test_socket_addr_v6_round_trip() {
+        let addr: SocketAddr = "[2001:db8::ff00:42:8329]:443".parse().unwrap();
+        let wasi = socket_addr_to_wasi(addr);
+        assert_eq!(wasi.tag, Addressfamily::Inet6);
+        assert_eq!(&wasi.u.octs[..4], &[0x01, 0xbb, 0x20, 0x01]);
+        assert_eq!(socket_addr_from_wasi(&wasi), Ok(addr));
+    }
+
+    #[test]
+    fn test_socket_addr_v6_with_scope_round_trip() {
+        // The scope id and flow information have nowhere to go
+        let ip: Ipv6Addr = "fe80::1".parse().unwrap();
+        let addr = SocketAddr::V6(SocketAddrV6::new(ip, 22, 7, 3));
+        let wasi = socket_addr_to_wasi(addr);
+        assert_eq!(
+            socket_addr_from_wasi(&wasi),
+            Ok(SocketAddr::V6(SocketAddrV6::new(ip, 22, 0, 0)))
+        );
+        assert_eq!(
+            socket_addr_to_wasi(socket_addr_from_wasi(&wasi).unwrap())
+                .u
+                .octs,
+            wasi.u.octs
+        );
+    }
+
+    #[test]
+    fn test_ip_round_trip() {
+        for ip in ["10.0.0.1", "::1", "2001:db8::1"] {
+            let ip: IpAddr = ip.parse().unwrap();
+            assert_eq!(ip_from_wasi(&ip_to_wasi(ip)), Ok(ip));
+        }
+    }
+
+    #[test]
+    fn test_interfaces_have_an_entry_per_address() {
+        let tunnel = NetworkInterface {
+            name: "a-very-long-tunnel-name".to_string(),
+            addrs: Vec::new(),
+            up: false,
+            loopback: false,
+        };
+        let ifaddrs = interfaces_to_wasi(&[NetworkInterface::loopback(), tunnel]);
+        assert_eq!(ifaddrs.len(), 3);
+
+        assert_eq!(&ifaddrs[0].name[..3], b"lo\0");
+        assert_eq!(ifaddrs[0].flags, __WASI_IFF_UP | __WASI_IFF_LOOPBACK);
+        assert_eq!(ifaddrs[0].addr.tag, Addressfamily::Inet4);
+        assert_eq!(&ifaddrs[0].addr.u.octs[..5], &[127, 0, 0, 1, 8]);
+        assert_eq!(ifaddrs[1].addr.tag, Addressfamily::Inet6);
+        assert_eq!(ifaddrs[1].addr.u.octs[16], 128);
+
+        assert_eq!(&ifaddrs[2].name, b"a-very-long-tun\0");
+        assert_eq!(ifaddrs[2].flags, 0);
+        assert_eq!(ifaddrs[2].addr.tag, Addressfamily::Unspec);
+    }
+
+    #[test]
+    fn test_tcp_stats_leave_out_missing_values() {
+        let stats = TcpStats {
+            rtt: Some(std::time::Duration::from_micros(250)),
+            bytes_sent: Some(42),
+            ..TcpStats::default()
+        };
+        let wasi = tcp_stats_to_wasi(&stats);
+        assert_eq!(
+            wasi.fields,
+            __WASI_TCP_STATS_RTT | __WASI_TCP_STATS_BYTES_SENT
+        );
+        assert_eq!(wasi.rtt, 250_000);
+        assert_eq!(wasi.bytes_sent, 42);
+        assert_eq!(wasi.retransmits, 0);
+        assert_eq!(tcp_stats_to_wasi(&TcpStats::default()).fields, 0);
+    }
+
+    #[test]
+    fn test_unknown_family_is_rejected() {
+        let mut wasi = socket_addr_to_wasi("127.0.0.1:80".parse().unwrap());
+        wasi.tag = Addressfamily::Unix;
+        assert_eq!(socket_addr_from_wasi(&wasi), Err(Errno::Inval));
+        let mut wasi = ip_to_wasi("127.0.0.1".parse().unwrap());
+        wasi.tag = Addressfamily::Unspec;
+        assert_eq!(ip_from_wasi(&wasi), Err(Errno::Inval));
+    }
+}
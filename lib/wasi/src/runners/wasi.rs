@@ -6,6 +6,7 @@ use crate::{WasiFunctionEnv, WasiState};
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use std::error::Error as StdError;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use wasmer::{Cranelift, Instance, Module, Store};
 use wasmer_vfs::webc_fs::WebcFileSystem;
@@ -14,12 +15,32 @@ use webc::{Command, WebCMmap};
 #[derive(Debug, Default, Clone, PartialEq, PartialOrd, Hash, Serialize, Deserialize)]
 pub struct WasiRunner {
     args: Vec<String>,
+    stdin: Option<PathBuf>,
+    stdout: Option<PathBuf>,
+    stderr: Option<PathBuf>,
 }
 
 impl WasiRunner {
     pub fn set_args(&mut self, args: Vec<String>) {
         self.args = args;
     }
+
+    /// Makes the program read its standard input from a host file
+    pub fn set_stdin(&mut self, path: PathBuf) {
+        self.stdin = Some(path);
+    }
+
+    /// Makes the program write its standard output to a host file, which is
+    /// created or truncated
+    pub fn set_stdout(&mut self, path: PathBuf) {
+        self.stdout = Some(path);
+    }
+
+    /// Makes the program write its standard error to a host file, which is
+    /// created or truncated
+    pub fn set_stderr(&mut self, path: PathBuf) {
+        self.stderr = Some(path);
+    }
 }
 
 impl crate::runners::Runner for WasiRunner {
@@ -47,7 +68,15 @@ impl crate::runners::Runner for WasiRunner {
         let mut module = Module::new(&store, atom_bytes)?;
         module.set_name(&atom_name);
 
-        let env = prepare_webc_env(&mut store, container.webc.clone(), &atom_name, &self.args)?;
+        let env = prepare_webc_env(
+            &mut store,
+            container.webc.clone(),
+            &atom_name,
+            &self.args,
+            self.stdin.as_deref(),
+            self.stdout.as_deref(),
+            self.stderr.as_deref(),
+        )?;
 
         exec_module(&mut store, &module, env)?;
 
@@ -61,6 +90,9 @@ fn prepare_webc_env(
     webc: Arc<WebCMmap>,
     command: &str,
     args: &[String],
+    stdin: Option<&Path>,
+    stdout: Option<&Path>,
+    stderr: Option<&Path>,
 ) -> Result<WasiFunctionEnv, anyhow::Error> {
     use webc::FsEntryType;
 
@@ -84,6 +116,23 @@ fn prepare_webc_env(
     let mut wasi_env = WasiState::new(command);
     wasi_env.set_fs(filesystem);
     wasi_env.args(args);
+    if let Some(path) = stdin {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("could not open stdin file {}", path.display()))?;
+        wasi_env.stdin(Box::new(wasmer_vfs::host_fs::File::new(
+            file,
+            path.to_path_buf(),
+            true,
+            false,
+            false,
+        )));
+    }
+    if let Some(path) = stdout {
+        wasi_env.stdout(Box::new(create_output(path, "stdout")?));
+    }
+    if let Some(path) = stderr {
+        wasi_env.stderr(Box::new(create_output(path, "stderr")?));
+    }
     for f_name in top_level_dirs.iter() {
         wasi_env.preopen(|p| p.directory(f_name).read(true).write(true).create(true))?;
     }
@@ -91,6 +140,19 @@ fn prepare_webc_env(
     Ok(wasi_env.finalize(store)?)
 }
 
+/// Creates the file the program writes one of its outputs to
+fn create_output(path: &Path, stream: &str) -> Result<wasmer_vfs::host_fs::File, anyhow::Error> {
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("could not create {} file {}", stream, path.display()))?;
+    Ok(wasmer_vfs::host_fs::File::new(
+        file,
+        path.to_path_buf(),
+        false,
+        true,
+        false,
+    ))
+}
+
 pub(crate) fn exec_module(
     store: &mut Store,
     module: &Module,
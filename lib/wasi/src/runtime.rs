@@ -1,11 +1,17 @@
+use derivative::Derivative;
 use std::fmt;
+use std::io::{self, Write};
 use std::ops::Deref;
 use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use thiserror::Error;
+use tracing::Level;
 use wasmer_vbus::{UnsupportedVirtualBus, VirtualBus};
 use wasmer_vnet::VirtualNetworking;
 use wasmer_wasi_types::wasi::Errno;
 
+use super::WasiEnv;
 use super::WasiError;
 use super::WasiThreadId;
 
@@ -15,6 +21,10 @@ pub enum WasiThreadError {
     Unsupported,
     #[error("The method named is not an exported function")]
     MethodNotFound,
+    #[error("The maximum number of threads has been reached")]
+    TooManyThreads,
+    #[error("The CPU affinity names no CPU of this machine")]
+    InvalidAffinity,
 }
 
 impl From<WasiThreadError> for Errno {
@@ -22,6 +32,8 @@ impl From<WasiThreadError> for Errno {
         match a {
             WasiThreadError::Unsupported => Errno::Notsup,
             WasiThreadError::MethodNotFound => Errno::Inval,
+            WasiThreadError::TooManyThreads => Errno::Again,
+            WasiThreadError::InvalidAffinity => Errno::Inval,
         }
     }
 }
@@ -39,6 +51,60 @@ pub struct WasiTtyState {
     pub line_buffered: bool,
 }
 
+impl Default for WasiTtyState {
+    fn default() -> Self {
+        Self {
+            rows: 25,
+            cols: 80,
+            width: 800,
+            height: 600,
+            stdin_tty: false,
+            stdout_tty: false,
+            stderr_tty: false,
+            echo: true,
+            line_buffered: true,
+        }
+    }
+}
+
+/// Parts of the terminal that can be cleared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WasiTtyClear {
+    /// The visible screen, the cursor is moved to the top left corner
+    Screen,
+    /// The lines that scrolled off the top of the screen
+    Scrollback,
+}
+
+/// Modes of the terminal that programs can turn on and off
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WasiTtyMode {
+    /// Pasted text is wrapped in `ESC[200~` and `ESC[201~`
+    BracketedPaste,
+    /// Mouse buttons being pressed and released are reported
+    MouseClicks,
+    /// Mouse movements while a button is held are reported as well
+    MouseDrags,
+    /// All mouse movements are reported as well
+    MouseMotion,
+    /// Mouse reports use the SGR encoding
+    MouseSgr,
+}
+
+impl WasiTtyMode {
+    /// Number of the DEC private mode that controls this mode on ANSI
+    /// terminals
+    pub fn dec_private_mode(self) -> u32 {
+        match self {
+            Self::BracketedPaste => 2004,
+            Self::MouseClicks => 1000,
+            Self::MouseDrags => 1002,
+            Self::MouseMotion => 1003,
+            Self::MouseSgr => 1006,
+        }
+    }
+}
+
 /// Represents an implementation of the WASI runtime - by default everything is
 /// unimplemented.
 pub trait WasiRuntimeImplementation: fmt::Debug + Sync {
@@ -57,22 +123,54 @@ pub trait WasiRuntimeImplementation: fmt::Debug + Sync {
 
     /// Gets the TTY state
     fn tty_get(&self) -> WasiTtyState {
-        WasiTtyState {
-            rows: 25,
-            cols: 80,
-            width: 800,
-            height: 600,
-            stdin_tty: false,
-            stdout_tty: false,
-            stderr_tty: false,
-            echo: true,
-            line_buffered: true,
-        }
+        WasiTtyState::default()
     }
 
     /// Sets the TTY state
     fn tty_set(&self, _tty_state: WasiTtyState) {}
 
+    /// Puts the TTY into raw mode, where input is neither echoed nor
+    /// gathered into lines, in one go
+    fn tty_raw_mode(&self) {
+        let mut tty = self.tty_get();
+        tty.echo = false;
+        tty.line_buffered = false;
+        self.tty_set(tty);
+    }
+
+    /// Puts the TTY back into cooked mode, where input is echoed and
+    /// gathered into lines
+    fn tty_cooked_mode(&self) {
+        let mut tty = self.tty_get();
+        tty.echo = true;
+        tty.line_buffered = true;
+        self.tty_set(tty);
+    }
+
+    /// Clears part of the terminal, by default by writing the ANSI escape
+    /// codes for it to the standard output
+    fn tty_clear(&self, clear: WasiTtyClear) -> io::Result<()> {
+        match clear {
+            WasiTtyClear::Screen => self.stdout(b"\x1B[H\x1B[2J"),
+            WasiTtyClear::Scrollback => self.stdout(b"\x1B[3J"),
+        }
+    }
+
+    /// Moves the cursor of the terminal to the (zero based) row and column,
+    /// by default by writing the ANSI escape code for it to the standard output
+    fn tty_move_cursor(&self, row: u32, col: u32) -> io::Result<()> {
+        let code = format!("\x1B[{};{}H", row as u64 + 1, col as u64 + 1);
+        self.stdout(code.as_bytes())
+    }
+
+    /// Turns a mode of the terminal on or off, by default by writing the
+    /// ANSI escape code for it to the standard output
+    fn tty_set_mode(&self, mode: WasiTtyMode, enabled: bool) -> io::Result<()> {
+        let set = if enabled { 'h' } else { 'l' };
+        let code = format!("\x1B[?{}{}", mode.dec_private_mode(), set);
+        self.stdout(code.as_bytes())
+    }
+
     /// Spawns a new thread by invoking the
     fn thread_spawn(
         &self,
@@ -81,11 +179,42 @@ pub trait WasiRuntimeImplementation: fmt::Debug + Sync {
         Err(WasiThreadError::Unsupported)
     }
 
+    /// Runs the guest code of a thread the program spawned, on the thread
+    /// the runtime spawned for it. `env` is the environment of the new
+    /// thread and `entry` the exported function it starts at, which is called
+    /// with `user_data`. What the thread passes to `proc_exit()` becomes its
+    /// exit code.
+    ///
+    /// The default implementation runs no guest code, as the store of the
+    /// program can't be shared with another thread, and the thread exits
+    /// straight away with a zero exit code.
+    fn thread_run(&self, _env: WasiEnv, _entry: &str, _user_data: u64) -> Result<(), WasiError> {
+        Ok(())
+    }
+
     /// Returns the amount of parallelism that is possible on this platform
     fn thread_parallelism(&self) -> Result<usize, WasiThreadError> {
         Err(WasiThreadError::Unsupported)
     }
 
+    /// Restricts the calling thread to the CPUs set in `mask`, where bit
+    /// `n % 8` of byte `n / 8` stands for CPU `n`
+    fn thread_set_affinity(&self, _mask: &[u8]) -> Result<(), WasiThreadError> {
+        Err(WasiThreadError::Unsupported)
+    }
+
+    /// Returns the mask of the CPUs the calling thread may run on (see
+    /// [`WasiRuntimeImplementation::thread_set_affinity`])
+    fn thread_affinity(&self) -> Result<Vec<u8>, WasiThreadError> {
+        Err(WasiThreadError::Unsupported)
+    }
+
+    /// Longest a thread may busy-wait in `thread_spin()` before it goes to
+    /// sleep instead, zero disables busy-waiting altogether
+    fn thread_spin_limit(&self) -> Duration {
+        Duration::from_micros(50)
+    }
+
     /// Invokes whenever a WASM thread goes idle. In some runtimes (like singlethreaded
     /// execution environments) they will need to do asynchronous work whenever the main
     /// thread goes idle and this is the place to hook for that.
@@ -98,13 +227,165 @@ pub trait WasiRuntimeImplementation: fmt::Debug + Sync {
     fn getpid(&self) -> Option<u32> {
         None
     }
+
+    /// Writes output of the program to the standard output of the host
+    fn stdout(&self, data: &[u8]) -> io::Result<()> {
+        io::stdout().write_all(data)
+    }
+
+    /// Writes output of the program to the standard error of the host
+    fn stderr(&self, data: &[u8]) -> io::Result<()> {
+        io::stderr().write_all(data)
+    }
+
+    /// Flushes the host side of the standard output streams, this is invoked
+    /// whenever the program asks for its output to be flushed
+    fn flush(&self) -> io::Result<()> {
+        io::stdout().flush()?;
+        io::stderr().flush()
+    }
+
+    /// Records a message the program logged
+    fn log(&self, record: &WasiLogRecord) {
+        trace_log(record)
+    }
+}
+
+/// Message logged by the program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasiLogRecord {
+    /// Process that logged the message, when the runtime knows its ID
+    pub pid: Option<u32>,
+    /// Thread that logged the message
+    pub tid: WasiThreadId,
+    pub level: Level,
+    pub text: String,
 }
 
-#[derive(Debug)]
+impl WasiLogRecord {
+    /// Makes a record of a line the program logged. A line may start with
+    /// the level it is logged at, either as `[WARN] ...` or `WARN: ...`,
+    /// otherwise it is logged at info level.
+    pub fn parse(pid: Option<u32>, tid: WasiThreadId, line: &str) -> Self {
+        let (level, text) = split_level(line).unwrap_or((Level::INFO, line));
+        Self {
+            pid,
+            tid,
+            level,
+            text: text.to_string(),
+        }
+    }
+}
+
+fn split_level(line: &str) -> Option<(Level, &str)> {
+    let (word, text) = match line.strip_prefix('[') {
+        Some(rest) => rest.split_once(']')?,
+        None => line.split_once(':')?,
+    };
+    // Levels also parse from numbers, which are not meant here
+    if word.is_empty() || !word.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some((word.parse().ok()?, text.trim_start()))
+}
+
+/// Records a message through tracing
+fn trace_log(record: &WasiLogRecord) {
+    let (pid, tid, text) = (record.pid, u32::from(record.tid), &record.text);
+    // The level of a tracing event has to be known up front
+    match record.level {
+        Level::ERROR => tracing::error!(?pid, tid, "{}", text),
+        Level::WARN => tracing::warn!(?pid, tid, "{}", text),
+        Level::INFO => tracing::info!(?pid, tid, "{}", text),
+        Level::DEBUG => tracing::debug!(?pid, tid, "{}", text),
+        _ => tracing::trace!(?pid, tid, "{}", text),
+    }
+}
+
+/// Destination for the output written through the runtime
+pub type WasiOutputSink = Arc<Mutex<dyn Write + Send>>;
+
+/// Receives the messages the program logs
+pub type WasiLogSink = Arc<dyn Fn(&WasiLogRecord) + Send + Sync>;
+
+/// Size of the blocks fully buffered output is passed on in
+pub const OUTPUT_BLOCK_SIZE: usize = 8192;
+
+/// How the output written through the runtime is buffered before it is
+/// passed on to its destination
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WasiBuffering {
+    /// Every write is passed on, and flushed, straight away
+    Unbuffered,
+    /// Output is passed on once a line is complete
+    Line,
+    /// Output is passed on in blocks of [`OUTPUT_BLOCK_SIZE`] bytes
+    Full,
+}
+
+impl Default for WasiBuffering {
+    fn default() -> Self {
+        Self::Unbuffered
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Writes `data` to `dest`, flushing it afterwards if asked to
+fn pass_on<W: Write + ?Sized>(dest: &mut W, data: &[u8], flush: bool) -> io::Result<()> {
+    if !data.is_empty() {
+        dest.write_all(data)?;
+    }
+    if flush {
+        dest.flush()?;
+    }
+    Ok(())
+}
+
+/// Size of the terminal in columns and rows given by the `COLUMNS` and
+/// `LINES` variables that `var` looks up, the default size is used for the
+/// ones that are missing or invalid
+fn tty_size_from_vars(var: impl Fn(&str) -> Option<String>) -> (u32, u32) {
+    let size = |name: &str, default: u32| {
+        var(name)
+            .and_then(|value| value.trim().parse().ok())
+            .filter(|size| *size > 0)
+            .unwrap_or(default)
+    };
+    let default = WasiTtyState::default();
+    (size("COLUMNS", default.cols), size("LINES", default.rows))
+}
+
+#[derive(Derivative)]
+#[derivative(Debug)]
 pub struct PluggableRuntimeImplementation {
     pub bus: Box<dyn VirtualBus + Sync>,
     pub networking: Box<dyn VirtualNetworking + Sync>,
     pub thread_id_seed: AtomicU32,
+    /// State of the TTY as it was last set
+    pub tty: Mutex<WasiTtyState>,
+    /// Receives what is written to `stdout()` instead of the host
+    #[derivative(Debug = "ignore")]
+    pub stdout_sink: Option<WasiOutputSink>,
+    /// Receives what is written to `stderr()` instead of the host
+    #[derivative(Debug = "ignore")]
+    pub stderr_sink: Option<WasiOutputSink>,
+    pub stdout_buffering: WasiBuffering,
+    pub stderr_buffering: WasiBuffering,
+    /// Output that was written but not passed on yet
+    #[derivative(Debug = "ignore")]
+    stdout_pending: Mutex<Vec<u8>>,
+    #[derivative(Debug = "ignore")]
+    stderr_pending: Mutex<Vec<u8>>,
+    /// Least severe level of the messages the program logs that are recorded
+    pub log_level: Level,
+    /// Receives what is passed to `log()` instead of tracing
+    #[derivative(Debug = "ignore")]
+    pub log_sink: Option<WasiLogSink>,
 }
 
 impl PluggableRuntimeImplementation {
@@ -121,17 +402,152 @@ impl PluggableRuntimeImplementation {
     {
         self.networking = Box::new(net)
     }
+
+    pub fn set_stdout_sink<W>(&mut self, sink: W)
+    where
+        W: Write + Send + 'static,
+    {
+        self.stdout_sink = Some(Arc::new(Mutex::new(sink)))
+    }
+
+    pub fn set_stderr_sink<W>(&mut self, sink: W)
+    where
+        W: Write + Send + 'static,
+    {
+        self.stderr_sink = Some(Arc::new(Mutex::new(sink)))
+    }
+
+    /// Sets the size of the terminal programs see until it is resized,
+    /// instead of the one given by the `COLUMNS` and `LINES` environment
+    /// variables (80 columns by 25 rows without them)
+    pub fn set_tty_size(&mut self, cols: u32, rows: u32) {
+        let tty = self.tty.get_mut().unwrap();
+        tty.cols = cols;
+        tty.rows = rows;
+    }
+
+    pub fn set_log_level(&mut self, level: Level) {
+        self.log_level = level;
+    }
+
+    pub fn set_log_sink<F>(&mut self, sink: F)
+    where
+        F: Fn(&WasiLogRecord) + Send + Sync + 'static,
+    {
+        self.log_sink = Some(Arc::new(sink))
+    }
+
+    /// Writes the messages the program logs to `writer`, one per line
+    pub fn set_log_writer<W>(&mut self, writer: W)
+    where
+        W: Write + Send + 'static,
+    {
+        let writer = Mutex::new(writer);
+        self.set_log_sink(move |record| {
+            let mut writer = writer.lock().unwrap();
+            let pid = match record.pid {
+                Some(pid) => pid.to_string(),
+                None => "-".to_string(),
+            };
+            let tid = u32::from(record.tid);
+            let _ = writeln!(writer, "{} [{}:{}] {}", record.level, pid, tid, record.text)
+                .and_then(|_| writer.flush());
+        })
+    }
+
+    pub fn set_stdout_buffering(&mut self, buffering: WasiBuffering) {
+        self.stdout_buffering = buffering;
+    }
+
+    pub fn set_stderr_buffering(&mut self, buffering: WasiBuffering) {
+        self.stderr_buffering = buffering;
+    }
+
+    /// Passes output on to the sink of the stream, or the host if it has none
+    fn write_out(&self, stream: OutputStream, data: &[u8], flush: bool) -> io::Result<()> {
+        let sink = match stream {
+            OutputStream::Stdout => self.stdout_sink.as_ref(),
+            OutputStream::Stderr => self.stderr_sink.as_ref(),
+        };
+        match (sink, stream) {
+            (Some(sink), _) => pass_on(&mut *sink.lock().unwrap(), data, flush),
+            (None, OutputStream::Stdout) => pass_on(&mut io::stdout(), data, flush),
+            (None, OutputStream::Stderr) => pass_on(&mut io::stderr(), data, flush),
+        }
+    }
+
+    /// Buffers output as configured for the stream, passing on whatever is
+    /// due. The buffer stays locked meanwhile so the output keeps its order.
+    fn write_buffered(&self, stream: OutputStream, data: &[u8]) -> io::Result<()> {
+        let (buffering, pending) = match stream {
+            OutputStream::Stdout => (self.stdout_buffering, &self.stdout_pending),
+            OutputStream::Stderr => (self.stderr_buffering, &self.stderr_pending),
+        };
+        let mut pending = pending.lock().unwrap();
+        pending.extend_from_slice(data);
+        match buffering {
+            WasiBuffering::Unbuffered => {
+                let out = std::mem::take(&mut *pending);
+                self.write_out(stream, &out, true)
+            }
+            WasiBuffering::Line => match pending.iter().rposition(|b| *b == b'\n') {
+                Some(end) => {
+                    let rest = pending.split_off(end + 1);
+                    let out = std::mem::replace(&mut *pending, rest);
+                    self.write_out(stream, &out, true)
+                }
+                None => Ok(()),
+            },
+            WasiBuffering::Full if pending.len() >= OUTPUT_BLOCK_SIZE => {
+                let out = std::mem::take(&mut *pending);
+                self.write_out(stream, &out, false)
+            }
+            WasiBuffering::Full => Ok(()),
+        }
+    }
+
+    /// Passes on whatever output is still buffered and flushes the stream
+    fn flush_stream(&self, stream: OutputStream) -> io::Result<()> {
+        let pending = match stream {
+            OutputStream::Stdout => &self.stdout_pending,
+            OutputStream::Stderr => &self.stderr_pending,
+        };
+        let mut pending = pending.lock().unwrap();
+        let out = std::mem::take(&mut *pending);
+        self.write_out(stream, &out, true)
+    }
 }
 
 impl Default for PluggableRuntimeImplementation {
     fn default() -> Self {
+        let (cols, rows) = tty_size_from_vars(|name| std::env::var(name).ok());
         Self {
             #[cfg(not(feature = "host-vnet"))]
             networking: Box::new(wasmer_vnet::UnsupportedVirtualNetworking::default()),
-            #[cfg(feature = "host-vnet")]
+            #[cfg(all(feature = "host-vnet", not(feature = "host-tls")))]
             networking: Box::new(wasmer_wasi_local_networking::LocalNetworking::default()),
+            #[cfg(feature = "host-tls")]
+            networking: Box::new(wasmer_wasi_local_networking::TlsNetworking::new(
+                wasmer_wasi_local_networking::LocalNetworking::default(),
+            )),
             bus: Box::new(UnsupportedVirtualBus::default()),
             thread_id_seed: Default::default(),
+            tty: Mutex::new(WasiTtyState {
+                stdin_tty: atty::is(atty::Stream::Stdin),
+                stdout_tty: atty::is(atty::Stream::Stdout),
+                stderr_tty: atty::is(atty::Stream::Stderr),
+                cols,
+                rows,
+                ..WasiTtyState::default()
+            }),
+            stdout_sink: None,
+            stderr_sink: None,
+            stdout_buffering: WasiBuffering::default(),
+            stderr_buffering: WasiBuffering::default(),
+            stdout_pending: Mutex::new(Vec::new()),
+            stderr_pending: Mutex::new(Vec::new()),
+            log_level: Level::TRACE,
+            log_sink: None,
         }
     }
 }
@@ -148,4 +564,254 @@ impl WasiRuntimeImplementation for PluggableRuntimeImplementation {
     fn thread_generate_id(&self) -> WasiThreadId {
         self.thread_id_seed.fetch_add(1, Ordering::Relaxed).into()
     }
+
+    fn tty_get(&self) -> WasiTtyState {
+        self.tty.lock().unwrap().clone()
+    }
+
+    fn tty_set(&self, tty_state: WasiTtyState) {
+        *self.tty.lock().unwrap() = tty_state;
+    }
+
+    #[cfg(target_os = "linux")]
+    fn thread_set_affinity(&self, mask: &[u8]) -> Result<(), WasiThreadError> {
+        affinity::set(mask)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn thread_affinity(&self) -> Result<Vec<u8>, WasiThreadError> {
+        affinity::get()
+    }
+
+    fn stdout(&self, data: &[u8]) -> io::Result<()> {
+        self.write_buffered(OutputStream::Stdout, data)
+    }
+
+    fn stderr(&self, data: &[u8]) -> io::Result<()> {
+        self.write_buffered(OutputStream::Stderr, data)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.flush_stream(OutputStream::Stdout)?;
+        self.flush_stream(OutputStream::Stderr)
+    }
+
+    fn log(&self, record: &WasiLogRecord) {
+        // Levels compare by verbosity, the most severe being the least
+        if record.level > self.log_level {
+            return;
+        }
+        match self.log_sink.as_ref() {
+            Some(sink) => sink(record),
+            None => trace_log(record),
+        }
+    }
+}
+
+impl Drop for PluggableRuntimeImplementation {
+    fn drop(&mut self) {
+        // Buffered output would otherwise get lost
+        let _ = WasiRuntimeImplementation::flush(self);
+    }
+}
+
+/// CPU affinity of the calling thread (`sched_setaffinity`)
+#[cfg(target_os = "linux")]
+mod affinity {
+    use super::WasiThreadError;
+    use std::mem;
+
+    /// Number of CPUs of the machine, online or not
+    fn cpu_count() -> usize {
+        let count = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_CONF) };
+        (count.max(1) as usize).min(libc::CPU_SETSIZE as usize)
+    }
+
+    pub(super) fn set(mask: &[u8]) -> Result<(), WasiThreadError> {
+        let cpus = cpu_count();
+        let mut set: libc::cpu_set_t = unsafe { mem::zeroed() };
+        let mut any = false;
+        for (cpu, _) in mask
+            .iter()
+            .flat_map(|byte| (0..8).map(move |bit| byte & (1 << bit) != 0))
+            .enumerate()
+            .filter(|(_, on)| *on)
+        {
+            if cpu >= cpus {
+                return Err(WasiThreadError::InvalidAffinity);
+            }
+            unsafe { libc::CPU_SET(cpu, &mut set) };
+            any = true;
+        }
+        if !any {
+            return Err(WasiThreadError::InvalidAffinity);
+        }
+
+        let ret = unsafe { libc::sched_setaffinity(0, mem::size_of::<libc::cpu_set_t>(), &set) };
+        if ret != 0 {
+            // None of the CPUs are available to this process
+            return Err(WasiThreadError::InvalidAffinity);
+        }
+        Ok(())
+    }
+
+    pub(super) fn get() -> Result<Vec<u8>, WasiThreadError> {
+        let mut set: libc::cpu_set_t = unsafe { mem::zeroed() };
+        let ret =
+            unsafe { libc::sched_getaffinity(0, mem::size_of::<libc::cpu_set_t>(), &mut set) };
+        if ret != 0 {
+            return Err(WasiThreadError::Unsupported);
+        }
+
+        let cpus = cpu_count();
+        let mut mask = vec![0u8; (cpus + 7) / 8];
+        for cpu in 0..cpus {
+            if unsafe { libc::CPU_ISSET(cpu, &set) } {
+                mask[cpu / 8] |= 1 << (cpu % 8);
+            }
+        }
+        Ok(mask)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Pipe;
+    use std::io::Read;
+
+    #[test]
+    fn test_tty_size_from_vars() {
+        assert_eq!(tty_size_from_vars(|_| None), (80, 25));
+
+        let vars = |name: &str| match name {
+            "COLUMNS" => Some("132".to_string()),
+            "LINES" => Some(" 43\n".to_string()),
+            _ => None,
+        };
+        assert_eq!(tty_size_from_vars(vars), (132, 43));
+
+        // Invalid sizes are ignored
+        let vars = |name: &str| match name {
+            "COLUMNS" => Some("wide".to_string()),
+            "LINES" => Some("0".to_string()),
+            _ => None,
+        };
+        assert_eq!(tty_size_from_vars(vars), (80, 25));
+    }
+
+    #[test]
+    fn test_tty_size() {
+        let mut runtime = PluggableRuntimeImplementation::default();
+        runtime.set_tty_size(132, 43);
+        let tty = runtime.tty_get();
+        assert_eq!((tty.cols, tty.rows), (132, 43));
+        assert_eq!(tty.width, WasiTtyState::default().width);
+    }
+
+    #[test]
+    fn test_output_sinks() {
+        let mut stdout = Pipe::new();
+        let mut stderr = Pipe::new();
+        let mut runtime = PluggableRuntimeImplementation::default();
+        runtime.set_stdout_sink(stdout.clone());
+        runtime.set_stderr_sink(stderr.clone());
+
+        runtime.stdout(b"hello ").unwrap();
+        runtime.stdout(b"world").unwrap();
+        runtime.stderr(b"oops").unwrap();
+        runtime.flush().unwrap();
+
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).unwrap();
+        assert_eq!(output, "hello world");
+        let mut output = String::new();
+        stderr.read_to_string(&mut output).unwrap();
+        assert_eq!(output, "oops");
+    }
+
+    #[test]
+    fn test_output_buffering() {
+        let mut stdout = Pipe::new();
+        let mut stderr = Pipe::new();
+        let mut runtime = PluggableRuntimeImplementation::default();
+        runtime.set_stdout_sink(stdout.clone());
+        runtime.set_stderr_sink(stderr.clone());
+        runtime.set_stdout_buffering(WasiBuffering::Line);
+        runtime.set_stderr_buffering(WasiBuffering::Unbuffered);
+        let read = |pipe: &mut Pipe| {
+            let mut output = String::new();
+            pipe.read_to_string(&mut output).unwrap();
+            output
+        };
+
+        // A partial line is held back until the line is complete
+        runtime.stdout(b"hello ").unwrap();
+        assert_eq!(read(&mut stdout), "");
+        runtime.stdout(b"world\nbye").unwrap();
+        assert_eq!(read(&mut stdout), "hello world\n");
+        runtime.flush().unwrap();
+        assert_eq!(read(&mut stdout), "bye");
+
+        // Unbuffered output shows up straight away
+        runtime.stderr(b"oo").unwrap();
+        assert_eq!(read(&mut stderr), "oo");
+        runtime.stderr(b"ps").unwrap();
+        assert_eq!(read(&mut stderr), "ps");
+
+        // Fully buffered output is passed on in blocks
+        runtime.set_stderr_buffering(WasiBuffering::Full);
+        runtime.stderr(b"\n").unwrap();
+        assert_eq!(read(&mut stderr), "");
+        runtime.stderr(&[b'x'; OUTPUT_BLOCK_SIZE]).unwrap();
+        assert_eq!(read(&mut stderr).len(), OUTPUT_BLOCK_SIZE + 1);
+
+        runtime.stdout(b"unfinished").unwrap();
+        drop(runtime);
+        assert_eq!(read(&mut stdout), "unfinished");
+    }
+
+    #[test]
+    fn test_log_record_level() {
+        let parse = |line| {
+            let record = WasiLogRecord::parse(Some(1), 2u32.into(), line);
+            (record.level, record.text)
+        };
+        assert_eq!(
+            parse("WARN: disk is full"),
+            (Level::WARN, "disk is full".into())
+        );
+        assert_eq!(parse("[error] gone"), (Level::ERROR, "gone".into()));
+        assert_eq!(
+            parse("[1] not a level"),
+            (Level::INFO, "[1] not a level".into())
+        );
+        assert_eq!(parse("note: plain"), (Level::INFO, "note: plain".into()));
+        assert_eq!(parse("plain"), (Level::INFO, "plain".into()));
+    }
+
+    #[test]
+    fn test_log_sink() {
+        let logged = Arc::new(Mutex::new(Vec::new()));
+        let mut runtime = PluggableRuntimeImplementation::default();
+        runtime.set_log_level(Level::WARN);
+        runtime.set_log_sink({
+            let logged = logged.clone();
+            move |record: &WasiLogRecord| logged.lock().unwrap().push(record.clone())
+        });
+
+        let full = WasiLogRecord::parse(Some(7), 3u32.into(), "WARN: disk is almost full");
+        runtime.log(&full);
+        runtime.log(&WasiLogRecord::parse(Some(7), 3u32.into(), "too chatty"));
+        assert_eq!(*logged.lock().unwrap(), vec![full]);
+
+        let mut file = Pipe::new();
+        runtime.set_log_writer(file.clone());
+        runtime.log(&WasiLogRecord::parse(Some(7), 0u32.into(), "ERROR: first"));
+        runtime.log(&WasiLogRecord::parse(None, 1u32.into(), "[warn] second"));
+        let mut output = String::new();
+        file.read_to_string(&mut output).unwrap();
+        assert_eq!(output, "ERROR [7:0] first\nWARN [-:1] second\n");
+        assert_eq!(logged.lock().unwrap().len(), 1);
+    }
 }
@@ -1,14 +1,15 @@
 //! Builder system for configuring a [`WasiState`] and creating it.
 
-use crate::state::{default_fs_backing, WasiFs, WasiState};
+use crate::state::{default_fs_backing, RuntimeOutput, WasiFs, WasiState, WasiStateThreading};
 use crate::syscalls::types::{__WASI_STDERR_FILENO, __WASI_STDIN_FILENO, __WASI_STDOUT_FILENO};
 use crate::{WasiEnv, WasiFunctionEnv, WasiInodes};
 use generational_arena::Arena;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::sync::RwLock;
+use std::sync::{Condvar, Mutex, RwLock};
+use std::time::Duration;
 use thiserror::Error;
 use wasmer::AsStoreMut;
 use wasmer_vfs::{FsError, VirtualFile};
@@ -52,6 +53,11 @@ pub struct WasiStateBuilder {
     stdin_override: Option<Box<dyn VirtualFile + Send + Sync + 'static>>,
     fs_override: Option<Box<dyn wasmer_vfs::FileSystem>>,
     runtime_override: Option<Arc<dyn crate::WasiRuntimeImplementation + Send + Sync + 'static>>,
+    max_threads: Option<usize>,
+    max_processes: Option<usize>,
+    max_total_processes: Option<u32>,
+    default_accept_timeout: Option<Duration>,
+    allow_raw_sockets: bool,
 }
 
 impl std::fmt::Debug for WasiStateBuilder {
@@ -66,6 +72,11 @@ impl std::fmt::Debug for WasiStateBuilder {
             .field("stderr_override exists", &self.stderr_override.is_some())
             .field("stdin_override exists", &self.stdin_override.is_some())
             .field("runtime_override_exists", &self.runtime_override.is_some())
+            .field("max_threads", &self.max_threads)
+            .field("max_processes", &self.max_processes)
+            .field("max_total_processes", &self.max_total_processes)
+            .field("default_accept_timeout", &self.default_accept_timeout)
+            .field("allow_raw_sockets", &self.allow_raw_sockets)
             .finish()
     }
 }
@@ -336,6 +347,49 @@ impl WasiStateBuilder {
         self
     }
 
+    /// Limits the number of threads the program may have running at the
+    /// same time, on top of its main thread.
+    ///
+    /// Spawning a thread beyond this limit fails with `Errno::Again`
+    /// instead of creating it.
+    pub fn max_threads(&mut self, max_threads: usize) -> &mut Self {
+        self.max_threads = Some(max_threads);
+        self
+    }
+
+    /// Limits the number of sub-processes the program may have running at
+    /// the same time, processes that have exited don't count.
+    ///
+    /// Spawning a process beyond this limit fails with `BusErrno::Alloc`
+    /// instead of creating it.
+    pub fn max_processes(&mut self, max_processes: usize) -> &mut Self {
+        self.max_processes = Some(max_processes);
+        self
+    }
+
+    /// Limits the number of sub-processes the program may spawn over the
+    /// whole run, including the ones that have already exited.
+    pub fn max_total_processes(&mut self, max_total_processes: u32) -> &mut Self {
+        self.max_total_processes = Some(max_total_processes);
+        self
+    }
+
+    /// Sets how long accepting a connection waits on sockets that have no
+    /// accept timeout of their own, by default it waits forever.
+    pub fn default_accept_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.default_accept_timeout = Some(timeout);
+        self
+    }
+
+    /// Lets the program open raw sockets (`Socktype::Raw`), which can send
+    /// and receive arbitrary packets, e.g. ICMP for `ping`. Only trusted
+    /// programs should be given this, by default opening one fails with
+    /// `Errno::Perm`.
+    pub fn allow_raw_sockets(&mut self, allow: bool) -> &mut Self {
+        self.allow_raw_sockets = allow;
+        self
+    }
+
     /// Consumes the [`WasiStateBuilder`] and produces a [`WasiState`]
     ///
     /// Returns the error from `WasiFs::new` if there's an error
@@ -467,7 +521,16 @@ impl WasiStateBuilder {
             fs: wasi_fs,
             inodes: Arc::new(inodes),
             args: self.args.clone(),
-            threading: Default::default(),
+            threading: Mutex::new(WasiStateThreading {
+                max_threads: self.max_threads,
+                max_processes: self.max_processes,
+                max_total_processes: self.max_total_processes,
+                ..Default::default()
+            }),
+            futex_wakeups: Condvar::new(),
+            signals: Mutex::new(Default::default()),
+            tty_before_raw: Mutex::new(None),
+            tty_modes: Mutex::new(BTreeSet::new()),
             envs: self
                 .envs
                 .iter()
@@ -497,12 +560,40 @@ impl WasiStateBuilder {
         &mut self,
         store: &mut impl AsStoreMut,
     ) -> Result<WasiFunctionEnv, WasiStateCreationError> {
+        let redirected = [
+            self.stdin_override.is_some(),
+            self.stdout_override.is_some(),
+            self.stderr_override.is_some(),
+        ];
         let state = self.build()?;
 
         let mut env = WasiEnv::new(state);
         if let Some(runtime) = self.runtime_override.as_ref() {
             env.runtime = runtime.clone();
         }
+
+        // Output that wasn't redirected goes wherever the runtime sends it
+        {
+            let inodes = env.state.inodes.read().unwrap();
+            if !redirected[1] {
+                let stdout = Box::new(RuntimeOutput::stdout(env.runtime.clone()));
+                env.state
+                    .fs
+                    .swap_file(inodes.deref(), __WASI_STDOUT_FILENO, stdout)
+                    .map_err(WasiStateCreationError::FileSystemError)?;
+            }
+            if !redirected[2] {
+                let stderr = Box::new(RuntimeOutput::stderr(env.runtime.clone()));
+                env.state
+                    .fs
+                    .swap_file(inodes.deref(), __WASI_STDERR_FILENO, stderr)
+                    .map_err(WasiStateCreationError::FileSystemError)?;
+            }
+        }
+
+        env.set_redirected_stdio(redirected[0], redirected[1], redirected[2]);
+        env.set_default_accept_timeout(self.default_accept_timeout);
+        env.set_raw_sockets_allowed(self.allow_raw_sockets);
         Ok(WasiFunctionEnv::new(store, env))
     }
 }
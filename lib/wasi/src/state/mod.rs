@@ -36,8 +36,8 @@ pub use generational_arena::Index as Inode;
 #[cfg(feature = "enable-serde")]
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
-use std::collections::HashMap;
 use std::collections::VecDeque;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::sync::mpsc;
 use std::sync::Arc;
 use std::{
@@ -47,13 +47,13 @@ use std::{
     path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
-        Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard,
+        Condvar, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard,
     },
 };
 use tracing::{debug, trace};
 use wasmer_vbus::BusSpawnedProcess;
 use wasmer_wasi_types::wasi::{
-    Errno, Fd as WasiFd, Fdflags, Fdstat, Filesize, Filestat, Filetype, Preopentype, Rights,
+    Errno, Fd as WasiFd, Fdflags, Fdstat, Filesize, Filestat, Filetype, Preopentype, Rights, Signal,
 };
 use wasmer_wasi_types::wasi::{Prestat, PrestatEnum};
 
@@ -71,6 +71,7 @@ const STDIN_DEFAULT_RIGHTS: Rights = {
             | Rights::FD_READ.bits()
             | Rights::FD_SYNC.bits()
             | Rights::FD_ADVISE.bits()
+            | Rights::FD_FDSTAT_SET_FLAGS.bits()
             | Rights::FD_FILESTAT_GET.bits()
             | Rights::POLL_FD_READWRITE.bits(),
     )
@@ -1320,12 +1321,17 @@ impl WasiFs {
     pub fn fdstat(&self, inodes: &WasiInodes, fd: WasiFd) -> Result<Fdstat, Errno> {
         match fd {
             __WASI_STDIN_FILENO => {
+                // Stdin may have been made non-blocking by the program
+                let fs_flags = self
+                    .get_fd(fd)
+                    .map(|fd| fd.flags)
+                    .unwrap_or_else(|_| Fdflags::empty());
                 return Ok(Fdstat {
                     fs_filetype: Filetype::CharacterDevice,
-                    fs_flags: Fdflags::empty(),
+                    fs_flags,
                     fs_rights_base: STDIN_DEFAULT_RIGHTS,
                     fs_rights_inheriting: Rights::empty(),
-                })
+                });
             }
             __WASI_STDOUT_FILENO => {
                 return Ok(Fdstat {
@@ -1814,12 +1820,126 @@ impl WasiState {
 pub(crate) struct WasiStateThreading {
     #[cfg_attr(feature = "enable-serde", serde(skip))]
     pub threads: HashMap<WasiThreadId, WasiThread>,
+    /// Exit codes of the threads that exited but weren't joined yet
+    #[cfg_attr(feature = "enable-serde", serde(skip))]
+    pub exit_codes: HashMap<WasiThreadId, __wasi_exitcode_t>,
+    /// Running threads that were detached, nothing keeps their exit code
+    #[cfg_attr(feature = "enable-serde", serde(skip))]
+    pub detached: HashSet<WasiThreadId>,
     pub thread_seed: u32,
     #[cfg_attr(feature = "enable-serde", serde(skip))]
     pub processes: HashMap<WasiBusProcessId, BusSpawnedProcess>,
     #[cfg_attr(feature = "enable-serde", serde(skip))]
     pub process_reuse: HashMap<Cow<'static, str>, WasiBusProcessId>,
     pub process_seed: u32,
+    /// Sub-processes that are being spawned and already count towards the
+    /// limits
+    #[cfg_attr(feature = "enable-serde", serde(skip))]
+    pub processes_spawning: usize,
+    /// Number of sub-processes that were spawned over the whole run
+    pub processes_spawned: u32,
+    /// Maximum number of threads that may run at the same time (on top of
+    /// the main thread)
+    pub max_threads: Option<usize>,
+    /// Maximum number of sub-processes that may run at the same time
+    pub max_processes: Option<usize>,
+    /// Maximum number of sub-processes that may be spawned over the whole run
+    pub max_total_processes: Option<u32>,
+    /// Threads waiting on a futex, indexed by the address of the futex
+    #[cfg_attr(feature = "enable-serde", serde(skip))]
+    pub futexes: HashMap<u64, WasiFutex>,
+}
+
+/// Threads waiting on a futex and the wakeups they haven't picked up yet
+#[derive(Debug, Default)]
+pub(crate) struct WasiFutex {
+    pub waiters: u32,
+    pub wakeups: u32,
+}
+
+impl WasiStateThreading {
+    /// Reserves room for another sub-process if it stays within the limits,
+    /// the reservation ends with `insert_process` once the process is
+    /// spawned or with `cancel_process` if spawning it failed
+    pub fn reserve_process(&mut self) -> bool {
+        if let Some(max_processes) = self.max_processes {
+            let running = self
+                .processes
+                .values()
+                .filter(|process| process.inst.exit_code().is_none())
+                .count();
+            if running + self.processes_spawning >= max_processes {
+                return false;
+            }
+        }
+        if let Some(max_total_processes) = self.max_total_processes {
+            if self.processes_spawned >= max_total_processes {
+                return false;
+            }
+        }
+        self.processes_spawning += 1;
+        self.processes_spawned += 1;
+        true
+    }
+
+    /// Adds a sub-process that was reserved with `reserve_process`
+    pub fn insert_process(&mut self, process: BusSpawnedProcess) -> WasiBusProcessId {
+        self.processes_spawning -= 1;
+        self.process_seed += 1;
+        let bid: WasiBusProcessId = self.process_seed.into();
+        self.processes.insert(bid, process);
+        bid
+    }
+
+    /// Gives back the room reserved for a sub-process that failed to spawn
+    pub fn cancel_process(&mut self) {
+        self.processes_spawning -= 1;
+        self.processes_spawned -= 1;
+    }
+}
+
+/// Signals that were sent to the process, in the order they were sent
+#[derive(Debug, Default)]
+pub(crate) struct WasiSignals {
+    pending: Vec<Signal>,
+    /// How many of the pending signals (the oldest ones) already interrupted
+    /// a blocking call
+    interrupted: usize,
+}
+
+impl WasiSignals {
+    /// Makes a signal pending. A signal that is already pending isn't
+    /// queued twice, it moves to the back instead and can interrupt a
+    /// blocking call again.
+    pub(crate) fn push(&mut self, sig: Signal) {
+        if let Some(index) = self.pending.iter().position(|pending| *pending == sig) {
+            self.pending.remove(index);
+            if index < self.interrupted {
+                self.interrupted -= 1;
+            }
+        }
+        self.pending.push(sig);
+    }
+
+    /// Takes the oldest pending signal
+    pub(crate) fn take(&mut self) -> Option<Signal> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        self.interrupted = self.interrupted.saturating_sub(1);
+        Some(self.pending.remove(0))
+    }
+
+    /// Whether a blocking call should give up with `Errno::Intr`, which is the
+    /// case once for every signal. The signal stays pending.
+    pub(crate) fn interrupt(&mut self) -> bool {
+        if self.interrupted < self.pending.len() {
+            self.interrupted += 1;
+            true
+        } else {
+            false
+        }
+    }
 }
 
 /// Top level data type containing all* the state with which WASI can
@@ -1856,6 +1976,18 @@ pub struct WasiState {
     pub fs: WasiFs,
     pub inodes: Arc<RwLock<WasiInodes>>,
     pub(crate) threading: Mutex<WasiStateThreading>,
+    /// Signalled whenever a futex is woken up (used with `threading`)
+    #[cfg_attr(feature = "enable-serde", serde(skip))]
+    pub(crate) futex_wakeups: Condvar,
+    /// Signals that were sent to the process and weren't taken yet
+    #[cfg_attr(feature = "enable-serde", serde(skip))]
+    pub(crate) signals: Mutex<WasiSignals>,
+    /// State of the TTY before the program put it into raw mode
+    #[cfg_attr(feature = "enable-serde", serde(skip))]
+    pub(crate) tty_before_raw: Mutex<Option<crate::WasiTtyState>>,
+    /// Modes the program turned on in the terminal
+    #[cfg_attr(feature = "enable-serde", serde(skip))]
+    pub(crate) tty_modes: Mutex<BTreeSet<crate::WasiTtyMode>>,
     pub args: Vec<Vec<u8>>,
     pub envs: Vec<Vec<u8>>,
 }
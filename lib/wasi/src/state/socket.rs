@@ -1,4 +1,4 @@
-use super::types::net_error_into_wasi_err;
+use super::types::{bind_error_into_wasi_err, net_error_into_wasi_err};
 use crate::syscalls::types::*;
 use crate::syscalls::{read_bytes, write_bytes};
 use bytes::{Buf, Bytes};
@@ -11,7 +11,7 @@ use std::time::Duration;
 #[allow(unused_imports)]
 use tracing::{debug, error, info, warn};
 use wasmer::{MemorySize, MemoryView, WasmPtr, WasmSlice};
-use wasmer_vnet::{net_error_into_io_err, TimeType};
+use wasmer_vnet::{net_error_into_io_err, TcpStats, TimeType};
 use wasmer_vnet::{
     IpCidr, IpRoute, SocketHttpRequest, VirtualIcmpSocket, VirtualNetworking, VirtualRawSocket,
     VirtualTcpListener, VirtualTcpSocket, VirtualUdpSocket, VirtualWebSocket,
@@ -49,17 +49,50 @@ pub enum InodeSocketKind {
         recv_timeout: Option<Duration>,
         connect_timeout: Option<Duration>,
         accept_timeout: Option<Duration>,
+        linger: Option<Duration>,
     },
     HttpRequest(Mutex<SocketHttpRequest>, InodeHttpSocketType),
     WebSocket(Box<dyn VirtualWebSocket + Sync>),
     Icmp(Box<dyn VirtualIcmpSocket + Sync>),
     Raw(Box<dyn VirtualRawSocket + Sync>),
-    TcpListener(Box<dyn VirtualTcpListener + Sync>),
+    TcpListener {
+        socket: Box<dyn VirtualTcpListener + Sync>,
+        /// Timeouts the accepted connections start with
+        send_timeout: Option<Duration>,
+        recv_timeout: Option<Duration>,
+        linger: Option<Duration>,
+    },
     TcpStream(Box<dyn VirtualTcpSocket + Sync>),
     UdpSocket(Box<dyn VirtualUdpSocket + Sync>),
     Closed,
 }
 
+/// State of a socket, as reported by [`SocketInfo`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WasiSocketState {
+    /// Opened but neither bound nor connected yet
+    Opened,
+    /// Bound to a local address
+    Bound,
+    /// Waiting for incoming connections
+    Listening,
+    /// Connected to a peer
+    Connected,
+    /// Shut down
+    Closed,
+}
+
+/// Description of an open socket (see `WasiEnv::socket_snapshot()`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocketInfo {
+    /// Descriptor the program uses for the socket
+    pub fd: u32,
+    pub protocol: SockProto,
+    pub state: WasiSocketState,
+    pub local_addr: Option<SocketAddr>,
+    pub peer_addr: Option<SocketAddr>,
+}
+
 pub enum WasiSocketOption {
     Noop,
     ReusePort,
@@ -167,6 +200,7 @@ impl InodeSocket {
             InodeSocketKind::PreSocket {
                 family,
                 ty,
+                pt,
                 addr,
                 reuse_port,
                 reuse_addr,
@@ -200,9 +234,20 @@ impl InodeSocket {
                     Socktype::Dgram => {
                         let socket = net
                             .bind_udp(addr, *reuse_port, *reuse_addr)
-                            .map_err(net_error_into_wasi_err)?;
+                            .map_err(bind_error_into_wasi_err)?;
                         Some(InodeSocket::new(InodeSocketKind::UdpSocket(socket)))
                     }
+                    Socktype::Raw => match *pt {
+                        SockProto::Icmp | SockProto::Icmpv6 => {
+                            let socket =
+                                net.bind_icmp(addr.ip()).map_err(net_error_into_wasi_err)?;
+                            Some(InodeSocket::new(InodeSocketKind::Icmp(socket)))
+                        }
+                        _ => {
+                            let socket = net.bind_raw().map_err(net_error_into_wasi_err)?;
+                            Some(InodeSocket::new(InodeSocketKind::Raw(socket)))
+                        }
+                    },
                     _ => return Err(Errno::Inval),
                 })
             }
@@ -222,7 +267,10 @@ impl InodeSocket {
                 only_v6,
                 reuse_port,
                 reuse_addr,
+                send_timeout,
+                recv_timeout,
                 accept_timeout,
+                linger,
                 ..
             } => Ok(match *ty {
                 Socktype::Stream => {
@@ -232,13 +280,18 @@ impl InodeSocket {
                     let addr = *addr.as_ref().unwrap();
                     let mut socket = net
                         .listen_tcp(addr, *only_v6, *reuse_port, *reuse_addr)
-                        .map_err(net_error_into_wasi_err)?;
+                        .map_err(bind_error_into_wasi_err)?;
                     if let Some(accept_timeout) = accept_timeout {
                         socket
                             .set_timeout(Some(*accept_timeout))
                             .map_err(net_error_into_wasi_err)?;
                     }
-                    Some(InodeSocket::new(InodeSocketKind::TcpListener(socket)))
+                    Some(InodeSocket::new(InodeSocketKind::TcpListener {
+                        socket,
+                        send_timeout: *send_timeout,
+                        recv_timeout: *recv_timeout,
+                        linger: *linger,
+                    }))
                 }
                 _ => return Err(Errno::Notsup),
             }),
@@ -251,13 +304,15 @@ impl InodeSocket {
         &self,
         _fd_flags: Fdflags,
     ) -> Result<(Box<dyn VirtualTcpSocket + Sync>, SocketAddr), Errno> {
-        let (sock, addr) = match &self.kind {
-            InodeSocketKind::TcpListener(sock) => sock.accept().map_err(net_error_into_wasi_err),
+        match &self.kind {
+            InodeSocketKind::TcpListener { socket, .. } => {
+                let (sock, addr) = socket.accept().map_err(net_error_into_wasi_err)?;
+                Ok((self.seed_accepted(sock)?, addr))
+            }
             InodeSocketKind::PreSocket { .. } => Err(Errno::Notconn),
             InodeSocketKind::Closed => Err(Errno::Io),
             _ => Err(Errno::Notsup),
-        }?;
-        Ok((sock, addr))
+        }
     }
 
     pub fn accept_timeout(
@@ -265,29 +320,92 @@ impl InodeSocket {
         _fd_flags: Fdflags,
         timeout: Duration,
     ) -> Result<(Box<dyn VirtualTcpSocket + Sync>, SocketAddr), Errno> {
-        let (sock, addr) = match &self.kind {
-            InodeSocketKind::TcpListener(sock) => sock
-                .accept_timeout(timeout)
-                .map_err(net_error_into_wasi_err),
+        match &self.kind {
+            InodeSocketKind::TcpListener { socket, .. } => {
+                let (sock, addr) = socket
+                    .accept_timeout(timeout)
+                    .map_err(net_error_into_wasi_err)?;
+                Ok((self.seed_accepted(sock)?, addr))
+            }
             InodeSocketKind::PreSocket { .. } => Err(Errno::Notconn),
             InodeSocketKind::Closed => Err(Errno::Io),
             _ => Err(Errno::Notsup),
-        }?;
-        Ok((sock, addr))
+        }
+    }
+
+    /// Gives a connection accepted by this listener the read and write
+    /// timeouts and the linger time of the listener
+    fn seed_accepted(
+        &self,
+        mut sock: Box<dyn VirtualTcpSocket + Sync>,
+    ) -> Result<Box<dyn VirtualTcpSocket + Sync>, Errno> {
+        if let InodeSocketKind::TcpListener {
+            send_timeout,
+            recv_timeout,
+            linger,
+            ..
+        } = &self.kind
+        {
+            if linger.is_some() {
+                sock.set_opt_time(TimeType::Linger, *linger)
+                    .map_err(net_error_into_wasi_err)?;
+            }
+            if send_timeout.is_some() {
+                sock.set_opt_time(TimeType::WriteTimeout, *send_timeout)
+                    .map_err(net_error_into_wasi_err)?;
+            }
+            if recv_timeout.is_some() {
+                sock.set_opt_time(TimeType::ReadTimeout, *recv_timeout)
+                    .map_err(net_error_into_wasi_err)?;
+            }
+        }
+        Ok(sock)
     }
 
     pub fn connect(
         &mut self,
         net: &(dyn VirtualNetworking),
         peer: SocketAddr,
+    ) -> Result<Option<InodeSocket>, Errno> {
+        self.connect_inner(net, peer, None)
+    }
+
+    /// Connects a stream socket and secures the connection with TLS, the
+    /// certificate of the peer is verified against `server_name`
+    pub fn connect_tls(
+        &mut self,
+        net: &(dyn VirtualNetworking),
+        peer: SocketAddr,
+        server_name: &str,
+    ) -> Result<Option<InodeSocket>, Errno> {
+        match &self.kind {
+            InodeSocketKind::PreSocket {
+                ty: Socktype::Stream,
+                ..
+            } => self.connect_inner(net, peer, Some(server_name)),
+            InodeSocketKind::Closed => Err(Errno::Io),
+            _ => Err(Errno::Notsup),
+        }
+    }
+
+    fn connect_inner(
+        &mut self,
+        net: &(dyn VirtualNetworking),
+        peer: SocketAddr,
+        server_name: Option<&str>,
     ) -> Result<Option<InodeSocket>, Errno> {
         match &mut self.kind {
             InodeSocketKind::PreSocket {
                 ty,
                 addr,
+                send_buf_size,
+                recv_buf_size,
+                reuse_port,
+                reuse_addr,
                 send_timeout,
                 recv_timeout,
                 connect_timeout,
+                linger,
                 ..
             } => Ok(match *ty {
                 Socktype::Stream => {
@@ -301,9 +419,13 @@ impl InodeSocket {
                             SocketAddr::new(ip, 0)
                         }
                     };
-                    let mut socket = net
-                        .connect_tcp(addr, peer, *connect_timeout)
-                        .map_err(net_error_into_wasi_err)?;
+                    let mut socket = match server_name {
+                        Some(server_name) => {
+                            net.connect_tls(addr, peer, server_name, *connect_timeout)
+                        }
+                        None => net.connect_tcp(addr, peer, *connect_timeout),
+                    }
+                    .map_err(bind_error_into_wasi_err)?;
                     if let Some(timeout) = send_timeout {
                         socket
                             .set_opt_time(TimeType::WriteTimeout, Some(*timeout))
@@ -314,9 +436,43 @@ impl InodeSocket {
                             .set_opt_time(TimeType::ReadTimeout, Some(*timeout))
                             .map_err(net_error_into_wasi_err)?;
                     }
+                    if let Some(size) = send_buf_size {
+                        socket
+                            .set_send_buf_size(*size)
+                            .map_err(net_error_into_wasi_err)?;
+                    }
+                    if let Some(size) = recv_buf_size {
+                        socket
+                            .set_recv_buf_size(*size)
+                            .map_err(net_error_into_wasi_err)?;
+                    }
+                    if let Some(linger) = linger {
+                        socket
+                            .set_opt_time(TimeType::Linger, Some(*linger))
+                            .map_err(net_error_into_wasi_err)?;
+                    }
                     Some(InodeSocket::new(InodeSocketKind::TcpStream(socket)))
                 }
-                Socktype::Dgram => return Err(Errno::Inval),
+                Socktype::Dgram => {
+                    // Connecting an unbound datagram socket implicitly binds it,
+                    // after which sends without an address go to the peer and
+                    // only datagrams from the peer are received
+                    let addr = match addr {
+                        Some(a) => *a,
+                        None => {
+                            let ip = match peer.is_ipv4() {
+                                true => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                                false => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+                            };
+                            SocketAddr::new(ip, 0)
+                        }
+                    };
+                    let mut socket = net
+                        .bind_udp(addr, *reuse_port, *reuse_addr)
+                        .map_err(bind_error_into_wasi_err)?;
+                    socket.connect(peer).map_err(net_error_into_wasi_err)?;
+                    Some(InodeSocket::new(InodeSocketKind::UdpSocket(socket)))
+                }
                 _ => return Err(Errno::Notsup),
             }),
             InodeSocketKind::UdpSocket(sock) => {
@@ -333,7 +489,7 @@ impl InodeSocket {
             InodeSocketKind::PreSocket { .. } => WasiSocketStatus::Opening,
             InodeSocketKind::WebSocket(_) => WasiSocketStatus::Opened,
             InodeSocketKind::HttpRequest(..) => WasiSocketStatus::Opened,
-            InodeSocketKind::TcpListener(_) => WasiSocketStatus::Opened,
+            InodeSocketKind::TcpListener { .. } => WasiSocketStatus::Opened,
             InodeSocketKind::TcpStream(_) => WasiSocketStatus::Opened,
             InodeSocketKind::UdpSocket(_) => WasiSocketStatus::Opened,
             InodeSocketKind::Closed => WasiSocketStatus::Closed,
@@ -379,8 +535,8 @@ impl InodeSocket {
                 }
             }
             InodeSocketKind::Icmp(sock) => sock.addr_local().map_err(net_error_into_wasi_err)?,
-            InodeSocketKind::TcpListener(sock) => {
-                sock.addr_local().map_err(net_error_into_wasi_err)?
+            InodeSocketKind::TcpListener { socket, .. } => {
+                socket.addr_local().map_err(net_error_into_wasi_err)?
             }
             InodeSocketKind::TcpStream(sock) => {
                 sock.addr_local().map_err(net_error_into_wasi_err)?
@@ -428,18 +584,80 @@ impl InodeSocket {
         })
     }
 
+    /// Describes the socket for the descriptor `fd`
+    pub fn info(&self, fd: u32) -> SocketInfo {
+        let (protocol, state) = match &self.kind {
+            InodeSocketKind::PreSocket { pt, addr, .. } => (
+                *pt,
+                match addr {
+                    Some(_) => WasiSocketState::Bound,
+                    None => WasiSocketState::Opened,
+                },
+            ),
+            InodeSocketKind::HttpRequest(..) | InodeSocketKind::WebSocket(_) => {
+                (SockProto::Tcp, WasiSocketState::Connected)
+            }
+            InodeSocketKind::Icmp(_) => (SockProto::Icmp, WasiSocketState::Bound),
+            InodeSocketKind::Raw(_) => (SockProto::ProtoRaw, WasiSocketState::Bound),
+            InodeSocketKind::TcpListener { .. } => (SockProto::Tcp, WasiSocketState::Listening),
+            InodeSocketKind::TcpStream(_) => (SockProto::Tcp, WasiSocketState::Connected),
+            InodeSocketKind::UdpSocket(sock) => (
+                SockProto::Udp,
+                match sock.addr_peer() {
+                    Ok(Some(_)) => WasiSocketState::Connected,
+                    _ => WasiSocketState::Bound,
+                },
+            ),
+            InodeSocketKind::Closed => (SockProto::Ip, WasiSocketState::Closed),
+        };
+        let local_addr = match state {
+            WasiSocketState::Opened | WasiSocketState::Closed => None,
+            _ => self.addr_local().ok(),
+        };
+        let peer_addr = match state {
+            WasiSocketState::Connected => self.addr_peer().ok(),
+            _ => None,
+        };
+        SocketInfo {
+            fd,
+            protocol,
+            state,
+            local_addr,
+            peer_addr,
+        }
+    }
+
+    /// Returns the statistics of a connected TCP socket, anything the
+    /// networking implementation does not track is left empty
+    pub fn tcp_stats(&self) -> Result<TcpStats, Errno> {
+        match &self.kind {
+            InodeSocketKind::TcpStream(sock) => sock.stats().map_err(net_error_into_wasi_err),
+            InodeSocketKind::PreSocket { .. } => Err(Errno::Notconn),
+            InodeSocketKind::Closed => Err(Errno::Io),
+            _ => Err(Errno::Notsup),
+        }
+    }
+
     pub fn set_opt_flag(&mut self, option: WasiSocketOption, val: bool) -> Result<(), Errno> {
         match &mut self.kind {
             InodeSocketKind::PreSocket {
                 only_v6,
                 reuse_port,
                 reuse_addr,
+                linger,
                 ..
             } => {
                 match option {
                     WasiSocketOption::OnlyV6 => *only_v6 = val,
                     WasiSocketOption::ReusePort => *reuse_port = val,
                     WasiSocketOption::ReuseAddr => *reuse_addr = val,
+                    // Turning linger on without a time closes abortively
+                    WasiSocketOption::Linger => {
+                        *linger = match val {
+                            true => Some(linger.unwrap_or_default()),
+                            false => None,
+                        }
+                    }
                     _ => return Err(Errno::Inval),
                 };
             }
@@ -453,6 +671,18 @@ impl InodeSocket {
                 WasiSocketOption::NoDelay => {
                     sock.set_nodelay(val).map_err(net_error_into_wasi_err)?
                 }
+                WasiSocketOption::Linger => {
+                    let linger = match val {
+                        true => Some(
+                            sock.opt_time(TimeType::Linger)
+                                .map_err(net_error_into_wasi_err)?
+                                .unwrap_or_default(),
+                        ),
+                        false => None,
+                    };
+                    sock.set_opt_time(TimeType::Linger, linger)
+                        .map_err(net_error_into_wasi_err)?
+                }
                 _ => return Err(Errno::Inval),
             },
             InodeSocketKind::UdpSocket(sock) => match option {
@@ -479,11 +709,13 @@ impl InodeSocket {
                 only_v6,
                 reuse_port,
                 reuse_addr,
+                linger,
                 ..
             } => match option {
                 WasiSocketOption::OnlyV6 => *only_v6,
                 WasiSocketOption::ReusePort => *reuse_port,
                 WasiSocketOption::ReuseAddr => *reuse_addr,
+                WasiSocketOption::Linger => linger.is_some(),
                 _ => return Err(Errno::Inval),
             },
             InodeSocketKind::Raw(sock) => match option {
@@ -494,6 +726,10 @@ impl InodeSocket {
             },
             InodeSocketKind::TcpStream(sock) => match option {
                 WasiSocketOption::NoDelay => sock.nodelay().map_err(net_error_into_wasi_err)?,
+                WasiSocketOption::Linger => sock
+                    .opt_time(TimeType::Linger)
+                    .map_err(net_error_into_wasi_err)?
+                    .is_some(),
                 _ => return Err(Errno::Inval),
             },
             InodeSocketKind::UdpSocket(sock) => match option {
@@ -596,9 +832,26 @@ impl InodeSocket {
             InodeSocketKind::TcpStream(sock) => sock
                 .set_opt_time(ty, timeout)
                 .map_err(net_error_into_wasi_err),
-            InodeSocketKind::TcpListener(sock) => match ty {
+            InodeSocketKind::TcpListener {
+                socket,
+                send_timeout,
+                recv_timeout,
+                linger,
+            } => match ty {
                 TimeType::AcceptTimeout => {
-                    sock.set_timeout(timeout).map_err(net_error_into_wasi_err)
+                    socket.set_timeout(timeout).map_err(net_error_into_wasi_err)
+                }
+                TimeType::ReadTimeout => {
+                    *recv_timeout = timeout;
+                    Ok(())
+                }
+                TimeType::WriteTimeout => {
+                    *send_timeout = timeout;
+                    Ok(())
+                }
+                TimeType::Linger => {
+                    *linger = timeout;
+                    Ok(())
                 }
                 _ => Err(Errno::Inval),
             },
@@ -607,12 +860,17 @@ impl InodeSocket {
                 send_timeout,
                 connect_timeout,
                 accept_timeout,
+                linger,
                 ..
             } => match ty {
                 TimeType::ConnectTimeout => {
                     *connect_timeout = timeout;
                     Ok(())
                 }
+                TimeType::Linger => {
+                    *linger = timeout;
+                    Ok(())
+                }
                 TimeType::AcceptTimeout => {
                     *accept_timeout = timeout;
                     Ok(())
@@ -625,7 +883,6 @@ impl InodeSocket {
                     *send_timeout = timeout;
                     Ok(())
                 }
-                _ => Err(Errno::Io),
             },
             InodeSocketKind::Closed => Err(Errno::Io),
             _ => Err(Errno::Notsup),
@@ -635,8 +892,16 @@ impl InodeSocket {
     pub fn opt_time(&self, ty: TimeType) -> Result<Option<std::time::Duration>, Errno> {
         match &self.kind {
             InodeSocketKind::TcpStream(sock) => sock.opt_time(ty).map_err(net_error_into_wasi_err),
-            InodeSocketKind::TcpListener(sock) => match ty {
-                TimeType::AcceptTimeout => sock.timeout().map_err(net_error_into_wasi_err),
+            InodeSocketKind::TcpListener {
+                socket,
+                send_timeout,
+                recv_timeout,
+                linger,
+            } => match ty {
+                TimeType::AcceptTimeout => socket.timeout().map_err(net_error_into_wasi_err),
+                TimeType::ReadTimeout => Ok(*recv_timeout),
+                TimeType::WriteTimeout => Ok(*send_timeout),
+                TimeType::Linger => Ok(*linger),
                 _ => Err(Errno::Inval),
             },
             InodeSocketKind::PreSocket {
@@ -644,13 +909,14 @@ impl InodeSocket {
                 send_timeout,
                 connect_timeout,
                 accept_timeout,
+                linger,
                 ..
             } => match ty {
                 TimeType::ConnectTimeout => Ok(*connect_timeout),
+                TimeType::Linger => Ok(*linger),
                 TimeType::AcceptTimeout => Ok(*accept_timeout),
                 TimeType::ReadTimeout => Ok(*recv_timeout),
                 TimeType::WriteTimeout => Ok(*send_timeout),
-                _ => Err(Errno::Inval),
             },
             InodeSocketKind::Closed => Err(Errno::Io),
             _ => Err(Errno::Notsup),
@@ -1116,16 +1382,7 @@ pub(crate) fn read_ip<M: MemorySize>(
 ) -> Result<IpAddr, Errno> {
     let addr_ptr = ptr.deref(memory);
     let addr = addr_ptr.read().map_err(crate::mem_error_to_wasi)?;
-
-    let o = addr.u.octs;
-    Ok(match addr.tag {
-        Addressfamily::Inet4 => IpAddr::V4(Ipv4Addr::new(o[0], o[1], o[2], o[3])),
-        Addressfamily::Inet6 => {
-            let [a, b, c, d, e, f, g, h] = unsafe { transmute::<_, [u16; 8]>(o) };
-            IpAddr::V6(Ipv6Addr::new(a, b, c, d, e, f, g, h))
-        }
-        _ => return Err(Errno::Inval),
-    })
+    crate::net::ip_from_wasi(&addr)
 }
 
 pub(crate) fn read_ip_v4<M: MemorySize>(
@@ -1155,27 +1412,10 @@ pub(crate) fn write_ip<M: MemorySize>(
     ptr: WasmPtr<__wasi_addr_t, M>,
     ip: IpAddr,
 ) -> Result<(), Errno> {
-    let ip = match ip {
-        IpAddr::V4(ip) => {
-            let o = ip.octets();
-            __wasi_addr_t {
-                tag: Addressfamily::Inet4,
-                u: __wasi_addr_u {
-                    octs: [o[0], o[1], o[2], o[3], 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-                },
-            }
-        }
-        IpAddr::V6(ip) => {
-            let o = ip.octets();
-            __wasi_addr_t {
-                tag: Addressfamily::Inet6,
-                u: __wasi_addr_u { octs: o },
-            }
-        }
-    };
-
     let addr_ptr = ptr.deref(memory);
-    addr_ptr.write(ip).map_err(crate::mem_error_to_wasi)?;
+    addr_ptr
+        .write(crate::net::ip_to_wasi(ip))
+        .map_err(crate::mem_error_to_wasi)?;
     Ok(())
 }
 
@@ -1216,35 +1456,10 @@ pub(crate) fn write_cidr<M: MemorySize>(
     ptr: WasmPtr<__wasi_cidr_t, M>,
     cidr: IpCidr,
 ) -> Result<(), Errno> {
-    let p = cidr.prefix;
-    let cidr = match cidr.ip {
-        IpAddr::V4(ip) => {
-            let o = ip.octets();
-            __wasi_cidr_t {
-                tag: Addressfamily::Inet4,
-                u: __wasi_cidr_u {
-                    octs: [
-                        o[0], o[1], o[2], o[3], p, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                    ],
-                },
-            }
-        }
-        IpAddr::V6(ip) => {
-            let o = ip.octets();
-            __wasi_cidr_t {
-                tag: Addressfamily::Inet6,
-                u: __wasi_cidr_u {
-                    octs: [
-                        o[0], o[1], o[2], o[3], o[4], o[5], o[6], o[7], o[8], o[9], o[10], o[11],
-                        o[12], o[13], o[14], o[15], p,
-                    ],
-                },
-            }
-        }
-    };
-
     let addr_ptr = ptr.deref(memory);
-    addr_ptr.write(cidr).map_err(crate::mem_error_to_wasi)?;
+    addr_ptr
+        .write(crate::net::cidr_to_wasi(cidr))
+        .map_err(crate::mem_error_to_wasi)?;
     Ok(())
 }
 
@@ -1254,28 +1469,8 @@ pub(crate) fn read_ip_port<M: MemorySize>(
 ) -> Result<(IpAddr, u16), Errno> {
     let addr_ptr = ptr.deref(memory);
     let addr = addr_ptr.read().map_err(crate::mem_error_to_wasi)?;
-
-    let o = addr.u.octs;
-    Ok(match addr.tag {
-        Addressfamily::Inet4 => {
-            let port = u16::from_ne_bytes([o[0], o[1]]);
-            (IpAddr::V4(Ipv4Addr::new(o[2], o[3], o[4], o[5])), port)
-        }
-        Addressfamily::Inet6 => {
-            let [a, b, c, d, e, f, g, h] = {
-                let o = [
-                    o[2], o[3], o[4], o[5], o[6], o[7], o[8], o[9], o[10], o[11], o[12], o[13],
-                    o[14], o[15], o[16], o[17],
-                ];
-                unsafe { transmute::<_, [u16; 8]>(o) }
-            };
-            (
-                IpAddr::V6(Ipv6Addr::new(a, b, c, d, e, f, g, h)),
-                u16::from_ne_bytes([o[0], o[1]]),
-            )
-        }
-        _ => return Err(Errno::Inval),
-    })
+    let addr = crate::net::socket_addr_from_wasi(&addr)?;
+    Ok((addr.ip(), addr.port()))
 }
 
 #[allow(dead_code)]
@@ -1285,35 +1480,10 @@ pub(crate) fn write_ip_port<M: MemorySize>(
     ip: IpAddr,
     port: u16,
 ) -> Result<(), Errno> {
-    let p = port.to_be_bytes();
-    let ipport = match ip {
-        IpAddr::V4(ip) => {
-            let o = ip.octets();
-            __wasi_addr_port_t {
-                tag: Addressfamily::Inet4,
-                u: __wasi_addr_port_u {
-                    octs: [
-                        p[0], p[1], o[0], o[1], o[2], o[3], 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-                    ],
-                },
-            }
-        }
-        IpAddr::V6(ip) => {
-            let o = ip.octets();
-            __wasi_addr_port_t {
-                tag: Addressfamily::Inet6,
-                u: __wasi_addr_port_u {
-                    octs: [
-                        p[0], p[1], o[0], o[1], o[2], o[3], o[4], o[5], o[6], o[7], o[8], o[9],
-                        o[10], o[11], o[12], o[13], o[14], o[15],
-                    ],
-                },
-            }
-        }
-    };
-
     let addr_ptr = ptr.deref(memory);
-    addr_ptr.write(ipport).map_err(crate::mem_error_to_wasi)?;
+    addr_ptr
+        .write(crate::net::socket_addr_to_wasi(SocketAddr::new(ip, port)))
+        .map_err(crate::mem_error_to_wasi)?;
     Ok(())
 }
 
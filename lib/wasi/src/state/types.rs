@@ -17,6 +17,7 @@ pub use wasmer_vfs::host_fs::{Stderr, Stdin, Stdout};
 #[cfg(feature = "mem-fs")]
 pub use wasmer_vfs::mem_fs::{Stderr, Stdin, Stdout};
 
+use crate::WasiRuntimeImplementation;
 use wasmer_vfs::{FsError, VirtualFile};
 use wasmer_vnet::NetworkError;
 
@@ -102,6 +103,16 @@ pub fn net_error_into_wasi_err(net_error: NetworkError) -> Errno {
     }
 }
 
+/// Converts the error of binding a socket to a local address, where a
+/// denied address (like a port outside of the range of a
+/// `PortRangeNetworking`) is reported as `EACCES` the way the host does
+pub(crate) fn bind_error_into_wasi_err(net_error: NetworkError) -> Errno {
+    match net_error {
+        NetworkError::PermissionDenied => Errno::Access,
+        net_error => net_error_into_wasi_err(net_error),
+    }
+}
+
 pub fn bus_error_into_wasi_err(bus_error: BusError) -> BusErrno {
     use BusError::*;
     match bus_error {
@@ -269,55 +280,108 @@ impl PollEventBuilder {
     }
 }
 
+/// Polls the files for the events. `streams` tells which of the files are
+/// streams (such as stdin), which only have something to read once bytes
+/// were buffered, unlike regular files which can always be read from.
 #[cfg(all(unix, feature = "sys-poll"))]
 pub(crate) fn poll(
     selfs: &[&(dyn VirtualFile + Send + Sync + 'static)],
     events: &[PollEventSet],
     seen_events: &mut [PollEventSet],
+    streams: &[bool],
     timeout: Duration,
 ) -> Result<u32, FsError> {
-    if !(selfs.len() == events.len() && events.len() == seen_events.len()) {
+    if !(selfs.len() == events.len()
+        && events.len() == seen_events.len()
+        && seen_events.len() == streams.len())
+    {
         return Err(FsError::InvalidInput);
     }
-    let mut fds = selfs
+    // Virtual files (such as a piped stdin) have no host descriptor to wait
+    // on, so their readiness is checked directly, while the ones that have
+    // a host descriptor are waited on by the host
+    let (host, virtual_files): (Vec<usize>, Vec<usize>) =
+        (0..selfs.len()).partition(|i| selfs[*i].get_fd().is_some());
+
+    let mut ret = 0;
+    if !virtual_files.is_empty() {
+        let files = virtual_files.iter().map(|i| selfs[*i]).collect::<Vec<_>>();
+        let file_events = virtual_files.iter().map(|i| events[*i]).collect::<Vec<_>>();
+        let file_streams = virtual_files
+            .iter()
+            .map(|i| streams[*i])
+            .collect::<Vec<_>>();
+        let mut file_seen_events = vec![0; files.len()];
+        ret = poll_virtual(
+            &files,
+            &file_events,
+            &mut file_seen_events,
+            &file_streams,
+            Duration::ZERO,
+        )?;
+        for (i, seen) in virtual_files.iter().zip(file_seen_events) {
+            seen_events[*i] = seen;
+        }
+    }
+    if host.is_empty() {
+        if ret == 0 && timeout > Duration::ZERO {
+            return Err(FsError::WouldBlock);
+        }
+        return Ok(ret);
+    }
+
+    // No need to wait on the host if a virtual file is ready already
+    let timeout = if ret > 0 { Duration::ZERO } else { timeout };
+    let mut fds = host
         .iter()
-        .enumerate()
-        .filter_map(|(i, s)| s.get_fd().map(|rfd| (i, rfd)))
+        .filter_map(|i| selfs[*i].get_fd().map(|rfd| (i, rfd)))
         .map(|(i, host_fd)| libc::pollfd {
             fd: host_fd.try_into().unwrap(),
-            events: poll_event_set_to_platform_poll_events(events[i]),
+            events: poll_event_set_to_platform_poll_events(events[*i]),
             revents: 0,
         })
         .collect::<Vec<_>>();
-    let result = unsafe {
-        libc::poll(
-            fds.as_mut_ptr(),
-            selfs.len() as _,
-            timeout.as_millis() as i32,
-        )
-    };
+    let result =
+        unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as _, timeout.as_millis() as i32) };
 
     if result < 0 {
         // TODO: check errno and return value
         return Err(FsError::IOError);
     }
     // convert result and write back values
-    for (i, fd) in fds.into_iter().enumerate() {
-        seen_events[i] = platform_poll_events_to_pollevent_set(fd.revents);
+    for (i, fd) in host.iter().zip(fds) {
+        seen_events[*i] = platform_poll_events_to_pollevent_set(fd.revents);
     }
     // unwrap is safe because we check for negative values above
-    Ok(result.try_into().unwrap())
+    let result: u32 = result.try_into().unwrap();
+    Ok(ret + result)
 }
 
+/// Polls the files for the events, see the other `poll`
 #[cfg(any(not(unix), not(feature = "sys-poll")))]
 pub(crate) fn poll(
     files: &[&(dyn VirtualFile + Send + Sync + 'static)],
     events: &[PollEventSet],
     seen_events: &mut [PollEventSet],
+    streams: &[bool],
+    timeout: Duration,
+) -> Result<u32, FsError> {
+    poll_virtual(files, events, seen_events, streams, timeout)
+}
+
+/// Polls the files by asking them how many bytes they have available
+fn poll_virtual(
+    files: &[&(dyn VirtualFile + Send + Sync + 'static)],
+    events: &[PollEventSet],
+    seen_events: &mut [PollEventSet],
+    streams: &[bool],
     timeout: Duration,
 ) -> Result<u32, FsError> {
-    if !(files.len() == events.len() && events.len() == seen_events.len()) {
-        tracing::debug!("the slice length of 'files', 'events' and 'seen_events' must be the same (files={}, events={}, seen_events={})", files.len(), events.len(), seen_events.len());
+    if !(files.len() == events.len()
+        && events.len() == seen_events.len()
+        && seen_events.len() == streams.len())
+    {
+        tracing::debug!("the slice length of 'files', 'events', 'seen_events' and 'streams' must be the same (files={}, events={}, seen_events={}, streams={})", files.len(), events.len(), seen_events.len(), streams.len());
         return Err(FsError::InvalidInput);
     }
 
@@ -326,7 +390,12 @@ pub(crate) fn poll(
         let mut builder = PollEventBuilder::new();
 
         let file = files[n];
-        let can_read = file.bytes_available_read()?.map(|_| true).unwrap_or(false);
+        // A stream that has nothing buffered isn't ready yet, while a regular
+        // file can be read from even at its end
+        let can_read = file
+            .bytes_available_read()?
+            .map(|s| s > 0 || !streams[n])
+            .unwrap_or(false);
         let can_write = file
             .bytes_available_write()?
             .map(|s| s > 0)
@@ -450,6 +519,89 @@ impl VirtualFile for Pipe {
     }
 }
 
+/// Standard output or error of the program that goes through the runtime
+/// (see [`WasiRuntimeImplementation::stdout`]), so that it ends up wherever
+/// the runtime sends it. These back the standard streams that weren't
+/// redirected when the environment is finalized.
+#[derive(Debug, Clone)]
+pub struct RuntimeOutput {
+    runtime: Arc<dyn WasiRuntimeImplementation + Send + Sync + 'static>,
+    stderr: bool,
+}
+
+impl RuntimeOutput {
+    pub fn stdout(runtime: Arc<dyn WasiRuntimeImplementation + Send + Sync + 'static>) -> Self {
+        Self {
+            runtime,
+            stderr: false,
+        }
+    }
+
+    pub fn stderr(runtime: Arc<dyn WasiRuntimeImplementation + Send + Sync + 'static>) -> Self {
+        Self {
+            runtime,
+            stderr: true,
+        }
+    }
+}
+
+impl Read for RuntimeOutput {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "can not read from an output stream",
+        ))
+    }
+}
+
+impl Write for RuntimeOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.stderr {
+            self.runtime.stderr(buf)?;
+        } else {
+            self.runtime.stdout(buf)?;
+        }
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.runtime.flush()
+    }
+}
+
+impl Seek for RuntimeOutput {
+    fn seek(&mut self, _pos: io::SeekFrom) -> io::Result<u64> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "can not seek an output stream",
+        ))
+    }
+}
+
+impl VirtualFile for RuntimeOutput {
+    fn last_accessed(&self) -> u64 {
+        0
+    }
+    fn last_modified(&self) -> u64 {
+        0
+    }
+    fn created_time(&self) -> u64 {
+        0
+    }
+    fn size(&self) -> u64 {
+        0
+    }
+    fn set_len(&mut self, _new_size: u64) -> Result<(), FsError> {
+        Err(FsError::PermissionDenied)
+    }
+    fn unlink(&mut self) -> Result<(), FsError> {
+        Ok(())
+    }
+    fn bytes_available_write(&self) -> Result<Option<usize>, FsError> {
+        // The runtime takes whatever is written to it
+        Ok(Some(crate::OUTPUT_BLOCK_SIZE))
+    }
+}
+
 /*
 TODO: Think about using this
 trait WasiFdBacking: std::fmt::Debug {
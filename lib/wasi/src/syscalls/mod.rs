@@ -45,7 +45,8 @@ use crate::{
         virtual_file_type_to_wasi_file_type, Inode, InodeSocket, InodeSocketKind, InodeVal, Kind,
         PollEvent, PollEventBuilder, WasiPipe, WasiState, MAX_SYMLINKS,
     },
-    Fd, WasiEnv, WasiError, WasiThread, WasiThreadId,
+    Fd, WasiEnv, WasiError, WasiThread, WasiThreadId, WasiTtyClear, WasiTtyMode,
+    THREAD_ABORTED_EXIT_CODE,
 };
 use bytes::Bytes;
 use std::borrow::{Borrow, Cow};
@@ -116,8 +117,10 @@ pub(crate) fn write_bytes<T: Write, M: MemorySize>(
     iovs_arr: WasmSlice<__wasi_ciovec_t<M>>,
 ) -> Result<usize, Errno> {
     let result = write_bytes_inner::<_, M>(&mut write_loc, memory, iovs_arr);
-    write_loc.flush();
-    result
+    // Buffered output may only find out the reader went away (`Errno::Pipe`)
+    // once it is flushed
+    let flushed = write_loc.flush().map_err(map_io_err);
+    result.and_then(|bytes_written| flushed.map(|_| bytes_written))
 }
 
 pub(crate) fn read_bytes<T: Read, M: MemorySize>(
@@ -617,7 +620,19 @@ pub fn fd_fdstat_get<M: MemorySize>(
     );
     let env = ctx.data();
     let (memory, mut state, inodes) = env.get_memory_and_wasi_state_and_inodes(&ctx, 0);
-    let stat = wasi_try!(state.fs.fdstat(inodes.deref(), fd));
+    let mut stat = wasi_try!(state.fs.fdstat(inodes.deref(), fd));
+
+    // The standard streams stay character devices, `isatty()` tells terminals
+    // apart by them not being able to tell their position
+    let is_tty = match fd {
+        __WASI_STDIN_FILENO => Some(env.tty_get().stdin_tty),
+        __WASI_STDOUT_FILENO => Some(env.tty_get().stdout_tty),
+        __WASI_STDERR_FILENO => Some(env.tty_get().stderr_tty),
+        _ => None,
+    };
+    if is_tty == Some(false) {
+        stat.fs_rights_base |= Rights::FD_TELL;
+    }
 
     let buf = buf_ptr.deref(&memory);
 
@@ -1133,6 +1148,15 @@ pub fn fd_read<M: MemorySize>(
                 env
             );
             if let Some(ref mut stdin) = guard.deref_mut() {
+                // Non-blocking reads fail rather than wait when there is
+                // nothing buffered yet, so the program can poll for input.
+                // Once the input is closed they read the end of it instead.
+                if fd_entry.flags.contains(Fdflags::NONBLOCK)
+                    && matches!(stdin.bytes_available_read(), Ok(Some(0)))
+                    && stdin.is_open()
+                {
+                    return Ok(Errno::Again);
+                }
                 wasi_try_ok!(read_bytes(stdin, &memory, iovs_arr), env)
             } else {
                 return Ok(Errno::Badf);
@@ -3092,6 +3116,7 @@ pub fn poll_oneoff<M: MemorySize>(
     let out_ptr = nevents.deref(&memory);
 
     let mut fd_guards = vec![];
+    let mut streams = vec![];
     let mut clock_subs = vec![];
     let mut in_events = vec![];
     let mut time_to_sleep = Duration::from_millis(5);
@@ -3200,6 +3225,10 @@ pub fn poll_oneoff<M: MemorySize>(
                 }
             };
             fd_guards.push(wasi_file_ref);
+            streams.push(matches!(
+                fd,
+                __WASI_STDIN_FILENO | __WASI_STDOUT_FILENO | __WASI_STDERR_FILENO
+            ));
         }
     }
 
@@ -3226,6 +3255,7 @@ pub fn poll_oneoff<M: MemorySize>(
             fds.as_slice(),
             in_events.as_slice(),
             seen_events.as_mut_slice(),
+            streams.as_slice(),
             Duration::from_millis(1),
         ) {
             Ok(0) => {
@@ -3324,6 +3354,9 @@ pub fn proc_exit(
     code: __wasi_exitcode_t,
 ) -> Result<(), WasiError> {
     debug!("wasi::proc_exit, {}", code);
+    // Hosts tend to exit along with the program, leaving no chance to do so
+    // afterwards
+    ctx.data().tty_restore();
     Err(WasiError::Exit(code))
 }
 
@@ -3338,8 +3371,31 @@ pub fn proc_raise(ctx: FunctionEnvMut<'_, WasiEnv>, sig: Signal) -> Errno {
     unimplemented!("wasi::proc_raise")
 }
 
+/// ### `proc_log()`
+/// Logs a message through the runtime, which records it along with the
+/// process and thread it came from instead of writing it to an output
+///
+/// ## Parameters
+///
+/// * `msg` - The line to log, it may start with the level it is logged at
+///   either as `[WARN] ...` or `WARN: ...`, otherwise it is logged at info
+///   level
+pub fn proc_log<M: MemorySize>(
+    ctx: FunctionEnvMut<'_, WasiEnv>,
+    msg: WasmPtr<u8, M>,
+    msg_len: M::Offset,
+) -> Errno {
+    trace!("wasi::proc_log");
+    let env = ctx.data();
+    let memory = env.memory_view(&ctx);
+    let msg = unsafe { get_input_str!(&memory, msg, msg_len) };
+    env.log(&msg);
+    Errno::Success
+}
+
 /// ### `sched_yield()`
-/// Yields execution of the thread
+/// Yields execution of the thread to the other threads that are ready to
+/// run, without going through the clocks like `thread_sleep()` does
 pub fn sched_yield(ctx: FunctionEnvMut<'_, WasiEnv>) -> Result<Errno, WasiError> {
     trace!("wasi::sched_yield");
     let env = ctx.data();
@@ -3384,7 +3440,7 @@ pub fn tty_get<M: MemorySize>(
     debug!("wasi::tty_stdin");
     let env = ctx.data();
 
-    let state = env.runtime.tty_get();
+    let state = env.tty_get();
     let state = Tty {
         cols: state.cols,
         rows: state.rows,
@@ -3426,11 +3482,131 @@ pub fn tty_set<M: MemorySize>(
         line_buffered: state.line_buffered,
     };
 
-    env.runtime.tty_set(state);
+    env.tty_set(state);
 
     Errno::Success
 }
 
+/// ### `tty_set_raw()`
+/// Switches the TTY into raw mode, where input is neither echoed nor
+/// gathered into lines, or back into the mode it was in before
+///
+/// ## Parameters
+///
+/// * `raw` - Whether to switch into raw mode
+pub fn tty_set_raw(ctx: FunctionEnvMut<'_, WasiEnv>, raw: Bool) -> Errno {
+    debug!("wasi::tty_set_raw");
+
+    let env = ctx.data();
+    match raw {
+        Bool::True => env.tty_raw_mode(),
+        Bool::False => {
+            // Cooked mode unless the state before raw mode is known
+            if env.state.tty_before_raw.lock().unwrap().is_none() {
+                env.runtime.tty_cooked_mode();
+            }
+            env.tty_restore();
+        }
+    }
+    Errno::Success
+}
+
+/// ### `tty_set_mode()`
+/// Turns a mode of the terminal on or off, the modes that are still on when
+/// the program exits are turned off. Nothing happens if the standard output
+/// is not a terminal.
+///
+/// ## Parameters
+///
+/// * `mode` - 0 for bracketed paste, 1 to report mouse clicks, 2 to report
+///   mouse drags, 3 to report all mouse movements and 4 for SGR mouse reports
+/// * `enabled` - Whether to turn the mode on
+pub fn tty_set_mode(ctx: FunctionEnvMut<'_, WasiEnv>, mode: u32, enabled: Bool) -> Errno {
+    debug!("wasi::tty_set_mode");
+
+    let mode = match mode {
+        0 => WasiTtyMode::BracketedPaste,
+        1 => WasiTtyMode::MouseClicks,
+        2 => WasiTtyMode::MouseDrags,
+        3 => WasiTtyMode::MouseMotion,
+        4 => WasiTtyMode::MouseSgr,
+        _ => return Errno::Inval,
+    };
+    let env = ctx.data();
+    wasi_try!(env
+        .tty_set_mode(mode, enabled == Bool::True)
+        .map_err(map_io_err));
+    wasi_try!(env.runtime.flush().map_err(map_io_err));
+    Errno::Success
+}
+
+/// ### `tty_clear()`
+/// Clears part of the terminal, nothing happens if the standard output is
+/// not a terminal
+///
+/// ## Parameters
+///
+/// * `what` - 0 clears the screen and moves the cursor to the top left
+///   corner, 1 clears the scrollback
+pub fn tty_clear(ctx: FunctionEnvMut<'_, WasiEnv>, what: u32) -> Errno {
+    debug!("wasi::tty_clear");
+
+    let clear = match what {
+        0 => WasiTtyClear::Screen,
+        1 => WasiTtyClear::Scrollback,
+        _ => return Errno::Inval,
+    };
+    let env = ctx.data();
+    wasi_try!(env.tty_clear(clear).map_err(map_io_err));
+    wasi_try!(env.runtime.flush().map_err(map_io_err));
+    Errno::Success
+}
+
+/// ### `tty_move_cursor()`
+/// Moves the cursor of the terminal, nothing happens if the standard output
+/// is not a terminal
+///
+/// ## Parameters
+///
+/// * `row` - Row to move to, counting from zero at the top
+/// * `col` - Column to move to, counting from zero on the left
+pub fn tty_move_cursor(ctx: FunctionEnvMut<'_, WasiEnv>, row: u32, col: u32) -> Errno {
+    debug!("wasi::tty_move_cursor");
+
+    let env = ctx.data();
+    wasi_try!(env.tty_move_cursor(row, col).map_err(map_io_err));
+    wasi_try!(env.runtime.flush().map_err(map_io_err));
+    Errno::Success
+}
+
+/// ### `stdio_flush()`
+/// Flushes whatever was written to the standard output and standard error
+/// through to the host, including the host's own buffers, so the output so
+/// far shows up before a long operation starts
+pub fn stdio_flush(ctx: FunctionEnvMut<'_, WasiEnv>) -> Errno {
+    debug!("wasi::stdio_flush");
+
+    let env = ctx.data();
+    let (_, state, inodes) = env.get_memory_and_wasi_state_and_inodes(&ctx, 0);
+    for fd in [__WASI_STDOUT_FILENO, __WASI_STDERR_FILENO] {
+        // Closed or replaced by something other than a file, there is
+        // nothing buffered to flush then
+        let guard = match fd {
+            __WASI_STDOUT_FILENO => inodes.stdout_mut(&state.fs.fd_map),
+            _ => inodes.stderr_mut(&state.fs.fd_map),
+        };
+        if let Ok(mut guard) = guard {
+            if let Some(file) = guard.deref_mut() {
+                wasi_try!(file.flush().map_err(map_io_err));
+            }
+        }
+    }
+    drop(inodes);
+
+    wasi_try!(env.runtime.flush().map_err(map_io_err));
+    Errno::Success
+}
+
 /// ### `getcwd()`
 /// Returns the current working directory
 /// If the path exceeds the size of the buffer then this function
@@ -3542,42 +3718,37 @@ pub fn thread_spawn<M: MemorySize>(
 
     // Create the sub-thread
     let mut sub_env = env.clone();
-    let mut sub_thread = env.new_thread();
+    let sub_thread = wasi_try!(env.new_thread().map_err(Errno::from));
     sub_env.id = sub_thread.id;
 
     let child = {
         let id = sub_thread.id;
+        let mut exit = ThreadExit {
+            env: sub_env,
+            thread: Some(sub_thread),
+            exit_code: None,
+        };
         wasi_try!(env
             .runtime
             .thread_spawn(Box::new(move || {
-                /*
-                if let Some(funct) = sub_env.thread_start_ref() {
-                    if let Err(err) = funct.call(user_data) {
+                let runtime = exit.env.runtime.clone();
+                let exit_code = match runtime.thread_run(exit.env.clone(), &method, user_data) {
+                    Ok(()) => 0,
+                    Err(WasiError::Exit(exit_code)) => exit_code,
+                    Err(err) => {
                         warn!("thread failed: {}", err);
-                        std::mem::forget(sub_thread);
-                        return;
+                        THREAD_ABORTED_EXIT_CODE
                     }
-                } else {
-                    warn!("failed to start thread: missing callback '__wasix_thread_start'");
-                    std::mem::forget(sub_thread);
-                    return;
-                }
-                */
-
-                let thread = {
-                    let mut guard = sub_env.state.threading.lock().unwrap();
-                    let thread = guard.threads.remove(&id);
-                    drop(guard);
-                    thread
                 };
-
-                if let Some(thread) = thread {
-                    let mut thread_guard = thread.exit.lock().unwrap();
-                    thread_guard.take();
-                }
-                drop(sub_thread);
+                exit.exit_code = Some(exit_code);
             }))
             .map_err(|err| {
+                // The thread never started, so it doesn't count against the
+                // limit of threads and has nothing to be joined on
+                let mut guard = env.state.threading.lock().unwrap();
+                guard.threads.remove(&id);
+                guard.exit_codes.remove(&id);
+                guard.detached.remove(&id);
                 let err: Errno = err.into();
                 err
             }));
@@ -3589,21 +3760,109 @@ pub fn thread_spawn<M: MemorySize>(
     Errno::Success
 }
 
+/// Marks a spawned thread as exited once the runtime is done with its task,
+/// be it because the task returned, unwound or was dropped without running
+struct ThreadExit {
+    env: WasiEnv,
+    thread: Option<WasiThread>,
+    /// Set when the task returned normally
+    exit_code: Option<__wasi_exitcode_t>,
+}
+
+impl Drop for ThreadExit {
+    fn drop(&mut self) {
+        let id = self.env.id;
+        let exit_code = self.exit_code.unwrap_or(THREAD_ABORTED_EXIT_CODE);
+
+        // This may run while unwinding, so a poisoned lock is not a reason
+        // to leave joiners waiting forever
+        let thread = {
+            let mut guard = self
+                .env
+                .state
+                .threading
+                .lock()
+                .unwrap_or_else(|err| err.into_inner());
+            // Nobody is going to join a detached thread
+            if !guard.detached.remove(&id) {
+                guard.exit_codes.insert(id, exit_code);
+            }
+            guard.threads.remove(&id)
+        };
+
+        if let Some(thread) = thread {
+            let mut thread_guard = thread.exit.lock().unwrap_or_else(|err| err.into_inner());
+            thread_guard.take();
+        }
+        self.thread.take();
+    }
+}
+
 /// ### `thread_sleep()`
 /// Sends the current thread to sleep for a period of time
 ///
 /// ## Parameters
 ///
 /// * `duration` - Amount of time that the thread should sleep
+///   (sleeping for zero nanoseconds is the same as `sched_yield()`)
+///
+/// A signal sent to the process cuts the sleep short with `Errno::Intr`.
 pub fn thread_sleep(
     ctx: FunctionEnvMut<'_, WasiEnv>,
     duration: Timestamp,
 ) -> Result<Errno, WasiError> {
     debug!("wasi::thread_sleep");
 
+    if duration == 0 {
+        return sched_yield(ctx);
+    }
+
+    // The sleep is done in slices so that signals can interrupt it
+    let env = ctx.data();
+    let mut remaining = Duration::from_nanos(duration as u64);
+    while !remaining.is_zero() {
+        if env.interrupted() {
+            return Ok(Errno::Intr);
+        }
+        let slice = remaining.min(Duration::from_millis(10));
+        env.sleep(slice)?;
+        remaining -= slice;
+    }
+    Ok(Errno::Success)
+}
+
+/// ### `thread_spin()`
+/// Busy-waits for a short period of time, which avoids the latency of going
+/// to sleep for tiny durations
+///
+/// ## Parameters
+///
+/// * `duration` - Amount of time that the thread should wait, whatever
+///   exceeds the spin limit of the runtime is slept like `thread_sleep()`
+///
+/// A signal sent to the process cuts the wait short with `Errno::Intr`.
+pub fn thread_spin(
+    ctx: FunctionEnvMut<'_, WasiEnv>,
+    duration: Timestamp,
+) -> Result<Errno, WasiError> {
+    debug!("wasi::thread_spin");
+
     let env = ctx.data();
-    let duration = Duration::from_nanos(duration as u64);
-    env.sleep(duration)?;
+    let spin = duration.min(env.runtime.thread_spin_limit().as_nanos() as Timestamp);
+    let start = platform_clock_time_get(Snapshot0Clockid::Monotonic, 1).unwrap() as Timestamp;
+    let mut elapsed = 0;
+    while elapsed < spin {
+        if env.interrupted() {
+            return Ok(Errno::Intr);
+        }
+        std::hint::spin_loop();
+        let now = platform_clock_time_get(Snapshot0Clockid::Monotonic, 1).unwrap() as Timestamp;
+        elapsed = now.saturating_sub(start);
+    }
+
+    if duration > elapsed {
+        return thread_sleep(ctx, duration - elapsed);
+    }
     Ok(Errno::Success)
 }
 
@@ -3630,9 +3889,57 @@ pub fn thread_id<M: MemorySize>(
 /// ## Parameters
 ///
 /// * `tid` - Handle of the thread to wait on
+///
+/// ## Return
+///
+/// Joining a thread that already finished succeeds straight away, while
+/// `Errno::Srch` is returned for threads that never existed
 pub fn thread_join(ctx: FunctionEnvMut<'_, WasiEnv>, tid: Tid) -> Result<Errno, WasiError> {
     debug!("wasi::thread_join");
 
+    let env = ctx.data();
+    let other_thread = {
+        let guard = env.state.threading.lock().unwrap();
+        // Threads are numbered in order, so an id that was handed out but
+        // isn't running any more belongs to a thread that already finished
+        if tid == 0 || tid > guard.thread_seed {
+            return Ok(Errno::Srch);
+        }
+        guard.threads.get(&WasiThreadId::from(tid)).cloned()
+    };
+    if let Some(other_thread) = other_thread {
+        loop {
+            if other_thread.join(Duration::from_millis(5)) {
+                break;
+            }
+            env.yield_now()?;
+        }
+    }
+    let tid: WasiThreadId = tid.into();
+    env.state.threading.lock().unwrap().exit_codes.remove(&tid);
+    Ok(Errno::Success)
+}
+
+/// ### `thread_join_v2()`
+/// Joins this thread with another thread, blocking this
+/// one until the other finishes, and returns its exit code
+///
+/// ## Parameters
+///
+/// * `tid` - Handle of the thread to wait on
+///
+/// ## Return
+///
+/// The exit code of the thread is written to `ret_exit_code`, threads that
+/// didn't return normally report `THREAD_ABORTED_EXIT_CODE`. Returns
+/// `Errno::Srch` when there is no such thread or it was already joined.
+pub fn thread_join_v2<M: MemorySize>(
+    ctx: FunctionEnvMut<'_, WasiEnv>,
+    tid: Tid,
+    ret_exit_code: WasmPtr<__wasi_exitcode_t, M>,
+) -> Result<Errno, WasiError> {
+    debug!("wasi::thread_join_v2");
+
     let env = ctx.data();
     let tid: WasiThreadId = tid.into();
     let other_thread = {
@@ -3646,10 +3953,44 @@ pub fn thread_join(ctx: FunctionEnvMut<'_, WasiEnv>, tid: Tid) -> Result<Errno,
             }
             env.yield_now()?;
         }
-        Ok(Errno::Success)
-    } else {
-        Ok(Errno::Success)
     }
+
+    let exit_code = env.state.threading.lock().unwrap().exit_codes.remove(&tid);
+    let exit_code = match exit_code {
+        Some(exit_code) => exit_code,
+        None => return Ok(Errno::Srch),
+    };
+    let memory = env.memory_view(&ctx);
+    wasi_try_mem_ok!(ret_exit_code.write(&memory, exit_code));
+    Ok(Errno::Success)
+}
+
+/// ### `thread_detach()`
+/// Detaches a thread, which can then no longer be joined and whose exit
+/// code is not kept once it exits
+///
+/// ## Parameters
+///
+/// * `tid` - Handle of the thread to detach
+///
+/// ## Return
+///
+/// Returns `Errno::Srch` when there is no such thread or it was already
+/// joined or detached
+pub fn thread_detach(ctx: FunctionEnvMut<'_, WasiEnv>, tid: Tid) -> Errno {
+    debug!("wasi::thread_detach");
+
+    let env = ctx.data();
+    let tid: WasiThreadId = tid.into();
+    let mut guard = env.state.threading.lock().unwrap();
+    // A thread that already exited only leaves its exit code behind
+    if guard.exit_codes.remove(&tid).is_some() {
+        return Errno::Success;
+    }
+    if !guard.threads.contains_key(&tid) || !guard.detached.insert(tid) {
+        return Errno::Srch;
+    }
+    Errno::Success
 }
 
 /// ### `thread_parallelism()`
@@ -3672,6 +4013,206 @@ pub fn thread_parallelism<M: MemorySize>(
     Errno::Success
 }
 
+/// ### `thread_affinity_set()`
+/// Restricts the current thread to a set of CPUs
+///
+/// ## Parameters
+///
+/// * `mask` - Mask of the CPUs the thread may run on, bit `n % 8` of
+///   byte `n / 8` stands for CPU `n`
+///
+/// Fails with `Errno::Inval` if the mask names no CPU, or a CPU this
+/// machine doesn't have, and with `Errno::Notsup` if the host can't pin
+/// threads.
+pub fn thread_affinity_set<M: MemorySize>(
+    ctx: FunctionEnvMut<'_, WasiEnv>,
+    mask: WasmPtr<u8, M>,
+    mask_len: M::Offset,
+) -> Errno {
+    debug!("wasi::thread_affinity_set");
+
+    let env = ctx.data();
+    let memory = env.memory_view(&ctx);
+    let mask = wasi_try_mem!(mask.slice(&memory, mask_len));
+    let mask = wasi_try_mem!(mask.read_to_vec());
+    wasi_try!(env.runtime().thread_set_affinity(&mask).map_err(|err| {
+        let err: Errno = err.into();
+        err
+    }));
+    Errno::Success
+}
+
+/// ### `thread_affinity_get()`
+/// Returns the set of CPUs the current thread may run on
+///
+/// ## Parameters
+///
+/// * `mask` - Buffer the mask of the CPUs is written to (see
+///   `thread_affinity_set()`)
+///
+/// ## Return
+///
+/// The length of the whole mask, the call fails with `Errno::Overflow` if
+/// the buffer is shorter than that.
+pub fn thread_affinity_get<M: MemorySize>(
+    ctx: FunctionEnvMut<'_, WasiEnv>,
+    mask: WasmPtr<u8, M>,
+    mask_len: M::Offset,
+    ret_mask_len: WasmPtr<M::Offset, M>,
+) -> Errno {
+    debug!("wasi::thread_affinity_get");
+
+    let env = ctx.data();
+    let affinity = wasi_try!(env.runtime().thread_affinity().map_err(|err| {
+        let err: Errno = err.into();
+        err
+    }));
+    let memory = env.memory_view(&ctx);
+    let affinity_len = wasi_try!(to_offset::<M>(affinity.len()));
+    wasi_try_mem!(ret_mask_len.write(&memory, affinity_len));
+    if affinity_len > mask_len {
+        return Errno::Overflow;
+    }
+    let mask = wasi_try_mem!(mask.slice(&memory, affinity_len));
+    wasi_try_mem!(mask.write_slice(&affinity));
+    Errno::Success
+}
+
+/// ### `futex_wait()`
+/// Blocks the current thread until the futex is woken up by another thread
+/// with `futex_wake()`, as long as the futex still holds the expected value
+///
+/// ## Parameters
+///
+/// * `futex` - Memory location that holds the value that will be checked
+/// * `expected` - Expected value that should be currently held at the memory location
+/// * `timeout` - Timeout should the futex not be woken up in time
+///
+/// ## Return
+///
+/// Whether the thread was woken up, the futex not holding the expected value
+/// or the timeout elapsing both return false. Waiters are only released by
+/// `futex_wake()` (or the timeout), so callers only ever see a spurious
+/// wakeup when another waiter consumed the wakeup they raced for.
+pub fn futex_wait<M: MemorySize>(
+    ctx: FunctionEnvMut<'_, WasiEnv>,
+    futex: WasmPtr<u32, M>,
+    expected: u32,
+    timeout: WasmPtr<OptionTimestamp, M>,
+    ret_woken: WasmPtr<Bool, M>,
+) -> Result<Errno, WasiError> {
+    debug!("wasi::futex_wait(offset={})", futex.offset());
+
+    let env = ctx.data();
+    let state = env.state.deref();
+    let memory = env.memory_view(&ctx);
+    let timeout = wasi_try_mem_ok!(timeout.read(&memory));
+    let timeout = match timeout.tag {
+        OptionTag::None => None,
+        OptionTag::Some => Some(timeout.u as u128),
+        _ => return Ok(Errno::Inval),
+    };
+    let addr: u64 = futex.offset().into();
+
+    // The value is checked while holding the lock, `futex_wake()` takes the
+    // same lock so a wakeup sent after the value changed can't be missed
+    let mut guard = state.threading.lock().unwrap();
+    let val = wasi_try_mem_ok!(futex.read(&memory));
+    if val != expected {
+        drop(guard);
+        wasi_try_mem_ok!(ret_woken.write(&memory, Bool::False));
+        return Ok(Errno::Success);
+    }
+    guard.futexes.entry(addr).or_default().waiters += 1;
+
+    let start = platform_clock_time_get(Snapshot0Clockid::Monotonic, 1_000_000).unwrap() as u128;
+    let woken = loop {
+        let entry = guard.futexes.get_mut(&addr).unwrap();
+        if entry.wakeups > 0 {
+            entry.wakeups -= 1;
+            break Ok(true);
+        }
+
+        let mut wait = Duration::from_millis(10);
+        if let Some(timeout) = timeout {
+            let now =
+                platform_clock_time_get(Snapshot0Clockid::Monotonic, 1_000_000).unwrap() as u128;
+            let elapsed = now.saturating_sub(start);
+            if elapsed >= timeout {
+                break Ok(false);
+            }
+            wait = wait.min(Duration::from_nanos((timeout - elapsed) as u64));
+        }
+        guard = state.futex_wakeups.wait_timeout(guard, wait).unwrap().0;
+
+        // Give the runtime a chance to interrupt the thread (e.g. when the
+        // process is exiting) without holding the lock
+        drop(guard);
+        let ret = env.yield_now();
+        guard = state.threading.lock().unwrap();
+        if let Err(err) = ret {
+            break Err(err);
+        }
+    };
+
+    let entry = guard.futexes.get_mut(&addr).unwrap();
+    entry.waiters -= 1;
+    entry.wakeups = entry.wakeups.min(entry.waiters);
+    if entry.waiters == 0 {
+        guard.futexes.remove(&addr);
+    }
+    drop(guard);
+
+    let woken = if woken? { Bool::True } else { Bool::False };
+    wasi_try_mem_ok!(ret_woken.write(&memory, woken));
+    Ok(Errno::Success)
+}
+
+/// ### `futex_wake()`
+/// Wakes up threads that are waiting on a futex with `futex_wait()`
+///
+/// ## Parameters
+///
+/// * `futex` - Memory location that holds a futex that others may be waiting on
+/// * `count` - Maximum number of waiting threads to wake up
+///
+/// ## Return
+///
+/// The number of threads that were woken up
+pub fn futex_wake<M: MemorySize>(
+    ctx: FunctionEnvMut<'_, WasiEnv>,
+    futex: WasmPtr<u32, M>,
+    count: u32,
+    ret_woken: WasmPtr<u32, M>,
+) -> Errno {
+    debug!("wasi::futex_wake(offset={})", futex.offset());
+
+    let env = ctx.data();
+    let state = env.state.deref();
+    let memory = env.memory_view(&ctx);
+    let addr: u64 = futex.offset().into();
+
+    let woken = {
+        let mut guard = state.threading.lock().unwrap();
+        match guard.futexes.get_mut(&addr) {
+            Some(entry) => {
+                // Waiters that were already woken up but didn't run yet
+                // can't be woken up a second time
+                let woken = count.min(entry.waiters - entry.wakeups);
+                entry.wakeups += woken;
+                woken
+            }
+            None => 0,
+        }
+    };
+    if woken > 0 {
+        state.futex_wakeups.notify_all();
+    }
+
+    wasi_try_mem!(ret_woken.write(&memory, woken));
+    Errno::Success
+}
+
 /// ### `getpid()`
 /// Returns the handle of the current process
 pub fn getpid<M: MemorySize>(ctx: FunctionEnvMut<'_, WasiEnv>, ret_pid: WasmPtr<Pid, M>) -> Errno {
@@ -3723,7 +4264,13 @@ pub fn thread_exit(
 ///
 /// ## Return
 ///
-/// Returns a bus process id that can be used to invoke calls
+/// Returns a bus process id that can be used to invoke calls, along with
+/// the descriptors of the stdio of the process:
+///
+/// * piped stdio is the descriptor of the pipe to the process
+/// * inherited stdio is the descriptor of this process the child uses
+///   (0, 1 or 2)
+/// * stdio that is closed, dropped or logged has no descriptor
 pub fn process_spawn<M: MemorySize>(
     ctx: FunctionEnvMut<'_, WasiEnv>,
     name: WasmPtr<u8, M>,
@@ -3761,10 +4308,14 @@ pub fn process_spawn<M: MemorySize>(
         WasiStdioMode::Piped => StdioMode::Piped,
         WasiStdioMode::Inherit => StdioMode::Inherit,
         WasiStdioMode::Log => StdioMode::Log,
-        /*__WASI_STDIO_MODE_NULL |*/ _ => StdioMode::Null,
+        WasiStdioMode::Null | WasiStdioMode::Reserved => StdioMode::Null,
     };
 
-    let process = wasi_try_bus!(bus
+    if !env.state.threading.lock().unwrap().reserve_process() {
+        return BusErrno::Alloc;
+    }
+
+    let process = bus
         .new_spawn()
         .chroot(chroot)
         .args(args)
@@ -3773,33 +4324,44 @@ pub fn process_spawn<M: MemorySize>(
         .stdout_mode(conv_stdio_mode(stdout))
         .stderr_mode(conv_stdio_mode(stderr))
         .working_dir(working_dir)
-        .spawn(name.as_str())
-        .map_err(bus_error_into_wasi_err));
-
-    let conv_stdio_fd = |a: Option<FileDescriptor>| match a {
-        Some(fd) => OptionFd {
-            tag: OptionTag::Some,
-            fd: fd.into(),
-        },
-        None => OptionFd {
-            tag: OptionTag::None,
-            fd: 0,
-        },
+        .spawn(name.as_str());
+    let process = match process {
+        Ok(process) => process,
+        Err(err) => {
+            env.state.threading.lock().unwrap().cancel_process();
+            return bus_error_into_wasi_err(err);
+        }
     };
 
+    let conv_stdio_fd =
+        |mode: WasiStdioMode, fd: Option<FileDescriptor>, inherited: WasiFd| match (mode, fd) {
+            (_, Some(fd)) => OptionFd {
+                tag: OptionTag::Some,
+                fd: fd.into(),
+            },
+            (WasiStdioMode::Inherit, None) => OptionFd {
+                tag: OptionTag::Some,
+                fd: inherited,
+            },
+            (_, None) => OptionFd {
+                tag: OptionTag::None,
+                fd: 0,
+            },
+        };
+
     // Convert the stdio
-    let stdin = conv_stdio_fd(process.inst.stdin_fd());
-    let stdout = conv_stdio_fd(process.inst.stdout_fd());
-    let stderr = conv_stdio_fd(process.inst.stderr_fd());
+    let stdin = conv_stdio_fd(stdin, process.inst.stdin_fd(), __WASI_STDIN_FILENO);
+    let stdout = conv_stdio_fd(stdout, process.inst.stdout_fd(), __WASI_STDOUT_FILENO);
+    let stderr = conv_stdio_fd(stderr, process.inst.stderr_fd(), __WASI_STDERR_FILENO);
 
     // Add the process to the environment state
-    let bid = {
-        let mut guard = env.state.threading.lock().unwrap();
-        guard.process_seed += 1;
-        let bid = guard.process_seed;
-        guard.processes.insert(bid.into(), process);
-        bid
-    };
+    let bid: Bid = env
+        .state
+        .threading
+        .lock()
+        .unwrap()
+        .insert_process(process)
+        .into();
 
     let handles = BusHandles {
         bid,
@@ -3906,6 +4468,10 @@ fn bus_open_local_internal<M: MemorySize>(
         }
     }
 
+    if !env.state.threading.lock().unwrap().reserve_process() {
+        return BusErrno::Alloc;
+    }
+
     let mut process = bus.new_spawn();
     process
         .reuse(reuse)
@@ -3921,16 +4487,18 @@ fn bus_open_local_internal<M: MemorySize>(
         process.access_token(token);
     }
 
-    let process = wasi_try_bus!(process
-        .spawn(name.as_ref())
-        .map_err(bus_error_into_wasi_err));
+    let process = match process.spawn(name.as_ref()) {
+        Ok(process) => process,
+        Err(err) => {
+            env.state.threading.lock().unwrap().cancel_process();
+            return bus_error_into_wasi_err(err);
+        }
+    };
 
     // Add the process to the environment state
     let bid = {
         let mut guard = env.state.threading.lock().unwrap();
-        guard.process_seed += 1;
-        let bid: WasiBusProcessId = guard.process_seed.into();
-        guard.processes.insert(bid, process);
+        let bid = guard.insert_process(process);
         guard.process_reuse.insert(name, bid);
         bid
     };
@@ -4487,6 +5055,44 @@ pub fn port_addr_list<M: MemorySize>(
     Errno::Success
 }
 
+/// ### `port_ifaddr_list()`
+/// Returns the network interfaces of the port with their addresses, with
+/// an entry for every address like `getifaddrs`
+///
+/// ## Parameters
+///
+/// * `ifaddrs` - The buffer where the entries will be stored
+/// * `nifaddrs` - Holds the number of entries the buffer has room for and
+///   is set to the number of entries there are
+pub fn port_ifaddr_list<M: MemorySize>(
+    ctx: FunctionEnvMut<'_, WasiEnv>,
+    ifaddrs: WasmPtr<__wasi_ifaddr_t, M>,
+    nifaddrs: WasmPtr<M::Offset, M>,
+) -> Errno {
+    debug!("wasi::port_ifaddr_list");
+    let env = ctx.data();
+    let memory = env.memory_view(&ctx);
+    let max_ifaddrs = wasi_try_mem!(nifaddrs.read(&memory));
+    let max_ifaddrs: u64 = wasi_try!(max_ifaddrs.try_into().map_err(|_| Errno::Overflow));
+    let ref_ifaddrs =
+        wasi_try_mem!(ifaddrs.slice(&memory, wasi_try!(to_offset::<M>(max_ifaddrs as usize))));
+
+    let interfaces = wasi_try!(env.net().interfaces().map_err(net_error_into_wasi_err));
+    let entries = crate::net::interfaces_to_wasi(&interfaces);
+
+    let entries_len: M::Offset = wasi_try!(entries.len().try_into().map_err(|_| Errno::Overflow));
+    wasi_try_mem!(nifaddrs.write(&memory, entries_len));
+    if entries.len() as u64 > max_ifaddrs {
+        return Errno::Overflow;
+    }
+
+    for (n, entry) in entries.into_iter().enumerate() {
+        wasi_try_mem!(ref_ifaddrs.index(n as u64).write(entry));
+    }
+
+    Errno::Success
+}
+
 /// ### `port_gateway_set()`
 /// Adds a default gateway to the port
 ///
@@ -4738,9 +5344,12 @@ pub fn sock_addr_peer<M: MemorySize>(
 /// ## Parameters
 ///
 /// * `af` - Address family
-/// * `socktype` - Socket type, either datagram or stream
+/// * `socktype` - Socket type, either datagram, stream or raw
 /// * `sock_proto` - Socket protocol
 ///
+/// Raw sockets (ICMP, raw IP or Ethernet) fail with `Errno::Perm` unless the
+/// runtime allows the program to open them.
+///
 /// ## Return
 ///
 /// The file descriptor of the socket that has been opened.
@@ -4756,25 +5365,34 @@ pub fn sock_open<M: MemorySize>(
     let env = ctx.data();
     let (memory, state, mut inodes) = env.get_memory_and_wasi_state_and_inodes_mut(&ctx, 0);
 
-    let kind = match ty {
-        Socktype::Stream | Socktype::Dgram => Kind::Socket {
-            socket: InodeSocket::new(InodeSocketKind::PreSocket {
-                family: af,
-                ty,
-                pt,
-                addr: None,
-                only_v6: false,
-                reuse_port: false,
-                reuse_addr: false,
-                send_buf_size: None,
-                recv_buf_size: None,
-                send_timeout: None,
-                recv_timeout: None,
-                connect_timeout: None,
-                accept_timeout: None,
-            }),
+    match ty {
+        Socktype::Stream | Socktype::Dgram => {}
+        // Raw sockets can send any packet, only trusted programs may open them
+        Socktype::Raw if !env.raw_sockets_allowed() => return Errno::Perm,
+        Socktype::Raw => match pt {
+            SockProto::Icmp | SockProto::Icmpv6 | SockProto::ProtoRaw | SockProto::Ethernet => {}
+            _ => return Errno::Notsup,
         },
         _ => return Errno::Notsup,
+    }
+
+    let kind = Kind::Socket {
+        socket: InodeSocket::new(InodeSocketKind::PreSocket {
+            family: af,
+            ty,
+            pt,
+            addr: None,
+            only_v6: false,
+            reuse_port: false,
+            reuse_addr: false,
+            send_buf_size: None,
+            recv_buf_size: None,
+            send_timeout: None,
+            recv_timeout: None,
+            connect_timeout: None,
+            accept_timeout: None,
+            linger: None,
+        }),
     };
 
     let inode = state.fs.create_inode_with_default_stat(
@@ -4941,6 +5559,33 @@ pub fn sock_get_opt_time<M: MemorySize>(
     Errno::Success
 }
 
+/// ### `sock_get_tcp_stats()`
+/// Retrieves the statistics of a connected TCP socket as tracked by the
+/// host, like the round trip time and the number of retransmits
+/// Note: This is similar to `getsockopt` in POSIX for TCP_INFO
+///
+/// ## Parameters
+///
+/// * `fd` - Socket descriptor
+/// * `ret_stats` - Where the statistics are written, the `fields` bitmask
+///   tells which of them the host provided
+pub fn sock_get_tcp_stats<M: MemorySize>(
+    ctx: FunctionEnvMut<'_, WasiEnv>,
+    sock: WasiFd,
+    ret_stats: WasmPtr<__wasi_tcp_stats_t, M>,
+) -> Errno {
+    debug!("wasi::sock_get_tcp_stats");
+    let env = ctx.data();
+    let memory = env.memory_view(&ctx);
+
+    let stats = wasi_try!(__sock_actor(&ctx, sock, Rights::empty(), |socket| {
+        socket.tcp_stats()
+    }));
+    wasi_try_mem!(ret_stats.write(&memory, crate::net::tcp_stats_to_wasi(&stats)));
+
+    Errno::Success
+}
+
 /// ### `sock_set_opt_size()
 /// Set size of particular option for this socket
 /// Note: This is similar to `setsockopt` in POSIX for SO_RCVBUF
@@ -4958,16 +5603,6 @@ pub fn sock_set_opt_size(
 ) -> Errno {
     debug!("wasi::sock_set_opt_size(ty={})", opt);
 
-    let ty = match opt {
-        Sockoption::RecvTimeout => wasmer_vnet::TimeType::ReadTimeout,
-        Sockoption::SendTimeout => wasmer_vnet::TimeType::WriteTimeout,
-        Sockoption::ConnectTimeout => wasmer_vnet::TimeType::ConnectTimeout,
-        Sockoption::AcceptTimeout => wasmer_vnet::TimeType::AcceptTimeout,
-        Sockoption::Linger => wasmer_vnet::TimeType::Linger,
-        _ => return Errno::Inval,
-    };
-
-    let option: super::state::WasiSocketOption = opt.into();
     wasi_try!(__sock_actor_mut(&ctx, sock, Rights::empty(), |socket| {
         match opt {
             Sockoption::RecvBufSize => socket.set_recv_buf_size(size as usize),
@@ -5175,6 +5810,14 @@ pub fn sock_listen<M: MemorySize>(
 /// * `fd` - The listening socket.
 /// * `flags` - The desired values of the file descriptor flags.
 ///
+/// The new connection is non-blocking when either `flags` asks for it or
+/// the listening socket is non-blocking, and it starts with the read and
+/// write timeouts of the listening socket.
+///
+/// Waits for at most the accept timeout of the socket, or the default one
+/// of the environment if the socket has none, before failing with
+/// `Errno::Timedout`. A zero timeout waits forever.
+///
 /// ## Return
 ///
 /// New socket connection
@@ -5188,6 +5831,21 @@ pub fn sock_accept<M: MemorySize>(
     debug!("wasi::sock_accept");
 
     let env = ctx.data();
+    let timeout = wasi_try_ok!(__sock_actor(&ctx, sock, Rights::SOCK_ACCEPT, |socket| {
+        socket.opt_time(wasmer_vnet::TimeType::AcceptTimeout)
+    }))
+    .or(env.default_accept_timeout())
+    .filter(|timeout| !timeout.is_zero());
+    let start = platform_clock_time_get(Snapshot0Clockid::Monotonic, 1_000_000).unwrap() as u128;
+    let timed_out = || match timeout {
+        Some(timeout) => {
+            let now =
+                platform_clock_time_get(Snapshot0Clockid::Monotonic, 1_000_000).unwrap() as u128;
+            now.saturating_sub(start) >= timeout.as_nanos()
+        }
+        None => false,
+    };
+
     let (child, addr) = {
         let mut ret;
         let (_, state) = env.get_memory_and_wasi_state(&ctx, 0);
@@ -5200,6 +5858,9 @@ pub fn sock_accept<M: MemorySize>(
                         ret = a;
                         break;
                     }
+                    Err(Errno::Timedout) | Err(Errno::Again) if timed_out() => {
+                        Err(Errno::Timedout)
+                    }
                     Err(Errno::Timedout) => {
                         env.yield_now()?;
                         continue;
@@ -5217,6 +5878,14 @@ pub fn sock_accept<M: MemorySize>(
 
     let (memory, state, mut inodes) = env.get_memory_and_wasi_state_and_inodes_mut(&ctx, 0);
 
+    let listener_nonblocking = wasi_try_ok!(state.fs.get_fd(sock))
+        .flags
+        .contains(Fdflags::NONBLOCK);
+    let mut new_flags = Fdflags::empty();
+    if fd_flags.contains(Fdflags::NONBLOCK) || listener_nonblocking {
+        new_flags.insert(Fdflags::NONBLOCK);
+    }
+
     let kind = Kind::Socket {
         socket: InodeSocket::new(InodeSocketKind::TcpStream(child)),
     };
@@ -5228,9 +5897,7 @@ pub fn sock_accept<M: MemorySize>(
     );
 
     let rights = Rights::all_socket();
-    let fd = wasi_try_ok!(state
-        .fs
-        .create_fd(rights, rights, Fdflags::empty(), 0, inode));
+    let fd = wasi_try_ok!(state.fs.create_fd(rights, rights, new_flags, 0, inode));
 
     wasi_try_mem_ok!(ro_fd.write(&memory, fd));
     wasi_try_ok!(super::state::write_ip_port(
@@ -5272,6 +5939,37 @@ pub fn sock_connect<M: MemorySize>(
     Errno::Success
 }
 
+/// ### `sock_connect_tls()`
+/// Connects a stream socket like `sock_connect()` and secures the
+/// connection with TLS. The handshake and encryption are done by the host,
+/// the program sends and receives plaintext.
+///
+/// ## Parameters
+///
+/// * `fd` - Socket descriptor
+/// * `addr` - Address of the socket to connect to
+/// * `server_name` - Name the certificate of the peer is verified against,
+///   which is also sent to the peer (SNI)
+pub fn sock_connect_tls<M: MemorySize>(
+    ctx: FunctionEnvMut<'_, WasiEnv>,
+    sock: WasiFd,
+    addr: WasmPtr<__wasi_addr_port_t, M>,
+    server_name: WasmPtr<u8, M>,
+    server_name_len: M::Offset,
+) -> Errno {
+    debug!("wasi::sock_connect_tls");
+
+    let env = ctx.data();
+    let memory = env.memory_view(&ctx);
+    let addr = wasi_try!(super::state::read_ip_port(&memory, addr));
+    let addr = SocketAddr::new(addr.0, addr.1);
+    let server_name = unsafe { get_input_str!(&memory, server_name, server_name_len) };
+    wasi_try!(__sock_upgrade(&ctx, sock, Rights::SOCK_CONNECT, |socket| {
+        socket.connect_tls(env.net(), addr, server_name.as_str())
+    }));
+    Errno::Success
+}
+
 /// ### `sock_recv()`
 /// Receive a message from a socket.
 /// Note: This is similar to `recv` in POSIX, though it also supports reading
@@ -5285,6 +5983,10 @@ pub fn sock_connect<M: MemorySize>(
 /// ## Return
 ///
 /// Number of bytes stored in ri_data and message flags.
+///
+/// A blocking receive that has nothing to return yet fails with
+/// `Errno::Intr` when a signal is sent to the process, bytes that were
+/// already received are returned instead.
 pub fn sock_recv<M: MemorySize>(
     ctx: FunctionEnvMut<'_, WasiEnv>,
     sock: WasiFd,
@@ -5300,9 +6002,76 @@ pub fn sock_recv<M: MemorySize>(
     let memory = env.memory_view(&ctx);
     let iovs_arr = wasi_try_mem_ok!(ri_data.slice(&memory, ri_data_len));
 
-    let bytes_read = wasi_try_ok!(__sock_actor_mut(&ctx, sock, Rights::SOCK_RECV, |socket| {
-        socket.recv(&memory, iovs_arr)
-    }));
+    // Blocking receives are polled in slices of 5ms, by lowering the read
+    // timeout of the socket to the slice, so that a signal sent in the
+    // meantime can interrupt them. The read timeout the program set still
+    // applies and is put back afterwards. Sockets that have no read timeout
+    // option receive without slices.
+    let nonblocking = wasi_try_ok!(env.state.fs.get_fd(sock))
+        .flags
+        .contains(Fdflags::NONBLOCK);
+    let timeout = if nonblocking {
+        None
+    } else {
+        __sock_actor(&ctx, sock, Rights::SOCK_RECV, |socket| {
+            socket.opt_time(wasmer_vnet::TimeType::ReadTimeout)
+        })
+        .ok()
+    };
+
+    let bytes_read = match timeout {
+        None => wasi_try_ok!(__sock_actor_mut(&ctx, sock, Rights::SOCK_RECV, |socket| {
+            socket.recv(&memory, iovs_arr)
+        })),
+        Some(timeout) => {
+            let restore_timeout = || {
+                __sock_actor_mut(&ctx, sock, Rights::SOCK_RECV, |socket| {
+                    socket.set_opt_time(wasmer_vnet::TimeType::ReadTimeout, timeout)
+                })
+            };
+            let start =
+                platform_clock_time_get(Snapshot0Clockid::Monotonic, 1_000_000).unwrap() as u128;
+            // The read timeout only changes for the first slice and the last
+            // one, which may be shorter
+            let mut current_slice = None;
+            let ret = loop {
+                let mut slice = Duration::from_millis(5);
+                if let Some(timeout) = timeout {
+                    let now = platform_clock_time_get(Snapshot0Clockid::Monotonic, 1_000_000)
+                        .unwrap() as u128;
+                    let elapsed = now.saturating_sub(start);
+                    if elapsed >= timeout.as_nanos() {
+                        break Err(Errno::Again);
+                    }
+                    slice = slice.min(timeout - Duration::from_nanos(elapsed as u64));
+                }
+
+                let ret = __sock_actor_mut(&ctx, sock, Rights::SOCK_RECV, |socket| {
+                    if current_slice != Some(slice) {
+                        socket.set_opt_time(wasmer_vnet::TimeType::ReadTimeout, Some(slice))?;
+                        current_slice = Some(slice);
+                    }
+                    socket.recv(&memory, iovs_arr)
+                });
+                match ret {
+                    Err(Errno::Again) | Err(Errno::Timedout) => {
+                        if env.interrupted() {
+                            break Err(Errno::Intr);
+                        }
+                        if let Err(err) = env.yield_now() {
+                            let _ = restore_timeout();
+                            return Err(err);
+                        }
+                    }
+                    ret => break ret,
+                }
+            };
+            let restored = restore_timeout();
+            let bytes_read = wasi_try_ok!(ret);
+            wasi_try_ok!(restored);
+            bytes_read
+        }
+    };
     let bytes_read: M::Offset = wasi_try_ok!(bytes_read.try_into().map_err(|_| Errno::Overflow));
 
     wasi_try_mem_ok!(ro_flags.write(&memory, 0));
@@ -26,6 +26,9 @@ pub fn platform_clock_res_get(
         };
         (clock_getres(unix_clock_id, &mut timespec_out), timespec_out)
     };
+    if output != 0 {
+        return Err(Errno::Inval);
+    }
 
     let t_out = (timespec_out.tv_sec * 1_000_000_000).wrapping_add(timespec_out.tv_nsec);
     Ok(t_out)
@@ -53,6 +56,9 @@ pub fn platform_clock_time_get(
             timespec_out,
         )
     };
+    if output != 0 {
+        return Err(Errno::Inval);
+    }
 
     let t_out = (timespec_out.tv_sec * 1_000_000_000).wrapping_add(timespec_out.tv_nsec);
     Ok(t_out)
@@ -396,6 +396,14 @@ pub(crate) fn proc_raise(ctx: FunctionEnvMut<WasiEnv>, sig: Signal) -> Errno {
     super::proc_raise(ctx, sig)
 }
 
+pub(crate) fn proc_log(
+    ctx: FunctionEnvMut<WasiEnv>,
+    msg: WasmPtr<u8, MemoryType>,
+    msg_len: MemoryOffset,
+) -> Errno {
+    super::proc_log::<MemoryType>(ctx, msg, msg_len)
+}
+
 pub(crate) fn random_get(
     ctx: FunctionEnvMut<WasiEnv>,
     buf: WasmPtr<u8, MemoryType>,
@@ -437,6 +445,26 @@ pub(crate) fn tty_set(ctx: FunctionEnvMut<WasiEnv>, tty_state: WasmPtr<Tty, Memo
     super::tty_set::<MemoryType>(ctx, tty_state)
 }
 
+pub(crate) fn tty_set_raw(ctx: FunctionEnvMut<WasiEnv>, raw: Bool) -> Errno {
+    super::tty_set_raw(ctx, raw)
+}
+
+pub(crate) fn tty_set_mode(ctx: FunctionEnvMut<WasiEnv>, mode: u32, enabled: Bool) -> Errno {
+    super::tty_set_mode(ctx, mode, enabled)
+}
+
+pub(crate) fn tty_clear(ctx: FunctionEnvMut<WasiEnv>, what: u32) -> Errno {
+    super::tty_clear(ctx, what)
+}
+
+pub(crate) fn tty_move_cursor(ctx: FunctionEnvMut<WasiEnv>, row: u32, col: u32) -> Errno {
+    super::tty_move_cursor(ctx, row, col)
+}
+
+pub(crate) fn stdio_flush(ctx: FunctionEnvMut<WasiEnv>) -> Errno {
+    super::stdio_flush(ctx)
+}
+
 pub(crate) fn getcwd(
     ctx: FunctionEnvMut<WasiEnv>,
     path: WasmPtr<u8, MemoryType>,
@@ -471,6 +499,13 @@ pub(crate) fn thread_sleep(
     super::thread_sleep(ctx, duration)
 }
 
+pub(crate) fn thread_spin(
+    ctx: FunctionEnvMut<WasiEnv>,
+    duration: Timestamp,
+) -> Result<Errno, WasiError> {
+    super::thread_spin(ctx, duration)
+}
+
 pub(crate) fn thread_id(ctx: FunctionEnvMut<WasiEnv>, ret_tid: WasmPtr<Tid, MemoryType>) -> Errno {
     super::thread_id::<MemoryType>(ctx, ret_tid)
 }
@@ -479,6 +514,18 @@ pub(crate) fn thread_join(ctx: FunctionEnvMut<WasiEnv>, tid: Tid) -> Result<Errn
     super::thread_join(ctx, tid)
 }
 
+pub(crate) fn thread_join_v2(
+    ctx: FunctionEnvMut<WasiEnv>,
+    tid: Tid,
+    ret_exit_code: WasmPtr<__wasi_exitcode_t, MemoryType>,
+) -> Result<Errno, WasiError> {
+    super::thread_join_v2::<MemoryType>(ctx, tid, ret_exit_code)
+}
+
+pub(crate) fn thread_detach(ctx: FunctionEnvMut<WasiEnv>, tid: Tid) -> Errno {
+    super::thread_detach(ctx, tid)
+}
+
 pub(crate) fn thread_parallelism(
     ctx: FunctionEnvMut<WasiEnv>,
     ret_parallelism: WasmPtr<MemoryOffset, MemoryType>,
@@ -486,6 +533,42 @@ pub(crate) fn thread_parallelism(
     super::thread_parallelism::<MemoryType>(ctx, ret_parallelism)
 }
 
+pub(crate) fn thread_affinity_set(
+    ctx: FunctionEnvMut<WasiEnv>,
+    mask: WasmPtr<u8, MemoryType>,
+    mask_len: MemoryOffset,
+) -> Errno {
+    super::thread_affinity_set::<MemoryType>(ctx, mask, mask_len)
+}
+
+pub(crate) fn thread_affinity_get(
+    ctx: FunctionEnvMut<WasiEnv>,
+    mask: WasmPtr<u8, MemoryType>,
+    mask_len: MemoryOffset,
+    ret_mask_len: WasmPtr<MemoryOffset, MemoryType>,
+) -> Errno {
+    super::thread_affinity_get::<MemoryType>(ctx, mask, mask_len, ret_mask_len)
+}
+
+pub(crate) fn futex_wait(
+    ctx: FunctionEnvMut<WasiEnv>,
+    futex: WasmPtr<u32, MemoryType>,
+    expected: u32,
+    timeout: WasmPtr<OptionTimestamp, MemoryType>,
+    ret_woken: WasmPtr<Bool, MemoryType>,
+) -> Result<Errno, WasiError> {
+    super::futex_wait::<MemoryType>(ctx, futex, expected, timeout, ret_woken)
+}
+
+pub(crate) fn futex_wake(
+    ctx: FunctionEnvMut<WasiEnv>,
+    futex: WasmPtr<u32, MemoryType>,
+    count: u32,
+    ret_woken: WasmPtr<u32, MemoryType>,
+) -> Errno {
+    super::futex_wake::<MemoryType>(ctx, futex, count, ret_woken)
+}
+
 pub(crate) fn thread_exit(
     ctx: FunctionEnvMut<WasiEnv>,
     exitcode: __wasi_exitcode_t,
@@ -688,6 +771,14 @@ pub(crate) fn port_addr_list(
     super::port_addr_list::<MemoryType>(ctx, addrs, naddrs)
 }
 
+pub(crate) fn port_ifaddr_list(
+    ctx: FunctionEnvMut<WasiEnv>,
+    ifaddrs: WasmPtr<__wasi_ifaddr_t, MemoryType>,
+    nifaddrs: WasmPtr<MemoryOffset, MemoryType>,
+) -> Errno {
+    super::port_ifaddr_list::<MemoryType>(ctx, ifaddrs, nifaddrs)
+}
+
 pub(crate) fn port_mac(
     ctx: FunctionEnvMut<WasiEnv>,
     ret_mac: WasmPtr<__wasi_hardwareaddress_t, MemoryType>,
@@ -846,6 +937,14 @@ pub fn sock_get_opt_time(
     super::sock_get_opt_time(ctx, sock, opt, ret_time)
 }
 
+pub fn sock_get_tcp_stats(
+    ctx: FunctionEnvMut<WasiEnv>,
+    sock: Fd,
+    ret_stats: WasmPtr<__wasi_tcp_stats_t, MemoryType>,
+) -> Errno {
+    super::sock_get_tcp_stats::<MemoryType>(ctx, sock, ret_stats)
+}
+
 pub fn sock_set_opt_size(
     ctx: FunctionEnvMut<WasiEnv>,
     sock: Fd,
@@ -930,6 +1029,16 @@ pub(crate) fn sock_connect(
     super::sock_connect::<MemoryType>(ctx, sock, addr)
 }
 
+pub(crate) fn sock_connect_tls(
+    ctx: FunctionEnvMut<WasiEnv>,
+    sock: Fd,
+    addr: WasmPtr<__wasi_addr_port_t, MemoryType>,
+    server_name: WasmPtr<u8, MemoryType>,
+    server_name_len: MemoryOffset,
+) -> Errno {
+    super::sock_connect_tls::<MemoryType>(ctx, sock, addr, server_name, server_name_len)
+}
+
 pub(crate) fn sock_recv(
     ctx: FunctionEnvMut<WasiEnv>,
     sock: Fd,
@@ -1,15 +1,32 @@
 use crate::syscalls::types::wasi::{self, Timestamp};
 use tracing::debug;
 use wasmer::WasmRef;
+use winapi::shared::ntdef::LARGE_INTEGER;
+use winapi::um::profileapi::{QueryPerformanceCounter, QueryPerformanceFrequency};
+
+/// Ticks per second of the performance counter that backs the monotonic clock
+fn performance_frequency() -> Result<u64, wasi::Errno> {
+    let mut frequency: LARGE_INTEGER = unsafe { std::mem::zeroed() };
+    if unsafe { QueryPerformanceFrequency(&mut frequency) } == 0 {
+        return Err(wasi::Errno::Io);
+    }
+    match unsafe { *frequency.QuadPart() } {
+        f if f > 0 => Ok(f as u64),
+        _ => Err(wasi::Errno::Io),
+    }
+}
 
 pub fn platform_clock_res_get(
     clock_id: wasi::Snapshot0Clockid,
     resolution: WasmRef<Timestamp>,
 ) -> Result<i64, wasi::Errno> {
     let resolution_val = match clock_id {
-        // resolution of monotonic clock at 10ms, from:
-        // https://docs.microsoft.com/en-us/windows/desktop/api/sysinfoapi/nf-sysinfoapi-gettickcount64
-        wasi::Snapshot0Clockid::Monotonic => 10_000_000,
+        // one tick of the performance counter, which is 100ns or finer on
+        // every supported version of Windows
+        wasi::Snapshot0Clockid::Monotonic => {
+            let frequency = performance_frequency()?;
+            ((1_000_000_000 + frequency - 1) / frequency) as i64
+        }
         // TODO: verify or compute this
         wasi::Snapshot0Clockid::Realtime => 1,
         wasi::Snapshot0Clockid::ProcessCputimeId => {
@@ -29,8 +46,13 @@ pub fn platform_clock_time_get(
 ) -> Result<i64, wasi::Errno> {
     let nanos = match clock_id {
         wasi::Snapshot0Clockid::Monotonic => {
-            let tick_ms = unsafe { winapi::um::sysinfoapi::GetTickCount64() };
-            tick_ms * 1_000_000
+            let frequency = performance_frequency()?;
+            let mut counter: LARGE_INTEGER = unsafe { std::mem::zeroed() };
+            if unsafe { QueryPerformanceCounter(&mut counter) } == 0 {
+                return Err(wasi::Errno::Io);
+            }
+            let counter = unsafe { *counter.QuadPart() } as u128;
+            (counter * 1_000_000_000 / frequency as u128) as u64
         }
         wasi::Snapshot0Clockid::Realtime => {
             let duration = std::time::SystemTime::now()
@@ -1,7 +1,7 @@
 use std::collections::BTreeSet;
 #[cfg(not(feature = "js"))]
 use wasmer::vm::VMSharedMemory;
-use wasmer::{AsStoreMut, Imports, Memory, Module};
+use wasmer::{AsStoreMut, Function, Imports, Memory, Module, RuntimeError};
 use wasmer_wasi_types::wasi::Errno;
 
 #[allow(dead_code)]
@@ -88,6 +88,29 @@ pub fn wasi_import_shared_memory(
 ) {
 }
 
+/// Defines every WASI function the module imports but `imports` doesn't
+/// provide with a stub that traps, naming the function, when it is called.
+///
+/// This lets a module that needs functions the runtime doesn't implement
+/// still be instantiated, so it runs until it actually calls one of them.
+pub fn wasi_stub_missing_imports(
+    imports: &mut Imports,
+    module: &Module,
+    store: &mut impl AsStoreMut,
+) {
+    for import in module.imports().functions() {
+        let (ns, name) = (import.module(), import.name());
+        if !is_wasi_namespace(ns) || imports.exists(ns, name) {
+            continue;
+        }
+        let message = format!("WASI function `{}::{}` is not implemented", ns, name);
+        let stub = Function::new(store, import.ty().clone(), move |_| {
+            Err(RuntimeError::new(message.clone()))
+        });
+        imports.define(ns, name, stub);
+    }
+}
+
 /// The version of WASI. This is determined by the imports namespace
 /// string.
 #[derive(Debug, Clone, Copy, Eq)]
@@ -186,6 +209,13 @@ const WASIX_32V1_NAMESPACE: &str = "wasix_32v1";
 /// Namespace for the `wasix` version.
 const WASIX_64V1_NAMESPACE: &str = "wasix_64v1";
 
+fn is_wasi_namespace(ns: &str) -> bool {
+    matches!(
+        ns,
+        SNAPSHOT0_NAMESPACE | SNAPSHOT1_NAMESPACE | WASIX_32V1_NAMESPACE | WASIX_64V1_NAMESPACE
+    )
+}
+
 /// Detect the version of WASI being used based on the import
 /// namespaces.
 ///
@@ -257,6 +287,115 @@ pub fn get_wasi_versions(module: &Module, strict: bool) -> Option<BTreeSet<WasiV
     }
 }
 
+/// Detects imports from a WASI snapshot this runtime knows of but doesn't
+/// implement, and returns the name of that snapshot.
+///
+/// Such modules would otherwise fail to instantiate with an unresolved
+/// import, this allows reporting what they actually target instead.
+pub fn get_unsupported_wasi_version(module: &Module) -> Option<&'static str> {
+    module.imports().find_map(|import| {
+        let ns = import.module();
+        // Component model interfaces are named like `wasi:cli/environment@0.2.0`
+        if ns.starts_with("wasi:") || ns == "wasi_snapshot_preview2" {
+            Some("WASI preview2")
+        } else {
+            None
+        }
+    })
+}
+
+/// Optional capabilities a module can import on top of the core WASI
+/// functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WasiCapability {
+    /// Spawning and joining threads, or sharing its memory with them
+    Threads,
+    /// Sockets and the configuration of the network ports
+    Sockets,
+    /// HTTP requests and web sockets made by the host
+    Http,
+    /// Calls to other processes over the bus
+    Bus,
+    /// Spawning other processes
+    Processes,
+    /// Reading and changing the state of the TTY
+    Tty,
+}
+
+impl WasiCapability {
+    /// Returns the capability a WASIX function import belongs to, if it is
+    /// an optional one.
+    fn of_import(name: &str) -> Option<Self> {
+        let capability = match name {
+            "thread_spawn"
+            | "thread_sleep"
+            | "thread_spin"
+            | "thread_id"
+            | "thread_join"
+            | "thread_join_v2"
+            | "thread_detach"
+            | "thread_parallelism"
+            | "thread_exit"
+            | "thread_affinity_set"
+            | "thread_affinity_get"
+            | "futex_wait"
+            | "futex_wake" => Self::Threads,
+            "http_request" | "http_status" | "ws_connect" => Self::Http,
+            "process_spawn" => Self::Processes,
+            "tty_get" | "tty_set" | "tty_set_raw" | "tty_set_mode" | "tty_clear"
+            | "tty_move_cursor" => Self::Tty,
+            "resolve" => Self::Sockets,
+            _ if name.starts_with("sock_") || name.starts_with("port_") => Self::Sockets,
+            _ if name.starts_with("bus_") || name.starts_with("call_") => Self::Bus,
+            _ => return None,
+        };
+        Some(capability)
+    }
+}
+
+/// Which WASI version a module targets and which optional capabilities it
+/// uses, as returned by [`module_wasi_profile`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WasiProfile {
+    /// The most recent WASI version the module imports functions from, or
+    /// `None` if it isn't a WASI module.
+    pub version: Option<WasiVersion>,
+    /// All the WASI versions the module imports functions from.
+    pub versions: BTreeSet<WasiVersion>,
+    /// The optional capabilities the module imports.
+    pub capabilities: BTreeSet<WasiCapability>,
+}
+
+/// Inspects the imports of a module to find out which WASI version it
+/// targets and which optional capabilities (threads, sockets, ...) it needs,
+/// without instantiating it.
+pub fn module_wasi_profile(module: &Module) -> WasiProfile {
+    let mut profile = WasiProfile::default();
+    for import in module.imports().functions() {
+        let version = match import.module() {
+            SNAPSHOT0_NAMESPACE => WasiVersion::Snapshot0,
+            SNAPSHOT1_NAMESPACE => WasiVersion::Snapshot1,
+            WASIX_32V1_NAMESPACE => WasiVersion::Wasix32v1,
+            WASIX_64V1_NAMESPACE => WasiVersion::Wasix64v1,
+            _ => continue,
+        };
+        profile.versions.insert(version);
+        if matches!(version, WasiVersion::Wasix32v1 | WasiVersion::Wasix64v1) {
+            if let Some(capability) = WasiCapability::of_import(import.name()) {
+                profile.capabilities.insert(capability);
+            }
+        }
+    }
+
+    // Threads share the memory of the module
+    if module.imports().memories().any(|m| m.ty().shared) {
+        profile.capabilities.insert(WasiCapability::Threads);
+    }
+
+    profile.version = profile.versions.iter().next_back().copied();
+    profile
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -325,4 +464,63 @@ mod test {
         assert!(WasiVersion::Wasix32v1 < WasiVersion::Wasix64v1);
         assert!(WasiVersion::Wasix64v1 > WasiVersion::Wasix32v1);
     }
+
+    #[test]
+    fn wasi_profile_of_preview1_module() {
+        let store = wasmer::Store::default();
+        let module = Module::new(
+            &store,
+            br#"(module
+                (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32)))
+                (import "wasi_snapshot_preview1" "proc_exit" (func (param i32))))"#,
+        )
+        .unwrap();
+
+        let profile = module_wasi_profile(&module);
+        assert_eq!(profile.version, Some(WasiVersion::Snapshot1));
+        assert!(profile.capabilities.is_empty());
+    }
+
+    #[test]
+    fn wasi_profile_of_wasix_module_with_sockets() {
+        let store = wasmer::Store::default();
+        let module = Module::new(
+            &store,
+            br#"(module
+                (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32)))
+                (import "wasix_32v1" "sock_open" (func (param i32 i32 i32 i32) (result i32)))
+                (import "wasix_32v1" "sock_connect" (func (param i32 i32) (result i32))))"#,
+        )
+        .unwrap();
+
+        let profile = module_wasi_profile(&module);
+        assert_eq!(profile.version, Some(WasiVersion::Wasix32v1));
+        assert_eq!(
+            profile.versions.into_iter().collect::<Vec<_>>(),
+            vec![WasiVersion::Snapshot1, WasiVersion::Wasix32v1]
+        );
+        assert_eq!(
+            profile.capabilities.into_iter().collect::<Vec<_>>(),
+            vec![WasiCapability::Sockets]
+        );
+    }
+
+    #[test]
+    fn wasi_profile_of_wasix_module_with_threads() {
+        let store = wasmer::Store::default();
+        let module = Module::new(
+            &store,
+            br#"(module
+                (import "wasix_64v1" "thread_spawn" (func (param i64 i64 i32 i64) (result i32)))
+                (import "env" "memory" (memory 1 1 shared)))"#,
+        )
+        .unwrap();
+
+        let profile = module_wasi_profile(&module);
+        assert_eq!(profile.version, Some(WasiVersion::Wasix64v1));
+        assert_eq!(
+            profile.capabilities.into_iter().collect::<Vec<_>>(),
+            vec![WasiCapability::Threads]
+        );
+    }
 }
@@ -0,0 +1,63 @@
+#![cfg(feature = "wasix")]
+
+use std::convert::TryInto;
+
+use wasmer::{Memory, Store, Value};
+use wasmer_wasi::types::wasi::Snapshot0Clockid;
+use wasmer_wasi::WasiState;
+
+mod common;
+use common::{call_ok, instantiate};
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "clock_res_get" (func $clock_res_get (param i32 i32) (result i32)))
+    (import "wasix_32v1" "clock_time_get" (func $clock_time_get (param i32 i64 i32) (result i32)))
+    (memory (export "memory") 1)
+    ;; The resolution is written at offset 0
+    (func (export "res") (param $clock i32) (result i32)
+        (call $clock_res_get (local.get $clock) (i32.const 0))
+    )
+    ;; Two successive readings are written at offsets 8 and 16
+    (func (export "time") (param $clock i32) (result i32)
+        (local $ret i32)
+        (local.set $ret (call $clock_time_get (local.get $clock) (i64.const 1) (i32.const 8)))
+        (if (i32.ne (local.get $ret) (i32.const 0))
+            (then (return (local.get $ret)))
+        )
+        (call $clock_time_get (local.get $clock) (i64.const 1) (i32.const 16))
+    )
+)
+"#;
+
+fn read_u64(store: &Store, memory: &Memory, offset: u64) -> u64 {
+    let mut buf = [0u8; 8];
+    memory.view(store).read(offset, &mut buf).unwrap();
+    u64::from_le_bytes(buf[..].try_into().unwrap())
+}
+
+#[test]
+fn test_monotonic_clock() {
+    let mut store = Store::default();
+    let (instance, memory, _) =
+        instantiate(&mut store, MODULE, &mut WasiState::new("command-name"));
+    let monotonic = Value::I32(Snapshot0Clockid::Monotonic as i32);
+
+    call_ok(&mut store, &instance, "res", &[monotonic.clone()]);
+    let resolution = read_u64(&store, &memory, 0);
+    assert!(resolution > 0);
+    // Linux counts the monotonic clock in nanoseconds, and the performance
+    // counter on Windows ticks every 100ns or faster
+    #[cfg(any(target_os = "linux", windows))]
+    assert!(resolution < 1_000, "resolution of {}ns", resolution);
+
+    let mut last = 0;
+    for _ in 0..100 {
+        call_ok(&mut store, &instance, "time", &[monotonic.clone()]);
+        let first = read_u64(&store, &memory, 8);
+        let second = read_u64(&store, &memory, 16);
+        assert!(last <= first, "{} then {}", last, first);
+        assert!(first <= second, "{} then {}", first, second);
+        last = second;
+    }
+}
@@ -0,0 +1,64 @@
+//! Helpers shared by the tests that instantiate a module with a WASI
+//! environment and call its exports
+#![allow(dead_code)]
+
+use wasmer::{Instance, Memory, Module, Store, Value};
+use wasmer_wasi::types::wasi::{Addressfamily, Errno};
+use wasmer_wasi::{WasiFunctionEnv, WasiStateBuilder};
+
+/// Instantiates `wat` with the environment `builder` creates, and returns
+/// the instance along with its memory and environment
+pub fn instantiate(
+    store: &mut Store,
+    wat: &[u8],
+    builder: &mut WasiStateBuilder,
+) -> (Instance, Memory, WasiFunctionEnv) {
+    let wasi_env = builder.finalize(store).unwrap();
+    let (instance, memory) = instantiate_with_env(store, wat, &wasi_env, None);
+    (instance, memory, wasi_env)
+}
+
+/// Instantiates `wat` with an environment that already exists, e.g. the one
+/// of the thread it runs on. The module either exports its memory or
+/// imports `memory` as `env.memory`
+pub fn instantiate_with_env(
+    store: &mut Store,
+    wat: &[u8],
+    wasi_env: &WasiFunctionEnv,
+    memory: Option<Memory>,
+) -> (Instance, Memory) {
+    let module = Module::new(&*store, wat).unwrap();
+    let mut import_object = wasi_env.import_object(store, &module).unwrap();
+    if let Some(memory) = &memory {
+        import_object.define("env", "memory", memory.clone());
+    }
+    let instance = Instance::new(store, &module, &import_object).unwrap();
+    let memory = match memory {
+        Some(memory) => memory,
+        None => instance.exports.get_memory("memory").unwrap().clone(),
+    };
+    wasi_env.data_mut(store).set_memory(memory.clone());
+    (instance, memory)
+}
+
+/// Calls the export `name` and returns its result
+pub fn call(store: &mut Store, instance: &Instance, name: &str, params: &[Value]) -> Value {
+    let func = instance.exports.get_function(name).unwrap();
+    func.call(store, params).unwrap()[0].clone()
+}
+
+/// Calls the export `name`, which has to succeed
+pub fn call_ok(store: &mut Store, instance: &Instance, name: &str, params: &[Value]) {
+    let ret = call(store, instance, name, params);
+    assert_eq!(ret, Value::I32(Errno::Success as i32), "{}", name);
+}
+
+/// Writes the address of `127.0.0.1:port` at `offset`, laid out the way
+/// the socket functions read it
+pub fn write_localhost_addr(store: &Store, memory: &Memory, offset: u64, port: u16) {
+    let mut addr = [0u8; 19];
+    addr[0] = Addressfamily::Inet4 as u8;
+    addr[1..3].copy_from_slice(&port.to_be_bytes());
+    addr[3..7].copy_from_slice(&[127, 0, 0, 1]);
+    memory.view(store).write(offset, &addr).unwrap();
+}
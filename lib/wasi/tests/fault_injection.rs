@@ -0,0 +1,85 @@
+use std::convert::TryInto;
+use std::io::{Read, Write};
+
+use wasmer::Store;
+use wasmer_wasi::types::wasi::Errno;
+use wasmer_wasi::{Pipe, WasiFaults, WasiState};
+
+mod common;
+use common::instantiate;
+
+mod sys {
+    #[test]
+    fn test_fault_injection() {
+        super::test_fault_injection()
+    }
+}
+
+#[cfg(feature = "js")]
+mod js {
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_fault_injection() {
+        super::test_fault_injection()
+    }
+}
+
+fn test_fault_injection() {
+    const MODULE: &[u8] = br#"
+    (module
+        (import "wasi_snapshot_preview1" "fd_read" (func $fd_read (param i32 i32 i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (func $main (export "_start")
+            ;; iov pointing at a 4 bytes buffer at offset 16
+            (i32.store (i32.const 0) (i32.const 16))
+            (i32.store (i32.const 4) (i32.const 4))
+
+            ;; Echo stdin to stdout 4 bytes at a time, keeping the error
+            ;; codes of the writes at offsets 32, 36 and 40
+            (drop (call $fd_read (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 8)))
+            (i32.store (i32.const 32) (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))
+            (drop (call $fd_read (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 8)))
+            (i32.store (i32.const 36) (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))
+            (i32.store (i32.const 40) (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))
+        )
+    )
+    "#;
+
+    let mut store = Store::default();
+    let mut stdin = Pipe::new();
+    stdin.write_all(b"abcdefgh").unwrap();
+    let mut stdout = Pipe::new();
+    let (instance, memory, wasi_env) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name")
+            .stdin(Box::new(stdin))
+            .stdout(Box::new(stdout.clone())),
+    );
+    let faults = WasiFaults::default();
+    faults.fail_nth("fd_write", 2, Errno::Nospc);
+    wasi_env.data_mut(&mut store).set_faults(faults);
+
+    let start = instance.exports.get_function("_start").unwrap();
+    start.call(&mut store, &[]).unwrap();
+
+    // Only the second write failed, and it did so without writing anything
+    let mut errnos = [0u8; 12];
+    memory.view(&store).read(32, &mut errnos).unwrap();
+    let errnos = [&errnos[0..4], &errnos[4..8], &errnos[8..12]]
+        .map(|errno| u32::from_le_bytes(errno.try_into().unwrap()));
+    assert_eq!(
+        errnos,
+        [
+            Errno::Success as u32,
+            Errno::Nospc as u32,
+            Errno::Success as u32
+        ]
+    );
+
+    let mut output = String::new();
+    stdout.read_to_string(&mut output).unwrap();
+    assert_eq!(output, "abcdefgh");
+}
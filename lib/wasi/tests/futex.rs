@@ -0,0 +1,145 @@
+#![cfg(feature = "wasix")]
+
+use std::time::{Duration, Instant};
+
+use wasmer::vm::VMMemory;
+use wasmer::{Instance, Memory, MemoryType, Store, Value};
+use wasmer_wasi::types::wasi::Errno;
+use wasmer_wasi::{WasiEnv, WasiFunctionEnv, WasiState};
+
+mod common;
+use common::instantiate_with_env;
+
+/// Mutex built on the futex syscalls, the lock lives at address 0 and the
+/// counter it protects at address 8
+const MUTEX_WAT: &[u8] = br#"
+(module
+    (import "wasix_32v1" "futex_wait" (func $futex_wait (param i32 i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "futex_wake" (func $futex_wake (param i32 i32 i32) (result i32)))
+    (import "env" "memory" (memory 1 1 shared))
+
+    (func $lock
+        (loop $retry
+            (if (i32.eqz (i32.atomic.rmw.cmpxchg (i32.const 0) (i32.const 0) (i32.const 1)))
+                (then (return)))
+            ;; No timeout (the option at 16 is left zeroed)
+            (drop (call $futex_wait (i32.const 0) (i32.const 1) (i32.const 16) (i32.const 48)))
+            (br $retry)
+        )
+    )
+
+    (func $unlock
+        (i32.atomic.store (i32.const 0) (i32.const 0))
+        (drop (call $futex_wake (i32.const 0) (i32.const 1) (i32.const 52)))
+    )
+
+    ;; Increments the counter without atomics, only the mutex keeps the
+    ;; two threads from losing updates
+    (func (export "run") (param $n i32)
+        (loop $next
+            (call $lock)
+            (i32.store (i32.const 8) (i32.add (i32.load (i32.const 8)) (i32.const 1)))
+            (call $unlock)
+            (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+            (br_if $next (local.get $n))
+        )
+    )
+
+    ;; Waits on the lock for at most `timeout` nanoseconds
+    (func (export "wait") (param $expected i32) (param $timeout i64) (result i32)
+        (i32.store8 (i32.const 32) (i32.const 1))
+        (i64.store (i32.const 40) (local.get $timeout))
+        (call $futex_wait (i32.const 0) (local.get $expected) (i32.const 32) (i32.const 48))
+    )
+
+    (func (export "woken") (result i32)
+        (i32.load8_u (i32.const 48))
+    )
+)
+"#;
+
+/// Instantiates the mutex module on a store of its own, sharing `memory`
+fn instantiate(store: &mut Store, env: WasiEnv, memory: VMMemory) -> Instance {
+    let memory = Memory::new_from_existing(store, memory);
+    let wasi_env = WasiFunctionEnv::new(store, env);
+    instantiate_with_env(store, MUTEX_WAT, &wasi_env, Some(memory)).0
+}
+
+/// Creates the shared memory and the WASI environment both threads use
+fn setup(store: &mut Store) -> (WasiEnv, Memory) {
+    let wasi_env = WasiState::new("command-name").finalize(store).unwrap();
+    let env = wasi_env.env.as_ref(store).clone();
+    let memory = Memory::new(store, MemoryType::new(1, Some(1), true)).unwrap();
+    (env, memory)
+}
+
+#[test]
+fn test_futex_mutex() {
+    const ITERATIONS: i32 = 10_000;
+
+    let mut store = Store::default();
+    let (env, memory) = setup(&mut store);
+    let own = memory.try_clone(&store).unwrap();
+    let instance = instantiate(&mut store, env.clone(), own);
+
+    let shared = memory.try_clone(&store).unwrap();
+    let other = std::thread::spawn(move || {
+        let mut store = Store::default();
+        let instance = instantiate(&mut store, env, shared);
+        let run = instance.exports.get_function("run").unwrap();
+        run.call(&mut store, &[Value::I32(ITERATIONS)]).unwrap();
+    });
+
+    let run = instance.exports.get_function("run").unwrap();
+    run.call(&mut store, &[Value::I32(ITERATIONS)]).unwrap();
+    other.join().unwrap();
+
+    // No increment got lost and the mutex was left unlocked
+    let view = memory.view(&store);
+    let mut counter = [0u8; 4];
+    view.read(8, &mut counter).unwrap();
+    assert_eq!(i32::from_le_bytes(counter), 2 * ITERATIONS);
+    let mut lock = [0u8; 4];
+    view.read(0, &mut lock).unwrap();
+    assert_eq!(i32::from_le_bytes(lock), 0);
+}
+
+#[test]
+fn test_futex_wait_returns_without_wakeup() {
+    let mut store = Store::default();
+    let (env, memory) = setup(&mut store);
+    let own = memory.try_clone(&store).unwrap();
+    let instance = instantiate(&mut store, env, own);
+
+    let wait = instance.exports.get_function("wait").unwrap();
+    let woken = instance.exports.get_function("woken").unwrap();
+    let success = Value::I32(Errno::Success as i32);
+
+    // The futex doesn't hold the expected value, no need to wait at all
+    let start = Instant::now();
+    let ret = wait
+        .call(
+            &mut store,
+            &[
+                Value::I32(1),
+                Value::I64(Duration::from_secs(60).as_nanos() as i64),
+            ],
+        )
+        .unwrap();
+    assert_eq!(ret[0], success);
+    assert_eq!(woken.call(&mut store, &[]).unwrap()[0], Value::I32(0));
+    assert!(start.elapsed() < Duration::from_secs(10));
+
+    // Nobody wakes the futex up, so the wait ends with the timeout
+    let timeout = Duration::from_millis(50);
+    let start = Instant::now();
+    let ret = wait
+        .call(
+            &mut store,
+            &[Value::I32(0), Value::I64(timeout.as_nanos() as i64)],
+        )
+        .unwrap();
+    assert_eq!(ret[0], success);
+    assert_eq!(woken.call(&mut store, &[]).unwrap()[0], Value::I32(0));
+    assert!(start.elapsed() >= timeout);
+}
@@ -0,0 +1,91 @@
+#![cfg(feature = "wasix")]
+
+use wasmer::{Instance, Store, Value};
+use wasmer_wasi::types::wasi::{Filetype, Rights};
+use wasmer_wasi::{
+    Pipe, PluggableRuntimeImplementation, WasiRuntimeImplementation, WasiState, WasiTtyState,
+};
+
+mod common;
+use common::{call, call_ok, instantiate};
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "fd_fdstat_get" (func $fd_fdstat_get (param i32 i32) (result i32)))
+    (memory (export "memory") 1)
+    ;; The fdstat is written at offset 0
+    (func (export "fdstat") (param $fd i32) (result i32)
+        (call $fd_fdstat_get (local.get $fd) (i32.const 0))
+    )
+    (func (export "filetype") (result i32)
+        (i32.load8_u (i32.const 0))
+    )
+    (func (export "rights") (result i64)
+        (i64.load (i32.const 8))
+    )
+)
+"#;
+
+/// Does what `isatty()` does in the program: a terminal is a character
+/// device that can't seek or tell its position
+fn isatty(store: &mut Store, instance: &Instance, fd: i32) -> bool {
+    call_ok(store, instance, "fdstat", &[Value::I32(fd)]);
+    // The standard streams are character devices either way
+    assert_eq!(
+        call(store, instance, "filetype", &[]),
+        Value::I32(Filetype::CharacterDevice as i32)
+    );
+    let rights = call(store, instance, "rights", &[]).unwrap_i64() as u64;
+    rights & (Rights::FD_SEEK | Rights::FD_TELL).bits() == 0
+}
+
+#[test]
+fn test_isatty_follows_redirections() {
+    // All of the streams are terminals on the host
+    let runtime = PluggableRuntimeImplementation::default();
+    runtime.tty_set(WasiTtyState {
+        stdin_tty: true,
+        stdout_tty: true,
+        stderr_tty: true,
+        ..WasiTtyState::default()
+    });
+
+    let mut store = Store::default();
+    let (instance, _, wasi_env) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name")
+            .stdout(Box::new(Pipe::new()))
+            .runtime(runtime),
+    );
+
+    assert!(isatty(&mut store, &instance, 0));
+    assert!(!isatty(&mut store, &instance, 1));
+    assert!(isatty(&mut store, &instance, 2));
+
+    // The TTY state of the program agrees
+    let env = wasi_env.env.as_ref(&store);
+    let tty = env.tty_get();
+    assert!(tty.stdin_tty);
+    assert!(!tty.stdout_tty);
+    assert!(tty.stderr_tty);
+    // while the runtime, which other programs may share, is left alone
+    assert!(env.runtime().tty_get().stdout_tty);
+}
+
+#[test]
+fn test_isatty_without_terminal() {
+    let runtime = PluggableRuntimeImplementation::default();
+    runtime.tty_set(WasiTtyState::default());
+
+    let mut store = Store::default();
+    let (instance, _, _) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name").runtime(runtime),
+    );
+
+    for fd in 0..3 {
+        assert!(!isatty(&mut store, &instance, fd));
+    }
+}
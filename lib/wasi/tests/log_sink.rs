@@ -0,0 +1,108 @@
+use std::sync::{Arc, Mutex};
+
+use tracing::Level;
+use wasmer::Store;
+use wasmer_wasi::{
+    PluggableRuntimeImplementation, VirtualBus, VirtualNetworking, WasiLogRecord,
+    WasiRuntimeImplementation, WasiState, WasiThreadId,
+};
+
+mod common;
+#[cfg(feature = "wasix")]
+use common::{call_ok, instantiate};
+
+/// Runtime of a process with a known ID that logs to the given runtime
+#[derive(Debug)]
+struct Process {
+    pid: u32,
+    inner: PluggableRuntimeImplementation,
+}
+
+impl WasiRuntimeImplementation for Process {
+    fn bus(&self) -> &(dyn VirtualBus) {
+        self.inner.bus()
+    }
+
+    fn networking(&self) -> &(dyn VirtualNetworking) {
+        self.inner.networking()
+    }
+
+    fn thread_generate_id(&self) -> WasiThreadId {
+        self.inner.thread_generate_id()
+    }
+
+    fn getpid(&self) -> Option<u32> {
+        Some(self.pid)
+    }
+
+    fn log(&self, record: &WasiLogRecord) {
+        self.inner.log(record)
+    }
+}
+
+#[test]
+fn test_log_reaches_sink_with_pid() {
+    let logged = Arc::new(Mutex::new(Vec::new()));
+    let mut inner = PluggableRuntimeImplementation::default();
+    inner.set_log_sink({
+        let logged = logged.clone();
+        move |record: &WasiLogRecord| logged.lock().unwrap().push(record.clone())
+    });
+
+    let mut store = Store::default();
+    let wasi_env = WasiState::new("command-name")
+        .runtime(Process { pid: 42, inner })
+        .finalize(&mut store)
+        .unwrap();
+    wasi_env.env.as_ref(&store).log("WARN: cache is cold");
+
+    assert_eq!(
+        *logged.lock().unwrap(),
+        vec![WasiLogRecord {
+            pid: Some(42),
+            tid: 0u32.into(),
+            level: Level::WARN,
+            text: "cache is cold".to_string(),
+        }]
+    );
+}
+
+#[cfg(feature = "wasix")]
+#[test]
+fn test_proc_log_reaches_sink() {
+    const MODULE: &[u8] = br#"
+    (module
+        (import "wasix_32v1" "proc_log" (func $proc_log (param i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 16) "[error] out of space")
+        (func (export "log") (result i32)
+            (call $proc_log (i32.const 16) (i32.const 20))
+        )
+    )
+    "#;
+
+    let logged = Arc::new(Mutex::new(Vec::new()));
+    let mut inner = PluggableRuntimeImplementation::default();
+    inner.set_log_sink({
+        let logged = logged.clone();
+        move |record: &WasiLogRecord| logged.lock().unwrap().push(record.clone())
+    });
+
+    let mut store = Store::default();
+    let (instance, _, _) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name").runtime(Process { pid: 7, inner }),
+    );
+
+    call_ok(&mut store, &instance, "log", &[]);
+    assert_eq!(
+        *logged.lock().unwrap(),
+        vec![WasiLogRecord {
+            pid: Some(7),
+            tid: 0u32.into(),
+            level: Level::ERROR,
+            text: "out of space".to_string(),
+        }]
+    );
+}
@@ -0,0 +1,50 @@
+#![cfg(all(feature = "wasix", feature = "host-vnet"))]
+
+use wasmer::{Store, Value};
+use wasmer_wasi::types::wasi::{Addressfamily, Errno};
+use wasmer_wasi::types::{__WASI_IFF_LOOPBACK, __WASI_IFF_UP};
+use wasmer_wasi::WasiState;
+
+mod common;
+use common::{call, call_ok, instantiate};
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "port_ifaddr_list" (func $port_ifaddr_list (param i32 i32) (result i32)))
+    (memory (export "memory") 1)
+    ;; Lists up to `max` entries into offset 64, the count is written at offset 0
+    (func (export "list") (param $max i32) (result i32)
+        (i32.store (i32.const 0) (local.get $max))
+        (call $port_ifaddr_list (i32.const 64) (i32.const 0))
+    )
+)
+"#;
+
+/// Size of an entry, the name, the flags and the address
+const IFADDR_SIZE: usize = 16 + 1 + 18;
+
+#[test]
+fn test_port_ifaddr_list_reports_loopback() {
+    let mut store = Store::default();
+    let (instance, memory, _) =
+        instantiate(&mut store, MODULE, &mut WasiState::new("command-name"));
+
+    // Without room the number of entries is still reported
+    let ret = call(&mut store, &instance, "list", &[Value::I32(0)]);
+    assert_eq!(ret, Value::I32(Errno::Overflow as i32));
+    let mut count = [0u8; 4];
+    memory.view(&store).read(0, &mut count).unwrap();
+    let count = u32::from_le_bytes(count);
+    assert!(count > 0);
+
+    call_ok(&mut store, &instance, "list", &[Value::I32(count as i32)]);
+    let mut entries = vec![0u8; count as usize * IFADDR_SIZE];
+    memory.view(&store).read(64, &mut entries).unwrap();
+
+    let loopback = entries
+        .chunks(IFADDR_SIZE)
+        .find(|entry| entry[17] == Addressfamily::Inet4 as u8 && entry[18] == 127)
+        .expect("no loopback address");
+    assert_ne!(loopback[0], 0, "the interface has no name");
+    assert_eq!(loopback[16], __WASI_IFF_UP | __WASI_IFF_LOOPBACK);
+}
@@ -0,0 +1,212 @@
+#![cfg(feature = "wasix")]
+
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use wasmer::{Store, Value};
+use wasmer_vbus::{
+    BusDataFormat, BusError, BusSpawnedProcess, FileDescriptor, SpawnOptions, SpawnOptionsConfig,
+    StdioMode, VirtualBus, VirtualBusInvocation, VirtualBusInvokable, VirtualBusListener,
+    VirtualBusProcess, VirtualBusScope, VirtualBusSpawner,
+};
+use wasmer_wasi::types::wasi::{BusErrno, StdioMode as WasiStdioMode};
+use wasmer_wasi::{PluggableRuntimeImplementation, WasiState};
+
+mod common;
+use common::{call, call_ok, instantiate};
+
+/// Bus whose processes keep running, as in a fork bomb, until they are
+/// all told to exit
+#[derive(Debug, Default, Clone)]
+struct IdleBus {
+    exited: Arc<AtomicBool>,
+}
+
+impl VirtualBus for IdleBus {
+    fn new_spawn(&self) -> SpawnOptions {
+        SpawnOptions::new(Box::new(self.clone()))
+    }
+
+    fn listen(&self) -> wasmer_vbus::Result<Box<dyn VirtualBusListener + Sync>> {
+        Err(BusError::Unsupported)
+    }
+}
+
+impl VirtualBusSpawner for IdleBus {
+    fn spawn(
+        &mut self,
+        _name: &str,
+        config: &SpawnOptionsConfig,
+    ) -> wasmer_vbus::Result<BusSpawnedProcess> {
+        // Pipes are given descriptors, anything else is not
+        let stdout = match config.stdout_mode() {
+            StdioMode::Piped => Some(10),
+            _ => None,
+        };
+        Ok(BusSpawnedProcess {
+            inst: Box::new(IdleProcess {
+                stdout,
+                exited: self.exited.clone(),
+            }),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct IdleProcess {
+    stdout: Option<u32>,
+    exited: Arc<AtomicBool>,
+}
+
+impl VirtualBusScope for IdleProcess {
+    fn poll_finished(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        Poll::Pending
+    }
+}
+
+impl VirtualBusInvokable for IdleProcess {
+    fn invoke(
+        &self,
+        _topic: String,
+        _format: BusDataFormat,
+        _buf: &[u8],
+    ) -> wasmer_vbus::Result<Box<dyn VirtualBusInvocation + Sync>> {
+        Err(BusError::Unsupported)
+    }
+}
+
+impl VirtualBusProcess for IdleProcess {
+    fn exit_code(&self) -> Option<u32> {
+        match self.exited.load(Ordering::SeqCst) {
+            true => Some(0),
+            false => None,
+        }
+    }
+
+    fn stdin_fd(&self) -> Option<FileDescriptor> {
+        None
+    }
+
+    fn stdout_fd(&self) -> Option<FileDescriptor> {
+        self.stdout.map(FileDescriptor::from)
+    }
+
+    fn stderr_fd(&self) -> Option<FileDescriptor> {
+        None
+    }
+}
+
+#[test]
+fn test_process_limit() {
+    const MODULE: &[u8] = br#"
+    (module
+        (import "wasix_32v1" "process_spawn" (func $process_spawn
+            (param i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 0) "child")
+        (data (i32.const 8) ".")
+        (func $spawn (export "spawn") (result i32)
+            (call $process_spawn
+                (i32.const 0) (i32.const 5) ;; name
+                (i32.const 0)               ;; chroot
+                (i32.const 0) (i32.const 0) ;; args
+                (i32.const 0) (i32.const 0) ;; preopen
+                (i32.const 3) (i32.const 3) (i32.const 3) ;; stdio
+                (i32.const 8) (i32.const 1) ;; working_dir
+                (i32.const 64)              ;; ret_handles
+            )
+        )
+        (func (export "fork_bomb") (param $n i32) (result i32)
+            (local $spawned i32)
+            (block $done
+                (loop $again
+                    (br_if $done (i32.eqz (local.get $n)))
+                    (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+                    (if (i32.eqz (call $spawn))
+                        (then (local.set $spawned (i32.add (local.get $spawned) (i32.const 1)))))
+                    (br $again)
+                )
+            )
+            (local.get $spawned)
+        )
+    )
+    "#;
+
+    let mut store = Store::default();
+    let bus = IdleBus::default();
+    let exited = bus.exited.clone();
+    let mut runtime = PluggableRuntimeImplementation::default();
+    runtime.set_bus_implementation(bus);
+    let (instance, _, _) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name")
+            .max_processes(3)
+            .max_total_processes(5)
+            .runtime(runtime),
+    );
+    let exhausted = Value::I32(BusErrno::Alloc as i32);
+
+    // Only as many processes as the limit are ever running
+    let spawned = call(&mut store, &instance, "fork_bomb", &[Value::I32(1000)]);
+    assert_eq!(spawned, Value::I32(3));
+    assert_eq!(call(&mut store, &instance, "spawn", &[]), exhausted);
+
+    // Processes that exited make room for new ones, until the total
+    // number of processes is reached
+    exited.store(true, Ordering::SeqCst);
+    let spawned = call(&mut store, &instance, "fork_bomb", &[Value::I32(1000)]);
+    assert_eq!(spawned, Value::I32(2));
+    assert_eq!(call(&mut store, &instance, "spawn", &[]), exhausted);
+}
+
+#[test]
+fn test_process_stdio_handles() {
+    const MODULE: &[u8] = br#"
+    (module
+        (import "wasix_32v1" "process_spawn" (func $process_spawn
+            (param i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 0) "child")
+        (data (i32.const 8) ".")
+        (func (export "spawn") (param $stdout i32) (result i32)
+            (call $process_spawn
+                (i32.const 0) (i32.const 5) ;; name
+                (i32.const 0)               ;; chroot
+                (i32.const 0) (i32.const 0) ;; args
+                (i32.const 0) (i32.const 0) ;; preopen
+                (i32.const 3) (local.get $stdout) (i32.const 3) ;; stdio
+                (i32.const 8) (i32.const 1) ;; working_dir
+                (i32.const 64)              ;; ret_handles
+            )
+        )
+    )
+    "#;
+
+    let mut store = Store::default();
+    let mut runtime = PluggableRuntimeImplementation::default();
+    runtime.set_bus_implementation(IdleBus::default());
+    let (instance, memory, _) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name").runtime(runtime),
+    );
+
+    let mut stdout_handle = |mode: WasiStdioMode| {
+        call_ok(&mut store, &instance, "spawn", &[Value::I32(mode as i32)]);
+
+        // `BusHandles { bid, stdin, stdout, stderr }` at offset 64
+        let mut stdout = [0u8; 8];
+        memory.view(&store).read(76, &mut stdout).unwrap();
+        let tag = stdout[0];
+        let fd = u32::from_le_bytes([stdout[4], stdout[5], stdout[6], stdout[7]]);
+        (tag, fd)
+    };
+
+    let (none, some) = (0, 1);
+    assert_eq!(stdout_handle(WasiStdioMode::Piped), (some, 10));
+    assert_eq!(stdout_handle(WasiStdioMode::Inherit), (some, 1));
+    assert_eq!(stdout_handle(WasiStdioMode::Null).0, none);
+}
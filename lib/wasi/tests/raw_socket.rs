@@ -0,0 +1,61 @@
+#![cfg(feature = "wasix")]
+
+use wasmer::{Store, Value};
+use wasmer_wasi::types::wasi::{Addressfamily, Errno, SockProto, Socktype};
+use wasmer_wasi::{WasiState, WasiStateBuilder};
+
+mod common;
+use common::{call, instantiate};
+
+/// Opens a socket with the given family, type and protocol and returns the
+/// error code of `sock_open`
+fn sock_open(builder: &mut WasiStateBuilder, ty: Socktype, pt: SockProto) -> Value {
+    const MODULE: &[u8] = br#"
+    (module
+        (import "wasix_32v1" "sock_open" (func $sock_open (param i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (func (export "open") (param i32 i32 i32) (result i32)
+            (call $sock_open (local.get 0) (local.get 1) (local.get 2) (i32.const 0))
+        )
+    )
+    "#;
+
+    let mut store = Store::default();
+    let (instance, _, _) = instantiate(&mut store, MODULE, builder);
+    let params = [
+        Value::I32(Addressfamily::Inet4 as i32),
+        Value::I32(ty as i32),
+        Value::I32(pt as i32),
+    ];
+    call(&mut store, &instance, "open", &params)
+}
+
+#[test]
+fn test_raw_socket_needs_permission() {
+    let success = Value::I32(Errno::Success as i32);
+    let perm = Value::I32(Errno::Perm as i32);
+
+    for pt in [SockProto::Icmp, SockProto::ProtoRaw] {
+        let mut denied = WasiState::new("command-name");
+        assert_eq!(sock_open(&mut denied, Socktype::Raw, pt), perm);
+
+        let mut allowed = WasiState::new("command-name");
+        allowed.allow_raw_sockets(true);
+        assert_eq!(sock_open(&mut allowed, Socktype::Raw, pt), success);
+    }
+
+    // Regular sockets don't need the permission
+    let mut denied = WasiState::new("command-name");
+    assert_eq!(
+        sock_open(&mut denied, Socktype::Stream, SockProto::Tcp),
+        success
+    );
+
+    // Raw sockets of other protocols aren't supported either way
+    let mut allowed = WasiState::new("command-name");
+    allowed.allow_raw_sockets(true);
+    assert_eq!(
+        sock_open(&mut allowed, Socktype::Raw, SockProto::Tcp),
+        Value::I32(Errno::Notsup as i32)
+    );
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "wasix")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use wasmer::{Store, Value};
+use wasmer_wasi::{
+    PluggableRuntimeImplementation, VirtualBus, VirtualNetworking, WasiError,
+    WasiRuntimeImplementation, WasiState, WasiThreadId,
+};
+
+mod common;
+use common::{call_ok, instantiate};
+
+/// Runtime that counts how many times the guest handed control back
+#[derive(Debug, Default)]
+struct CountingYields {
+    inner: PluggableRuntimeImplementation,
+    yields: Arc<AtomicUsize>,
+}
+
+impl WasiRuntimeImplementation for CountingYields {
+    fn bus(&self) -> &(dyn VirtualBus) {
+        self.inner.bus()
+    }
+
+    fn networking(&self) -> &(dyn VirtualNetworking) {
+        self.inner.networking()
+    }
+
+    fn thread_generate_id(&self) -> WasiThreadId {
+        self.inner.thread_generate_id()
+    }
+
+    fn yield_now(&self, _id: WasiThreadId) -> Result<(), WasiError> {
+        self.yields.fetch_add(1, Ordering::SeqCst);
+        std::thread::yield_now();
+        Ok(())
+    }
+}
+
+#[test]
+fn test_sched_yield() {
+    const MODULE: &[u8] = br#"
+    (module
+        (import "wasix_32v1" "sched_yield" (func $sched_yield (result i32)))
+        (import "wasix_32v1" "thread_sleep" (func $thread_sleep (param i64) (result i32)))
+        (memory (export "memory") 1)
+        (func (export "yield") (result i32)
+            (call $sched_yield)
+        )
+        (func (export "sleep") (param i64) (result i32)
+            (call $thread_sleep (local.get 0))
+        )
+    )
+    "#;
+
+    let mut store = Store::default();
+    let runtime = CountingYields::default();
+    let yields = runtime.yields.clone();
+    let (instance, _, _) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name").runtime(runtime),
+    );
+
+    // Every yield hands control to the scheduler exactly once
+    for i in 1..=10 {
+        call_ok(&mut store, &instance, "yield", &[]);
+        assert_eq!(yields.load(Ordering::SeqCst), i);
+    }
+
+    // Sleeping for no time at all is a plain yield
+    call_ok(&mut store, &instance, "sleep", &[Value::I64(0)]);
+    assert_eq!(yields.load(Ordering::SeqCst), 11);
+}
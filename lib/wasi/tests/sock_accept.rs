@@ -0,0 +1,210 @@
+#![cfg(all(feature = "wasix", feature = "host-vnet"))]
+
+use std::convert::TryInto;
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+use wasmer::{Instance, Store, Value};
+use wasmer_wasi::types::wasi::{Addressfamily, Errno, Fdflags, OptionTag, Sockoption, Socktype};
+use wasmer_wasi::{WasiState, WasiStateBuilder};
+
+mod common;
+use common::{call, call_ok, instantiate, write_localhost_addr};
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "sock_open" (func $sock_open (param i32 i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_bind" (func $sock_bind (param i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_listen" (func $sock_listen (param i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_set_opt_time" (func $sock_set_opt_time (param i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_accept" (func $sock_accept (param i32 i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_addr_local" (func $sock_addr_local (param i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_get_opt_time" (func $sock_get_opt_time (param i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "fd_fdstat_get" (func $fd_fdstat_get (param i32 i32) (result i32)))
+    (import "wasix_32v1" "fd_fdstat_set_flags" (func $fd_fdstat_set_flags (param i32 i32) (result i32)))
+    (memory (export "memory") 1)
+    ;; The descriptor is written at offset 0, the address is read from offset 16
+    (func (export "listen") (param $af i32) (param $ty i32) (result i32)
+        (local $errno i32)
+        (local.set $errno (call $sock_open (local.get $af) (local.get $ty) (i32.const 0) (i32.const 0)))
+        (if (local.get $errno) (then (return (local.get $errno))))
+        (local.set $errno (call $sock_bind (i32.load (i32.const 0)) (i32.const 16)))
+        (if (local.get $errno) (then (return (local.get $errno))))
+        (call $sock_listen (i32.load (i32.const 0)) (i32.const 16))
+    )
+    ;; The timeout is read from offset 48
+    (func (export "set_opt_time") (param $opt i32) (result i32)
+        (call $sock_set_opt_time (i32.load (i32.const 0)) (local.get $opt) (i32.const 48))
+    )
+    (func (export "set_flags") (param $flags i32) (result i32)
+        (call $fd_fdstat_set_flags (i32.load (i32.const 0)) (local.get $flags))
+    )
+    ;; The local address is written at offset 112
+    (func (export "addr_local") (result i32)
+        (call $sock_addr_local (i32.load (i32.const 0)) (i32.const 112))
+    )
+    ;; The accepted descriptor is written at offset 64
+    (func (export "accept") (param $flags i32) (result i32)
+        (call $sock_accept (i32.load (i32.const 0)) (local.get $flags) (i32.const 64) (i32.const 80))
+    )
+    ;; The `fdstat` of the accepted descriptor is written at offset 136
+    (func (export "accepted_fdstat") (result i32)
+        (call $fd_fdstat_get (i32.load (i32.const 64)) (i32.const 136))
+    )
+    ;; The timeout of the accepted descriptor is written at offset 160
+    (func (export "accepted_opt_time") (param $opt i32) (result i32)
+        (call $sock_get_opt_time (i32.load (i32.const 64)) (local.get $opt) (i32.const 160))
+    )
+)
+"#;
+
+/// Instantiates the module and makes it listen on a loopback port
+fn listen(store: &mut Store, builder: &mut WasiStateBuilder) -> Instance {
+    let (instance, memory, _) = instantiate(store, MODULE, builder);
+    write_localhost_addr(store, &memory, 16, 0);
+    let params = [
+        Value::I32(Addressfamily::Inet4 as i32),
+        Value::I32(Socktype::Stream as i32),
+    ];
+    call_ok(store, &instance, "listen", &params);
+    instance
+}
+
+fn accept(store: &mut Store, instance: &Instance, flags: Fdflags) -> (Value, Duration) {
+    let start = Instant::now();
+    let ret = call(
+        store,
+        instance,
+        "accept",
+        &[Value::I32(flags.bits() as i32)],
+    );
+    (ret, start.elapsed())
+}
+
+/// Connects to the listening socket from the host
+fn connect(store: &mut Store, instance: &Instance) -> TcpStream {
+    call_ok(store, instance, "addr_local", &[]);
+
+    let memory = instance.exports.get_memory("memory").unwrap();
+    let mut port = [0u8; 2];
+    memory.view(&*store).read(113, &mut port).unwrap();
+    TcpStream::connect(("127.0.0.1", u16::from_be_bytes(port))).unwrap()
+}
+
+/// Writes an `OptionTimestamp` at offset 48 and sets it as an option of
+/// the listening socket
+fn set_opt_time(store: &mut Store, instance: &Instance, opt: Sockoption, time: Duration) {
+    let memory = instance.exports.get_memory("memory").unwrap();
+    let view = memory.view(&*store);
+    view.write(48, &[OptionTag::Some as u8]).unwrap();
+    view.write(56, &(time.as_nanos() as u64).to_le_bytes())
+        .unwrap();
+    call_ok(store, instance, "set_opt_time", &[Value::I32(opt as i32)]);
+}
+
+#[test]
+fn test_socket_accept_timeout() {
+    let mut store = Store::default();
+    let instance = listen(&mut store, &mut WasiState::new("command-name"));
+    set_opt_time(
+        &mut store,
+        &instance,
+        Sockoption::AcceptTimeout,
+        Duration::from_millis(100),
+    );
+
+    let (errno, elapsed) = accept(&mut store, &instance, Fdflags::empty());
+    assert_eq!(errno, Value::I32(Errno::Timedout as i32));
+    assert!(elapsed >= Duration::from_millis(100));
+}
+
+#[test]
+fn test_default_accept_timeout() {
+    let mut store = Store::default();
+    let instance = listen(
+        &mut store,
+        WasiState::new("command-name").default_accept_timeout(Duration::from_millis(100)),
+    );
+
+    let (errno, elapsed) = accept(&mut store, &instance, Fdflags::empty());
+    assert_eq!(errno, Value::I32(Errno::Timedout as i32));
+    assert!(elapsed >= Duration::from_millis(100));
+}
+
+#[test]
+fn test_accepted_socket_nonblocking() {
+    for (listener_nonblocking, requested_nonblocking) in
+        [(false, false), (false, true), (true, false), (true, true)]
+    {
+        let mut store = Store::default();
+        let instance = listen(&mut store, &mut WasiState::new("command-name"));
+
+        if listener_nonblocking {
+            let flags = Value::I32(Fdflags::NONBLOCK.bits() as i32);
+            call_ok(&mut store, &instance, "set_flags", &[flags]);
+        }
+        let requested = if requested_nonblocking {
+            Fdflags::NONBLOCK
+        } else {
+            Fdflags::empty()
+        };
+
+        let _stream = connect(&mut store, &instance);
+        let (errno, _) = accept(&mut store, &instance, requested);
+        assert_eq!(errno, Value::I32(Errno::Success as i32));
+
+        call_ok(&mut store, &instance, "accepted_fdstat", &[]);
+        let memory = instance.exports.get_memory("memory").unwrap();
+        let mut flags = [0u8; 2];
+        memory.view(&store).read(138, &mut flags).unwrap();
+        let flags = Fdflags::from_bits_truncate(u16::from_le_bytes(flags));
+        assert_eq!(
+            flags.contains(Fdflags::NONBLOCK),
+            listener_nonblocking || requested_nonblocking,
+            "listener nonblocking: {}, requested nonblocking: {}",
+            listener_nonblocking,
+            requested_nonblocking
+        );
+    }
+}
+
+#[test]
+fn test_accepted_socket_inherits_timeouts() {
+    let mut store = Store::default();
+    let instance = listen(&mut store, &mut WasiState::new("command-name"));
+    set_opt_time(
+        &mut store,
+        &instance,
+        Sockoption::RecvTimeout,
+        Duration::from_millis(1500),
+    );
+    set_opt_time(
+        &mut store,
+        &instance,
+        Sockoption::SendTimeout,
+        Duration::from_millis(2500),
+    );
+
+    let _stream = connect(&mut store, &instance);
+    let (errno, _) = accept(&mut store, &instance, Fdflags::empty());
+    assert_eq!(errno, Value::I32(Errno::Success as i32));
+
+    let memory = instance.exports.get_memory("memory").unwrap();
+    for (opt, expected) in [
+        (Sockoption::RecvTimeout, Duration::from_millis(1500)),
+        (Sockoption::SendTimeout, Duration::from_millis(2500)),
+    ] {
+        call_ok(
+            &mut store,
+            &instance,
+            "accepted_opt_time",
+            &[Value::I32(opt as i32)],
+        );
+
+        let mut time = [0u8; 16];
+        memory.view(&store).read(160, &mut time).unwrap();
+        assert_eq!(time[0], OptionTag::Some as u8);
+        let nanos = u64::from_le_bytes(time[8..16].try_into().unwrap());
+        assert_eq!(Duration::from_nanos(nanos), expected);
+    }
+}
@@ -0,0 +1,147 @@
+#![cfg(all(feature = "wasix", feature = "host-tls"))]
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::thread;
+
+use wasmer::{Instance, Memory, Store, Value};
+use wasmer_wasi::types::wasi::{Addressfamily, Errno, Socktype};
+use wasmer_wasi::{PluggableRuntimeImplementation, WasiState};
+use wasmer_wasi_local_networking::{LocalNetworking, TlsNetworking};
+
+mod common;
+use common::{call, call_ok, instantiate, write_localhost_addr};
+
+// The CA and `localhost` certificate of the TLS tests of the networking crate
+const CA_CERT: &[u8] = include_bytes!("../../wasi-local-networking/tests/tls/ca.der");
+const LOCALHOST_CERT: &[u8] = include_bytes!("../../wasi-local-networking/tests/tls/localhost.der");
+const LOCALHOST_KEY: &[u8] =
+    include_bytes!("../../wasi-local-networking/tests/tls/localhost.key.der");
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "sock_open" (func $sock_open (param i32 i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_connect_tls" (func $sock_connect_tls (param i32 i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_send" (func $sock_send (param i32 i32 i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_recv" (func $sock_recv (param i32 i32 i32 i32 i32 i32) (result i32)))
+    (memory (export "memory") 1)
+    (data (i32.const 128) "localhost")
+    (data (i32.const 256) "hello over tls")
+    ;; The descriptor is written at offset 0
+    (func (export "open") (param $af i32) (param $ty i32) (result i32)
+        (call $sock_open (local.get $af) (local.get $ty) (i32.const 0) (i32.const 0))
+    )
+    ;; The address is read from offset 16
+    (func (export "connect") (param $name_len i32) (result i32)
+        (call $sock_connect_tls (i32.load (i32.const 0)) (i32.const 16) (i32.const 128) (local.get $name_len))
+    )
+    ;; Sends the 14 bytes at offset 256
+    (func (export "send") (result i32)
+        (i32.store (i32.const 48) (i32.const 256))
+        (i32.store (i32.const 52) (i32.const 14))
+        (call $sock_send (i32.load (i32.const 0)) (i32.const 48) (i32.const 1) (i32.const 0) (i32.const 56))
+    )
+    ;; Receives up to 64 bytes into offset 512, the length is written at offset 56
+    (func (export "recv") (result i32)
+        (i32.store (i32.const 48) (i32.const 512))
+        (i32.store (i32.const 52) (i32.const 64))
+        (call $sock_recv (i32.load (i32.const 0)) (i32.const 48) (i32.const 1) (i32.const 0) (i32.const 56) (i32.const 60))
+    )
+)
+"#;
+
+/// Starts a TLS server that echoes back everything it receives on one
+/// connection
+fn echo_server() -> TcpListener {
+    let config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![rustls::Certificate(LOCALHOST_CERT.to_vec())],
+            rustls::PrivateKey(LOCALHOST_KEY.to_vec()),
+        )
+        .unwrap();
+    let config = Arc::new(config);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let server = listener.try_clone().unwrap();
+    thread::spawn(move || {
+        let (mut stream, _) = server.accept().unwrap();
+        let mut conn = rustls::ServerConnection::new(config).unwrap();
+        let mut tls = rustls::Stream::new(&mut conn, &mut stream);
+        let mut buf = [0u8; 1024];
+        loop {
+            match tls.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    if tls.write_all(&buf[..n]).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+    listener
+}
+
+/// Opens a stream socket that will connect to `listener` once asked to
+fn open(store: &mut Store, listener: &TcpListener) -> (Instance, Memory) {
+    let mut runtime = PluggableRuntimeImplementation::default();
+    runtime.set_networking_implementation(
+        TlsNetworking::with_root_certificate(LocalNetworking::default(), CA_CERT).unwrap(),
+    );
+    let (instance, memory, _) = instantiate(
+        store,
+        MODULE,
+        WasiState::new("command-name").runtime(runtime),
+    );
+    let port = listener.local_addr().unwrap().port();
+    write_localhost_addr(store, &memory, 16, port);
+
+    let params = [
+        Value::I32(Addressfamily::Inet4 as i32),
+        Value::I32(Socktype::Stream as i32),
+    ];
+    call_ok(store, &instance, "open", &params);
+    (instance, memory)
+}
+
+#[test]
+fn test_sock_connect_tls_round_trip() {
+    let listener = echo_server();
+    let mut store = Store::default();
+    let (instance, memory) = open(&mut store, &listener);
+
+    let name_len = Value::I32("localhost".len() as i32);
+    call_ok(&mut store, &instance, "connect", &[name_len]);
+    call_ok(&mut store, &instance, "send", &[]);
+
+    let mut received = Vec::new();
+    while received.len() < b"hello over tls".len() {
+        call_ok(&mut store, &instance, "recv", &[]);
+        let view = memory.view(&store);
+        let mut len = [0u8; 4];
+        view.read(56, &mut len).unwrap();
+        let len = u32::from_le_bytes(len) as usize;
+        assert!(len > 0, "connection closed early");
+        let mut data = vec![0u8; len];
+        view.read(512, &mut data).unwrap();
+        received.extend_from_slice(&data);
+    }
+    assert_eq!(&received[..], b"hello over tls");
+}
+
+#[test]
+fn test_sock_connect_tls_verifies_the_server_name() {
+    let listener = echo_server();
+    let mut store = Store::default();
+    let (instance, _) = open(&mut store, &listener);
+
+    // The certificate is for `localhost`, not for `local`
+    let name_len = Value::I32("local".len() as i32);
+    assert_ne!(
+        call(&mut store, &instance, "connect", &[name_len]),
+        Value::I32(Errno::Success as i32)
+    );
+}
@@ -0,0 +1,138 @@
+#![cfg(all(feature = "wasix", feature = "host-vnet"))]
+
+use std::convert::TryInto;
+use std::net::TcpListener;
+use std::time::Duration;
+
+use wasmer::{Instance, Store, Value};
+use wasmer_wasi::types::wasi::{Addressfamily, OptionTag, Sockoption, Socktype};
+use wasmer_wasi::WasiState;
+
+mod common;
+use common::{call_ok, instantiate, write_localhost_addr};
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "sock_open" (func $sock_open (param i32 i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_connect" (func $sock_connect (param i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_set_opt_flag" (func $sock_set_opt_flag (param i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_get_opt_flag" (func $sock_get_opt_flag (param i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_set_opt_time" (func $sock_set_opt_time (param i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_get_opt_time" (func $sock_get_opt_time (param i32 i32 i32) (result i32)))
+    (memory (export "memory") 1)
+    ;; The descriptor is written at offset 0
+    (func (export "open") (param $af i32) (param $ty i32) (result i32)
+        (call $sock_open (local.get $af) (local.get $ty) (i32.const 0) (i32.const 0))
+    )
+    ;; The address is read from offset 16
+    (func (export "connect") (result i32)
+        (call $sock_connect (i32.load (i32.const 0)) (i32.const 16))
+    )
+    (func (export "set_opt_flag") (param $opt i32) (param $flag i32) (result i32)
+        (call $sock_set_opt_flag (i32.load (i32.const 0)) (local.get $opt) (local.get $flag))
+    )
+    ;; The flag is written at offset 40
+    (func (export "get_opt_flag") (param $opt i32) (result i32)
+        (call $sock_get_opt_flag (i32.load (i32.const 0)) (local.get $opt) (i32.const 40))
+    )
+    ;; The time is read from offset 48
+    (func (export "set_opt_time") (param $opt i32) (result i32)
+        (call $sock_set_opt_time (i32.load (i32.const 0)) (local.get $opt) (i32.const 48))
+    )
+    ;; The time is written at offset 64
+    (func (export "get_opt_time") (param $opt i32) (result i32)
+        (call $sock_get_opt_time (i32.load (i32.const 0)) (local.get $opt) (i32.const 64))
+    )
+)
+"#;
+
+const LINGER: Value = Value::I32(Sockoption::Linger as i32);
+
+/// Opens a stream socket that will connect to `listener` once asked to
+fn open(store: &mut Store, listener: &TcpListener) -> Instance {
+    let (instance, memory, _) = instantiate(store, MODULE, &mut WasiState::new("command-name"));
+    let port = listener.local_addr().unwrap().port();
+    write_localhost_addr(store, &memory, 16, port);
+
+    let params = [
+        Value::I32(Addressfamily::Inet4 as i32),
+        Value::I32(Socktype::Stream as i32),
+    ];
+    call_ok(store, &instance, "open", &params);
+    instance
+}
+
+/// Reads the linger time of the socket
+fn linger(store: &mut Store, instance: &Instance) -> Option<Duration> {
+    call_ok(store, instance, "get_opt_time", &[LINGER]);
+    let memory = instance.exports.get_memory("memory").unwrap();
+    let mut time = [0u8; 16];
+    memory.view(&*store).read(64, &mut time).unwrap();
+    match time[0] {
+        tag if tag == OptionTag::Some as u8 => Some(Duration::from_nanos(u64::from_le_bytes(
+            time[8..16].try_into().unwrap(),
+        ))),
+        _ => None,
+    }
+}
+
+/// Reads the linger flag of the socket
+fn linger_flag(store: &mut Store, instance: &Instance) -> bool {
+    call_ok(store, instance, "get_opt_flag", &[LINGER]);
+    let memory = instance.exports.get_memory("memory").unwrap();
+    let mut flag = [0u8; 1];
+    memory.view(&*store).read(40, &mut flag).unwrap();
+    flag[0] != 0
+}
+
+#[test]
+fn test_linger_time_is_applied_on_connect() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut store = Store::default();
+    let instance = open(&mut store, &listener);
+    assert_eq!(linger(&mut store, &instance), None);
+    assert!(!linger_flag(&mut store, &instance));
+
+    // `OptionTimestamp` of 3s at offset 48
+    let memory = instance.exports.get_memory("memory").unwrap();
+    let view = memory.view(&store);
+    view.write(48, &[OptionTag::Some as u8]).unwrap();
+    view.write(56, &3_000_000_000u64.to_le_bytes()).unwrap();
+    call_ok(&mut store, &instance, "set_opt_time", &[LINGER]);
+    assert_eq!(linger(&mut store, &instance), Some(Duration::from_secs(3)));
+
+    call_ok(&mut store, &instance, "connect", &[]);
+    let _peer = listener.accept().unwrap();
+    assert_eq!(linger(&mut store, &instance), Some(Duration::from_secs(3)));
+    assert!(linger_flag(&mut store, &instance));
+}
+
+#[test]
+fn test_linger_flag_is_applied_on_connect() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut store = Store::default();
+    let instance = open(&mut store, &listener);
+
+    // Turning linger on without a time makes the close abortive
+    call_ok(
+        &mut store,
+        &instance,
+        "set_opt_flag",
+        &[LINGER, Value::I32(1)],
+    );
+    assert!(linger_flag(&mut store, &instance));
+
+    call_ok(&mut store, &instance, "connect", &[]);
+    let _peer = listener.accept().unwrap();
+    assert!(linger_flag(&mut store, &instance));
+    assert_eq!(linger(&mut store, &instance), Some(Duration::ZERO));
+
+    call_ok(
+        &mut store,
+        &instance,
+        "set_opt_flag",
+        &[LINGER, Value::I32(0)],
+    );
+    assert!(!linger_flag(&mut store, &instance));
+    assert_eq!(linger(&mut store, &instance), None);
+}
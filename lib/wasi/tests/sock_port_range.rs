@@ -0,0 +1,71 @@
+#![cfg(all(feature = "wasix", feature = "host-vnet"))]
+
+use std::net::UdpSocket;
+
+use wasmer::{Store, Value};
+use wasmer_vnet::PortRangeNetworking;
+use wasmer_wasi::types::wasi::{Addressfamily, Errno, Socktype};
+use wasmer_wasi::{PluggableRuntimeImplementation, WasiState};
+use wasmer_wasi_local_networking::LocalNetworking;
+
+mod common;
+use common::{call, instantiate, write_localhost_addr};
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "sock_open" (func $sock_open (param i32 i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_bind" (func $sock_bind (param i32 i32) (result i32)))
+    (memory (export "memory") 1)
+    ;; Opens a datagram socket and binds it to the address at offset 16,
+    ;; the descriptor is written at offset 0
+    (func (export "bind") (param $af i32) (param $ty i32) (result i32)
+        (local $ret i32)
+        (local.set $ret
+            (call $sock_open (local.get $af) (local.get $ty) (i32.const 0) (i32.const 0)))
+        (if (i32.ne (local.get $ret) (i32.const 0))
+            (then (return (local.get $ret))))
+        (call $sock_bind (i32.load (i32.const 0)) (i32.const 16))
+    )
+)
+"#;
+
+#[test]
+fn test_sock_bind_outside_of_port_range() {
+    // A port that was free a moment ago makes up the whole range
+    let port = UdpSocket::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    let mut store = Store::default();
+    let mut runtime = PluggableRuntimeImplementation::default();
+    runtime.set_networking_implementation(PortRangeNetworking::new(
+        LocalNetworking::default(),
+        port..=port,
+    ));
+    let (instance, memory, _) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name").runtime(runtime),
+    );
+
+    let bind_to = |store: &mut Store, port: u16| {
+        write_localhost_addr(store, &memory, 16, port);
+        let params = [
+            Value::I32(Addressfamily::Inet4 as i32),
+            Value::I32(Socktype::Dgram as i32),
+        ];
+        call(store, &instance, "bind", &params)
+    };
+
+    // Explicit ports have to be in the range
+    let outside = port.checked_add(1).unwrap_or(port - 1);
+    assert_eq!(
+        bind_to(&mut store, outside),
+        Value::I32(Errno::Access as i32)
+    );
+    // Port zero picks one from the range, until there is none left
+    assert_eq!(bind_to(&mut store, 0), Value::I32(Errno::Success as i32));
+    assert_eq!(bind_to(&mut store, 0), Value::I32(Errno::Access as i32));
+}
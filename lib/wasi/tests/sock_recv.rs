@@ -0,0 +1,88 @@
+#![cfg(all(feature = "wasix", feature = "host-vnet"))]
+
+use std::io::Write;
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+
+use wasmer::{Store, Value};
+use wasmer_wasi::types::wasi::{Addressfamily, Errno, Signal, Socktype};
+use wasmer_wasi::WasiState;
+
+mod common;
+use common::{call, call_ok, instantiate, write_localhost_addr};
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "sock_open" (func $sock_open (param i32 i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_connect" (func $sock_connect (param i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_recv" (func $sock_recv (param i32 i32 i32 i32 i32 i32) (result i32)))
+    (memory (export "memory") 1)
+    ;; The descriptor is written at offset 0, the address is read from offset 16
+    (func (export "connect") (param $af i32) (param $ty i32) (result i32)
+        (local $errno i32)
+        (local.set $errno (call $sock_open (local.get $af) (local.get $ty) (i32.const 0) (i32.const 0)))
+        (if (local.get $errno) (then (return (local.get $errno))))
+        (call $sock_connect (i32.load (i32.const 0)) (i32.const 16))
+    )
+    ;; Receives into the 16 bytes at offset 64, the length is written at offset 48
+    (func (export "recv") (result i32)
+        (i32.store (i32.const 32) (i32.const 64))
+        (i32.store (i32.const 36) (i32.const 16))
+        (call $sock_recv (i32.load (i32.const 0)) (i32.const 32) (i32.const 1) (i32.const 0) (i32.const 48) (i32.const 52))
+    )
+)
+"#;
+
+#[test]
+fn test_signal_interrupts_sock_recv() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let mut store = Store::default();
+    let (instance, memory, wasi_env) =
+        instantiate(&mut store, MODULE, &mut WasiState::new("command-name"));
+    write_localhost_addr(&store, &memory, 16, port);
+    let params = [
+        Value::I32(Addressfamily::Inet4 as i32),
+        Value::I32(Socktype::Stream as i32),
+    ];
+    call_ok(&mut store, &instance, "connect", &params);
+    let (mut peer, _) = listener.accept().unwrap();
+
+    let env = wasi_env.env.as_ref(&store).clone();
+
+    // Nothing is ever sent, only the signal gets the receive to return
+    let signaller = {
+        let env = env.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            env.signal(Signal::Sigint);
+        })
+    };
+    let start = Instant::now();
+    let ret = call(&mut store, &instance, "recv", &[]);
+    assert_eq!(ret, Value::I32(Errno::Intr as i32));
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert!(start.elapsed() < Duration::from_secs(10));
+    signaller.join().unwrap();
+
+    // Bytes that already arrived are returned rather than interrupted, the
+    // signal then interrupts the next receive straight away
+    peer.write_all(b"hello").unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+    env.signal(Signal::Sigint);
+
+    call_ok(&mut store, &instance, "recv", &[]);
+    let view = memory.view(&store);
+    let mut len = [0u8; 4];
+    view.read(48, &mut len).unwrap();
+    let len = u32::from_le_bytes(len) as usize;
+    let mut data = vec![0u8; len];
+    view.read(64, &mut data).unwrap();
+    assert_eq!(data, b"hello");
+
+    let start = Instant::now();
+    let ret = call(&mut store, &instance, "recv", &[]);
+    assert_eq!(ret, Value::I32(Errno::Intr as i32));
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
@@ -0,0 +1,118 @@
+#![cfg(all(feature = "wasix", feature = "host-vnet", target_os = "linux"))]
+
+use std::convert::TryInto;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+use wasmer::{Instance, Memory, Store, Value};
+use wasmer_wasi::types::wasi::{Addressfamily, Errno, Socktype};
+use wasmer_wasi::types::{
+    __WASI_TCP_STATS_BYTES_RECEIVED, __WASI_TCP_STATS_BYTES_SENT, __WASI_TCP_STATS_RTT,
+    __WASI_TCP_STATS_SEGMENTS_SENT,
+};
+use wasmer_wasi::WasiState;
+
+mod common;
+use common::{call, call_ok, instantiate, write_localhost_addr};
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "sock_open" (func $sock_open (param i32 i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_connect" (func $sock_connect (param i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_send" (func $sock_send (param i32 i32 i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_recv" (func $sock_recv (param i32 i32 i32 i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_get_tcp_stats" (func $sock_get_tcp_stats (param i32 i32) (result i32)))
+    (memory (export "memory") 1)
+    (data (i32.const 256) "ping")
+    ;; The descriptor is written at offset 0
+    (func (export "open") (param $af i32) (param $ty i32) (result i32)
+        (call $sock_open (local.get $af) (local.get $ty) (i32.const 0) (i32.const 0))
+    )
+    ;; The address is read from offset 16
+    (func (export "connect") (result i32)
+        (call $sock_connect (i32.load (i32.const 0)) (i32.const 16))
+    )
+    ;; Sends the 4 bytes at offset 256
+    (func (export "send") (result i32)
+        (i32.store (i32.const 48) (i32.const 256))
+        (i32.store (i32.const 52) (i32.const 4))
+        (call $sock_send (i32.load (i32.const 0)) (i32.const 48) (i32.const 1) (i32.const 0) (i32.const 56))
+    )
+    ;; Receives up to 64 bytes into offset 512
+    (func (export "recv") (result i32)
+        (i32.store (i32.const 48) (i32.const 512))
+        (i32.store (i32.const 52) (i32.const 64))
+        (call $sock_recv (i32.load (i32.const 0)) (i32.const 48) (i32.const 1) (i32.const 0) (i32.const 56) (i32.const 60))
+    )
+    ;; The statistics are written at offset 128
+    (func (export "stats") (result i32)
+        (call $sock_get_tcp_stats (i32.load (i32.const 0)) (i32.const 128))
+    )
+)
+"#;
+
+/// Opens a stream socket that will connect to `listener` once asked to
+fn open(store: &mut Store, listener: &TcpListener) -> (Instance, Memory) {
+    let (instance, memory, _) = instantiate(store, MODULE, &mut WasiState::new("command-name"));
+    let port = listener.local_addr().unwrap().port();
+    write_localhost_addr(store, &memory, 16, port);
+
+    let params = [
+        Value::I32(Addressfamily::Inet4 as i32),
+        Value::I32(Socktype::Stream as i32),
+    ];
+    call_ok(store, &instance, "open", &params);
+    (instance, memory)
+}
+
+#[test]
+fn test_sock_get_tcp_stats_after_transfer() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut store = Store::default();
+    let (instance, memory) = open(&mut store, &listener);
+    call_ok(&mut store, &instance, "connect", &[]);
+
+    // Echo the ping back, once the program received it the peer has
+    // acknowledged what it sent
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0u8; 4];
+        stream.read_exact(&mut buf).unwrap();
+        stream.write_all(&buf).unwrap();
+    });
+    call_ok(&mut store, &instance, "send", &[]);
+    call_ok(&mut store, &instance, "recv", &[]);
+    server.join().unwrap();
+
+    call_ok(&mut store, &instance, "stats", &[]);
+    let mut stats = [0u8; 64];
+    memory.view(&store).read(128, &mut stats).unwrap();
+    let field = |offset: usize| u64::from_le_bytes(stats[offset..offset + 8].try_into().unwrap());
+    let fields = u32::from_le_bytes(stats[..4].try_into().unwrap());
+
+    for flag in [
+        __WASI_TCP_STATS_RTT,
+        __WASI_TCP_STATS_BYTES_SENT,
+        __WASI_TCP_STATS_BYTES_RECEIVED,
+        __WASI_TCP_STATS_SEGMENTS_SENT,
+    ] {
+        assert_ne!(fields & flag, 0, "{:#x}", flag);
+    }
+    // bytes_sent, bytes_received and segments_sent
+    assert!(field(32) >= 4);
+    assert!(field(40) >= 4);
+    assert_ne!(field(48), 0);
+}
+
+#[test]
+fn test_sock_get_tcp_stats_needs_a_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut store = Store::default();
+    let (instance, _) = open(&mut store, &listener);
+
+    assert_eq!(
+        call(&mut store, &instance, "stats", &[]),
+        Value::I32(Errno::Notconn as i32)
+    );
+}
@@ -0,0 +1,89 @@
+#![cfg(all(feature = "wasix", feature = "host-vnet"))]
+
+use std::net::SocketAddr;
+
+use wasmer::Store;
+use wasmer_wasi::types::wasi::{Addressfamily, SockProto, Socktype};
+use wasmer_wasi::{WasiSocketState, WasiState};
+
+mod common;
+use common::{call_ok, instantiate, write_localhost_addr};
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "sock_open" (func $sock_open (param i32 i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_bind" (func $sock_bind (param i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_listen" (func $sock_listen (param i32 i32) (result i32)))
+    (import "wasix_32v1" "sock_connect" (func $sock_connect (param i32 i32) (result i32)))
+    (memory (export "memory") 1)
+    ;; The descriptor is written at offset 0, the address is read from offset 16
+    (func (export "listen") (result i32)
+        (local $errno i32)
+        (local.set $errno (call $sock_open (i32.const 1) (i32.const 1) (i32.const 0) (i32.const 0)))
+        (if (local.get $errno) (then (return (local.get $errno))))
+        (local.set $errno (call $sock_bind (i32.load (i32.const 0)) (i32.const 16)))
+        (if (local.get $errno) (then (return (local.get $errno))))
+        (call $sock_listen (i32.load (i32.const 0)) (i32.const 16))
+    )
+    ;; The descriptor is written at offset 4, the address is read from offset 16
+    (func (export "connect") (result i32)
+        (local $errno i32)
+        (local.set $errno (call $sock_open (i32.const 1) (i32.const 1) (i32.const 0) (i32.const 4)))
+        (if (local.get $errno) (then (return (local.get $errno))))
+        (call $sock_connect (i32.load (i32.const 4)) (i32.const 16))
+    )
+    ;; Opens a socket without binding it, the descriptor is written at offset 8
+    (func (export "open") (result i32)
+        (call $sock_open (i32.const 1) (i32.const 1) (i32.const 0) (i32.const 8))
+    )
+)
+"#;
+
+#[test]
+fn test_socket_snapshot() {
+    // The module hardcodes the family and type it opens sockets with
+    assert_eq!(Addressfamily::Inet4 as i32, 1);
+    assert_eq!(Socktype::Stream as i32, 1);
+
+    let mut store = Store::default();
+    let (instance, memory, wasi_env) =
+        instantiate(&mut store, MODULE, &mut WasiState::new("command-name"));
+    write_localhost_addr(&store, &memory, 16, 0);
+    call_ok(&mut store, &instance, "listen", &[]);
+
+    // Connect to the port the listener got
+    let env = wasi_env.env.as_ref(&store).clone();
+    let listener = env.socket_snapshot()[0].clone();
+    let listener_addr: SocketAddr = listener.local_addr.unwrap();
+    assert_eq!(listener.state, WasiSocketState::Listening);
+    assert_eq!(listener.protocol, SockProto::Tcp);
+    assert_eq!(listener.peer_addr, None);
+    assert_ne!(listener_addr.port(), 0);
+    write_localhost_addr(&store, &memory, 16, listener_addr.port());
+    call_ok(&mut store, &instance, "connect", &[]);
+    call_ok(&mut store, &instance, "open", &[]);
+
+    let view = memory.view(&store);
+    let mut fds = [0u8; 12];
+    view.read(0, &mut fds).unwrap();
+    let fd = |i: usize| u32::from_le_bytes([fds[i], fds[i + 1], fds[i + 2], fds[i + 3]]);
+
+    let sockets = env.socket_snapshot();
+    assert_eq!(sockets.len(), 3);
+    assert_eq!(sockets[0].fd, fd(0));
+    assert_eq!(sockets[0].state, WasiSocketState::Listening);
+    assert_eq!(sockets[0].local_addr, Some(listener_addr));
+
+    assert_eq!(sockets[1].fd, fd(4));
+    assert_eq!(sockets[1].state, WasiSocketState::Connected);
+    assert_eq!(sockets[1].protocol, SockProto::Tcp);
+    assert_eq!(sockets[1].peer_addr, Some(listener_addr));
+    let local_addr = sockets[1].local_addr.unwrap();
+    assert!(local_addr.ip().is_loopback());
+    assert_ne!(local_addr.port(), listener_addr.port());
+
+    assert_eq!(sockets[2].fd, fd(8));
+    assert_eq!(sockets[2].state, WasiSocketState::Opened);
+    assert_eq!(sockets[2].local_addr, None);
+    assert_eq!(sockets[2].peer_addr, None);
+}
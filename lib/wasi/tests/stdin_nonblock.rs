@@ -0,0 +1,202 @@
+#![cfg(feature = "wasix")]
+
+use std::convert::TryInto;
+use std::io::{self, Read, Seek, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use wasmer::{Instance, Memory, Store, Value};
+use wasmer_wasi::types::wasi::{Errno, Eventtype, Fdflags};
+use wasmer_wasi::{FsError, Pipe, VirtualFile, WasiState};
+
+mod common;
+use common::{call, instantiate};
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "fd_fdstat_set_flags" (func $fd_fdstat_set_flags (param i32 i32) (result i32)))
+    (import "wasix_32v1" "fd_read" (func $fd_read (param i32 i32 i32 i32) (result i32)))
+    (import "wasix_32v1" "poll_oneoff" (func $poll_oneoff (param i32 i32 i32 i32) (result i32)))
+    (memory (export "memory") 1)
+    (func (export "set_flags") (param $flags i32) (result i32)
+        (call $fd_fdstat_set_flags (i32.const 0) (local.get $flags))
+    )
+    ;; Reads up to 64 bytes into offset 64, the length is written at offset 16
+    (func (export "read") (result i32)
+        (i32.store (i32.const 0) (i32.const 64))
+        (i32.store (i32.const 4) (i32.const 64))
+        (call $fd_read (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 16))
+    )
+    ;; Subscribes to stdin being readable with the subscription at offset 256,
+    ;; the event is written at offset 320
+    (func (export "poll") (param $type i32) (result i32)
+        (i64.store (i32.const 256) (i64.const 42))
+        (i32.store8 (i32.const 264) (local.get $type))
+        (i32.store (i32.const 272) (i32.const 0))
+        (call $poll_oneoff (i32.const 256) (i32.const 320) (i32.const 1) (i32.const 20))
+    )
+)
+"#;
+
+/// Standard input whose writing end can be closed, after which the
+/// program reads the end of it
+#[derive(Debug, Default)]
+struct ClosableStdin {
+    pipe: Pipe,
+    closed: Arc<AtomicBool>,
+}
+
+impl Read for ClosableStdin {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.pipe.read(buf)
+    }
+}
+
+impl Write for ClosableStdin {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pipe.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for ClosableStdin {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.pipe.seek(pos)
+    }
+}
+
+impl VirtualFile for ClosableStdin {
+    fn last_accessed(&self) -> u64 {
+        0
+    }
+    fn last_modified(&self) -> u64 {
+        0
+    }
+    fn created_time(&self) -> u64 {
+        0
+    }
+    fn size(&self) -> u64 {
+        self.pipe.size()
+    }
+    fn set_len(&mut self, new_size: u64) -> Result<(), FsError> {
+        self.pipe.set_len(new_size)
+    }
+    fn unlink(&mut self) -> Result<(), FsError> {
+        Ok(())
+    }
+    fn bytes_available_read(&self) -> Result<Option<usize>, FsError> {
+        self.pipe.bytes_available_read()
+    }
+    fn is_open(&self) -> bool {
+        !self.closed.load(Ordering::SeqCst)
+    }
+}
+
+/// Polls stdin for input and returns the error and byte count of its event
+fn poll_stdin(store: &mut Store, instance: &Instance, memory: &Memory) -> (u16, u64) {
+    let ret = call(
+        store,
+        instance,
+        "poll",
+        &[Value::I32(Eventtype::FdRead as i32)],
+    );
+    assert_eq!(ret, Value::I32(Errno::Success as i32));
+    let mut event = [0u8; 32];
+    memory.view(&*store).read(320, &mut event).unwrap();
+    assert_eq!(u64::from_le_bytes(event[..8].try_into().unwrap()), 42);
+    (
+        u16::from_le_bytes(event[8..10].try_into().unwrap()),
+        u64::from_le_bytes(event[16..24].try_into().unwrap()),
+    )
+}
+
+#[test]
+fn test_non_blocking_stdin() {
+    let mut store = Store::default();
+    let (instance, memory, wasi_env) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name").stdin(Box::new(Pipe::new())),
+    );
+    let env = wasi_env.env.as_ref(&store).clone();
+
+    let ret = call(
+        &mut store,
+        &instance,
+        "set_flags",
+        &[Value::I32(Fdflags::NONBLOCK.bits() as i32)],
+    );
+    assert_eq!(ret, Value::I32(Errno::Success as i32));
+
+    // Nothing has been typed yet
+    assert_eq!(
+        call(&mut store, &instance, "read", &[]),
+        Value::I32(Errno::Again as i32)
+    );
+    assert_eq!(
+        poll_stdin(&mut store, &instance, &memory),
+        (Errno::Again as u16, 0)
+    );
+
+    env.tty_input(b"q").unwrap();
+    assert_eq!(
+        poll_stdin(&mut store, &instance, &memory),
+        (Errno::Success as u16, 1)
+    );
+    assert_eq!(
+        call(&mut store, &instance, "read", &[]),
+        Value::I32(Errno::Success as i32)
+    );
+    let view = memory.view(&store);
+    let mut len = [0u8; 4];
+    view.read(16, &mut len).unwrap();
+    assert_eq!(u32::from_le_bytes(len), 1);
+    let mut data = [0u8; 1];
+    view.read(64, &mut data).unwrap();
+    assert_eq!(&data, b"q");
+
+    // Once the input is used up the reads fail again
+    assert_eq!(
+        call(&mut store, &instance, "read", &[]),
+        Value::I32(Errno::Again as i32)
+    );
+}
+
+#[test]
+fn test_non_blocking_stdin_end_of_input() {
+    let stdin = ClosableStdin::default();
+    let closed = stdin.closed.clone();
+    let mut store = Store::default();
+    let (instance, memory, _) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name").stdin(Box::new(stdin)),
+    );
+
+    let ret = call(
+        &mut store,
+        &instance,
+        "set_flags",
+        &[Value::I32(Fdflags::NONBLOCK.bits() as i32)],
+    );
+    assert_eq!(ret, Value::I32(Errno::Success as i32));
+
+    // An empty pipe has nothing to read yet
+    assert_eq!(
+        call(&mut store, &instance, "read", &[]),
+        Value::I32(Errno::Again as i32)
+    );
+
+    // A closed one has been read to its end
+    closed.store(true, Ordering::SeqCst);
+    assert_eq!(
+        call(&mut store, &instance, "read", &[]),
+        Value::I32(Errno::Success as i32)
+    );
+    let mut len = [0u8; 4];
+    memory.view(&store).read(16, &mut len).unwrap();
+    assert_eq!(u32::from_le_bytes(len), 0);
+}
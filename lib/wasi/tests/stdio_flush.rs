@@ -0,0 +1,134 @@
+#![cfg(feature = "wasix")]
+
+use std::io::{self, Read, Seek, Write};
+use std::sync::{Arc, Mutex};
+
+use wasmer::Store;
+use wasmer_wasi::{
+    FsError, PluggableRuntimeImplementation, VirtualBus, VirtualFile, VirtualNetworking,
+    WasiRuntimeImplementation, WasiState, WasiThreadId,
+};
+
+mod common;
+use common::{call_ok, instantiate};
+
+/// Standard output that hands what is written over to a host side buffer
+/// (see `HostSink`)
+#[derive(Debug)]
+struct BufferedStdout {
+    pending: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Read for BufferedStdout {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Ok(0)
+    }
+}
+
+impl Write for BufferedStdout {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for BufferedStdout {
+    fn seek(&mut self, _pos: io::SeekFrom) -> io::Result<u64> {
+        Err(io::Error::new(io::ErrorKind::Other, "can not seek stdout"))
+    }
+}
+
+impl VirtualFile for BufferedStdout {
+    fn last_accessed(&self) -> u64 {
+        0
+    }
+    fn last_modified(&self) -> u64 {
+        0
+    }
+    fn created_time(&self) -> u64 {
+        0
+    }
+    fn size(&self) -> u64 {
+        0
+    }
+    fn set_len(&mut self, _new_size: u64) -> Result<(), FsError> {
+        Ok(())
+    }
+    fn unlink(&mut self) -> Result<(), FsError> {
+        Ok(())
+    }
+}
+
+/// Runtime that only lets the output of the program through to the host
+/// once it is flushed
+#[derive(Debug, Default)]
+struct HostSink {
+    inner: PluggableRuntimeImplementation,
+    pending: Arc<Mutex<Vec<u8>>>,
+    visible: Arc<Mutex<Vec<u8>>>,
+}
+
+impl WasiRuntimeImplementation for HostSink {
+    fn bus(&self) -> &(dyn VirtualBus) {
+        self.inner.bus()
+    }
+
+    fn networking(&self) -> &(dyn VirtualNetworking) {
+        self.inner.networking()
+    }
+
+    fn thread_generate_id(&self) -> WasiThreadId {
+        self.inner.thread_generate_id()
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        let mut pending = self.pending.lock().unwrap();
+        self.visible.lock().unwrap().append(&mut pending);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_stdio_flush() {
+    const MODULE: &[u8] = br#"
+    (module
+        (import "wasix_32v1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (import "wasix_32v1" "stdio_flush" (func $stdio_flush (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 16) "hello")
+        (func (export "write") (result i32)
+            ;; iov pointing at the 5 bytes at offset 16
+            (i32.store (i32.const 0) (i32.const 16))
+            (i32.store (i32.const 4) (i32.const 5))
+            (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8))
+        )
+        (func (export "flush") (result i32)
+            (call $stdio_flush)
+        )
+    )
+    "#;
+
+    let mut store = Store::default();
+    let runtime = HostSink::default();
+    let stdout = BufferedStdout {
+        pending: runtime.pending.clone(),
+    };
+    let visible = runtime.visible.clone();
+    let (instance, _, _) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name")
+            .stdout(Box::new(stdout))
+            .runtime(runtime),
+    );
+
+    call_ok(&mut store, &instance, "write", &[]);
+    assert!(visible.lock().unwrap().is_empty());
+
+    call_ok(&mut store, &instance, "flush", &[]);
+    assert_eq!(visible.lock().unwrap().as_slice(), b"hello");
+}
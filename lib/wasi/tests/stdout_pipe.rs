@@ -0,0 +1,118 @@
+#![cfg(feature = "wasix")]
+
+use std::io::{self, Read, Seek, Write};
+use std::sync::{Arc, Mutex};
+
+use wasmer::{Store, Value};
+use wasmer_wasi::types::wasi::Errno;
+use wasmer_wasi::{FsError, VirtualFile, WasiState};
+
+mod common;
+use common::{call, instantiate};
+
+/// Standard output piped into a consumer that goes away after reading a
+/// few lines, like `head -n 3`. Like the host's standard output the
+/// writes are buffered, so the broken pipe shows once they are flushed.
+#[derive(Debug)]
+struct Head {
+    lines: usize,
+    pending: Vec<u8>,
+    received: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Read for Head {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Ok(0)
+    }
+}
+
+impl Write for Head {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut received = self.received.lock().unwrap();
+        if received.iter().filter(|b| **b == b'\n').count() >= self.lines {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        received.append(&mut self.pending);
+        Ok(())
+    }
+}
+
+impl Seek for Head {
+    fn seek(&mut self, _pos: io::SeekFrom) -> io::Result<u64> {
+        Err(io::Error::new(io::ErrorKind::Other, "can not seek a pipe"))
+    }
+}
+
+impl VirtualFile for Head {
+    fn last_accessed(&self) -> u64 {
+        0
+    }
+    fn last_modified(&self) -> u64 {
+        0
+    }
+    fn created_time(&self) -> u64 {
+        0
+    }
+    fn size(&self) -> u64 {
+        0
+    }
+    fn set_len(&mut self, _new_size: u64) -> Result<(), FsError> {
+        Ok(())
+    }
+    fn unlink(&mut self) -> Result<(), FsError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_write_to_closed_stdout_pipe() {
+    const MODULE: &[u8] = br#"
+    (module
+        (import "wasix_32v1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 16) "y\n")
+        ;; Writes lines until a write fails and returns its error, giving up
+        ;; after a thousand lines
+        (func (export "yes") (result i32)
+            (local $errno i32)
+            (local $lines i32)
+            (i32.store (i32.const 0) (i32.const 16))
+            (i32.store (i32.const 4) (i32.const 2))
+            (loop $write
+                (local.set $errno
+                    (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))
+                (local.set $lines (i32.add (local.get $lines) (i32.const 1)))
+                (br_if $write
+                    (i32.and
+                        (i32.eqz (local.get $errno))
+                        (i32.lt_u (local.get $lines) (i32.const 1000))))
+            )
+            (local.get $errno)
+        )
+    )
+    "#;
+
+    let mut store = Store::default();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let stdout = Head {
+        lines: 3,
+        pending: Vec::new(),
+        received: received.clone(),
+    };
+    let (instance, _, _) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name").stdout(Box::new(stdout)),
+    );
+
+    assert_eq!(
+        call(&mut store, &instance, "yes", &[]),
+        Value::I32(Errno::Pipe as i32)
+    );
+    assert_eq!(received.lock().unwrap().as_slice(), b"y\ny\ny\n");
+}
@@ -0,0 +1,49 @@
+#![cfg(feature = "wasix")]
+
+use std::io::Read;
+
+use wasmer::{Store, Value};
+use wasmer_wasi::{Pipe, PluggableRuntimeImplementation, WasiState};
+
+mod common;
+use common::{call_ok, instantiate};
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+    (memory (export "memory") 1)
+    (data (i32.const 16) "hello")
+    ;; Writes the 5 bytes at offset 16 to the descriptor
+    (func (export "write") (param $fd i32) (result i32)
+        (i32.store (i32.const 0) (i32.const 16))
+        (i32.store (i32.const 4) (i32.const 5))
+        (call $fd_write (local.get $fd) (i32.const 0) (i32.const 1) (i32.const 8))
+    )
+)
+"#;
+
+#[test]
+fn test_stdout_goes_to_runtime_sink() {
+    let mut stdout = Pipe::new();
+    let mut stderr = Pipe::new();
+    let mut runtime = PluggableRuntimeImplementation::default();
+    runtime.set_stdout_sink(stdout.clone());
+    runtime.set_stderr_sink(stderr.clone());
+    let mut store = Store::default();
+    let (instance, _, _) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name").runtime(runtime),
+    );
+
+    for fd in [1, 2] {
+        call_ok(&mut store, &instance, "write", &[Value::I32(fd)]);
+    }
+
+    let mut output = String::new();
+    stdout.read_to_string(&mut output).unwrap();
+    assert_eq!(output, "hello");
+    let mut output = String::new();
+    stderr.read_to_string(&mut output).unwrap();
+    assert_eq!(output, "hello");
+}
@@ -0,0 +1,64 @@
+use wasmer::{Instance, Module, Store, Value};
+use wasmer_wasi::{wasi_stub_missing_imports, WasiState};
+
+mod common;
+use common::call;
+
+mod sys {
+    #[test]
+    fn test_missing_import_traps_when_called() {
+        super::test_missing_import_traps_when_called()
+    }
+}
+
+#[cfg(feature = "js")]
+mod js {
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_missing_import_traps_when_called() {
+        super::test_missing_import_traps_when_called()
+    }
+}
+
+fn test_missing_import_traps_when_called() {
+    let mut store = Store::default();
+    let module = Module::new(
+        &store,
+        br#"
+    (module
+        (import "wasi_snapshot_preview1" "sched_yield" (func $sched_yield (result i32)))
+        (import "wasi_snapshot_preview1" "not_implemented" (func $not_implemented (param i32) (result i32)))
+        (memory (export "memory") 1)
+        (func (export "run_past") (result i32)
+            (drop (call $sched_yield))
+            (i32.const 42)
+        )
+        (func (export "call_stub") (result i32)
+            (call $not_implemented (i32.const 1))
+        )
+    )
+    "#,
+    )
+    .unwrap();
+
+    let wasi_env = WasiState::new("command-name").finalize(&mut store).unwrap();
+    let mut import_object = wasi_env.import_object(&mut store, &module).unwrap();
+
+    // Without the stubs the module can't be instantiated at all
+    assert!(Instance::new(&mut store, &module, &import_object).is_err());
+
+    wasi_stub_missing_imports(&mut import_object, &module, &mut store);
+    let instance = Instance::new(&mut store, &module, &import_object).unwrap();
+    let memory = instance.exports.get_memory("memory").unwrap();
+    wasi_env.data_mut(&mut store).set_memory(memory.clone());
+
+    assert_eq!(call(&mut store, &instance, "run_past", &[]), Value::I32(42));
+
+    let call_stub = instance.exports.get_function("call_stub").unwrap();
+    let err = call_stub.call(&mut store, &[]).unwrap_err();
+    assert_eq!(
+        err.message(),
+        "WASI function `wasi_snapshot_preview1::not_implemented` is not implemented"
+    );
+}
@@ -0,0 +1,72 @@
+use std::io::Write;
+
+use wasmer::Store;
+use wasmer_wasi::{Pipe, SyscallCounts, WasiState};
+
+mod common;
+use common::instantiate;
+
+mod sys {
+    #[test]
+    fn test_syscall_counts() {
+        super::test_syscall_counts()
+    }
+}
+
+#[cfg(feature = "js")]
+mod js {
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_syscall_counts() {
+        super::test_syscall_counts()
+    }
+}
+
+fn test_syscall_counts() {
+    const MODULE: &[u8] = br#"
+    (module
+        (import "wasi_snapshot_preview1" "fd_read" (func $fd_read (param i32 i32 i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "sched_yield" (func $sched_yield (result i32)))
+        (memory (export "memory") 1)
+        (func $main (export "_start")
+            ;; iov pointing at a 4 bytes buffer at offset 16
+            (i32.store (i32.const 0) (i32.const 16))
+            (i32.store (i32.const 4) (i32.const 4))
+
+            ;; Echo stdin to stdout, 4 bytes at a time
+            (drop (call $fd_read (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 8)))
+            (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))
+            (drop (call $fd_read (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 8)))
+            (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))
+            (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))
+        )
+    )
+    "#;
+
+    let mut store = Store::default();
+    let mut stdin = Pipe::new();
+    stdin.write_all(b"abcdefgh").unwrap();
+    let (instance, _, wasi_env) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name")
+            .stdin(Box::new(stdin))
+            .stdout(Box::new(Pipe::new())),
+    );
+
+    let counts = SyscallCounts::default();
+    wasi_env
+        .data_mut(&mut store)
+        .set_syscall_counts(counts.clone());
+
+    let start = instance.exports.get_function("_start").unwrap();
+    start.call(&mut store, &[]).unwrap();
+
+    let counts = counts.snapshot();
+    assert_eq!(counts.len(), 2);
+    assert_eq!(counts["fd_read"], 2);
+    assert_eq!(counts["fd_write"], 3);
+    assert!(!counts.contains_key("sched_yield"));
+}
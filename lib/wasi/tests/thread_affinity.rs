@@ -0,0 +1,72 @@
+#![cfg(all(feature = "wasix", target_os = "linux"))]
+
+use wasmer::{Store, Value};
+use wasmer_wasi::types::wasi::Errno;
+use wasmer_wasi::WasiState;
+
+mod common;
+use common::{call, call_ok, instantiate};
+
+#[test]
+fn test_thread_affinity() {
+    const MODULE: &[u8] = br#"
+    (module
+        (import "wasix_32v1" "thread_affinity_set" (func $set (param i32 i32) (result i32)))
+        (import "wasix_32v1" "thread_affinity_get" (func $get (param i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        ;; The mask lives at offset 16, its length at offset 0
+        (func (export "set") (param $len i32) (result i32)
+            (call $set (i32.const 16) (local.get $len))
+        )
+        (func (export "get") (result i32)
+            (call $get (i32.const 16) (i32.const 1024) (i32.const 0))
+        )
+    )
+    "#;
+
+    let mut store = Store::default();
+    let (instance, memory, _) =
+        instantiate(&mut store, MODULE, &mut WasiState::new("command-name"));
+
+    // Run this thread on the first CPU it is allowed to run on
+    let original = {
+        call_ok(&mut store, &instance, "get", &[]);
+        let view = memory.view(&store);
+        let mut len = [0u8; 4];
+        view.read(0, &mut len).unwrap();
+        let mut mask = vec![0u8; u32::from_le_bytes(len) as usize];
+        view.read(16, &mut mask).unwrap();
+        mask
+    };
+    let cpu = original
+        .iter()
+        .enumerate()
+        .find(|(_, byte)| **byte != 0)
+        .map(|(i, byte)| i * 8 + byte.trailing_zeros() as usize)
+        .unwrap();
+    let mut single = vec![0u8; original.len()];
+    single[cpu / 8] = 1 << (cpu % 8);
+
+    memory.view(&store).write(16, &single).unwrap();
+    let len = Value::I32(single.len() as i32);
+    call_ok(&mut store, &instance, "set", &[len]);
+
+    call_ok(&mut store, &instance, "get", &[]);
+    let mut mask = vec![0u8; single.len()];
+    memory.view(&store).read(16, &mut mask).unwrap();
+    assert_eq!(mask, single);
+
+    // A CPU the machine doesn't have is rejected
+    let mut invalid = vec![0u8; 1024];
+    invalid[1023] = 0x80;
+    memory.view(&store).write(16, &invalid).unwrap();
+    assert_eq!(
+        call(&mut store, &instance, "set", &[Value::I32(1024)]),
+        Value::I32(Errno::Inval as i32)
+    );
+
+    // Give the thread back the CPUs it had
+    memory.view(&store).write(16, &original).unwrap();
+    let len = Value::I32(original.len() as i32);
+    call_ok(&mut store, &instance, "set", &[len]);
+}
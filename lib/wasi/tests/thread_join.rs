@@ -0,0 +1,251 @@
+#![cfg(feature = "wasix")]
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use wasmer::{Instance, Store, Value};
+use wasmer_wasi::types::wasi::Errno;
+use wasmer_wasi::{
+    PluggableRuntimeImplementation, VirtualBus, VirtualNetworking, WasiEnv, WasiError,
+    WasiFunctionEnv, WasiRuntimeImplementation, WasiState, WasiThreadError, WasiThreadId,
+    THREAD_ABORTED_EXIT_CODE,
+};
+
+mod common;
+use common::{call, call_ok, instantiate, instantiate_with_env};
+
+/// Runtime that holds on to the threads it is asked to spawn until the test
+/// decides how they end, the threads then run the guest code in a store of
+/// their own
+#[derive(Default)]
+struct DeferredThreads {
+    inner: PluggableRuntimeImplementation,
+    pending: Arc<Mutex<Vec<Box<dyn FnOnce() + Send + 'static>>>>,
+}
+
+impl fmt::Debug for DeferredThreads {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeferredThreads")
+            .field("pending", &self.pending.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl WasiRuntimeImplementation for DeferredThreads {
+    fn bus(&self) -> &(dyn VirtualBus) {
+        self.inner.bus()
+    }
+
+    fn networking(&self) -> &(dyn VirtualNetworking) {
+        self.inner.networking()
+    }
+
+    fn thread_generate_id(&self) -> WasiThreadId {
+        self.inner.thread_generate_id()
+    }
+
+    fn thread_spawn(
+        &self,
+        callback: Box<dyn FnOnce() + Send + 'static>,
+    ) -> Result<(), WasiThreadError> {
+        self.pending.lock().unwrap().push(callback);
+        Ok(())
+    }
+
+    fn thread_run(&self, env: WasiEnv, entry: &str, user_data: u64) -> Result<(), WasiError> {
+        let mut store = Store::default();
+        let wasi_env = WasiFunctionEnv::new(&mut store, env);
+        let (instance, _) = instantiate_with_env(&mut store, MODULE, &wasi_env, None);
+
+        let entry = instance.exports.get_function(entry).unwrap();
+        match entry.call(&mut store, &[Value::I64(user_data as i64)]) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(err.downcast::<WasiError>().unwrap()),
+        }
+    }
+}
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "thread_spawn" (func $thread_spawn (param i32 i32 i64 i32 i32) (result i32)))
+    (import "wasix_32v1" "thread_join" (func $thread_join (param i32) (result i32)))
+    (import "wasix_32v1" "thread_join_v2" (func $thread_join_v2 (param i32 i32) (result i32)))
+    (import "wasix_32v1" "thread_detach" (func $thread_detach (param i32) (result i32)))
+    (import "wasix_32v1" "proc_exit" (func $proc_exit (param i32)))
+    (memory (export "memory") 1)
+    (data (i32.const 0) "_thread_start")
+    ;; The thread exits with the code it was spawned with
+    (func (export "_thread_start") (param $exit_code i64)
+        (call $proc_exit (i32.wrap_i64 (local.get $exit_code)))
+    )
+    ;; The handle of the thread is written at offset 16
+    (func (export "spawn") (param $exit_code i64) (result i32)
+        (call $thread_spawn (i32.const 0) (i32.const 13) (local.get $exit_code) (i32.const 0) (i32.const 16))
+    )
+    (func (export "tid") (result i32)
+        (i32.load (i32.const 16))
+    )
+    (func (export "join_v1") (param $tid i32) (result i32)
+        (call $thread_join (local.get $tid))
+    )
+    (func (export "detach") (param $tid i32) (result i32)
+        (call $thread_detach (local.get $tid))
+    )
+    ;; The exit code is written at offset 20
+    (func (export "join") (param $tid i32) (result i32)
+        (call $thread_join_v2 (local.get $tid) (i32.const 20))
+    )
+    (func (export "exit_code") (result i32)
+        (i32.load (i32.const 20))
+    )
+)
+"#;
+
+struct Guest {
+    store: Store,
+    instance: Instance,
+    pending: Arc<Mutex<Vec<Box<dyn FnOnce() + Send + 'static>>>>,
+}
+
+impl Guest {
+    fn new() -> Self {
+        let mut store = Store::default();
+        let runtime = DeferredThreads::default();
+        let pending = runtime.pending.clone();
+        let (instance, _, _) = instantiate(
+            &mut store,
+            MODULE,
+            WasiState::new("command-name").runtime(runtime),
+        );
+        Self {
+            store,
+            instance,
+            pending,
+        }
+    }
+
+    fn call(&mut self, name: &str, params: &[Value]) -> Value {
+        call(&mut self.store, &self.instance, name, params)
+    }
+
+    /// Spawns a thread that exits with `exit_code` and returns its handle
+    /// along with the task the runtime was given for it
+    fn spawn(&mut self, exit_code: i64) -> (Value, Box<dyn FnOnce() + Send + 'static>) {
+        call_ok(
+            &mut self.store,
+            &self.instance,
+            "spawn",
+            &[Value::I64(exit_code)],
+        );
+        let task = self.pending.lock().unwrap().pop().unwrap();
+        (self.call("tid", &[]), task)
+    }
+
+    /// Joins the thread and returns the error code along with the exit code
+    fn join(&mut self, tid: Value) -> (Value, Value) {
+        let ret = self.call("join", &[tid]);
+        (ret, self.call("exit_code", &[]))
+    }
+}
+
+#[test]
+fn test_thread_join_returns_exit_code() {
+    let mut guest = Guest::new();
+    let (tid, task) = guest.spawn(0);
+    task();
+    assert_eq!(
+        guest.join(tid),
+        (Value::I32(Errno::Success as i32), Value::I32(0))
+    );
+}
+
+#[test]
+fn test_thread_join_returns_exit_code_of_proc_exit() {
+    let mut guest = Guest::new();
+    let (tid, task) = guest.spawn(3);
+    task();
+    assert_eq!(
+        guest.join(tid),
+        (Value::I32(Errno::Success as i32), Value::I32(3))
+    );
+}
+
+#[test]
+fn test_thread_join_reports_aborted_thread() {
+    let mut guest = Guest::new();
+    let (tid, task) = guest.spawn(0);
+
+    // The task goes down with the runtime thread that was meant to run it
+    let panicked = std::thread::spawn(move || {
+        let _task = task;
+        panic!("runtime thread died");
+    })
+    .join();
+    assert!(panicked.is_err());
+
+    assert_eq!(
+        guest.join(tid),
+        (
+            Value::I32(Errno::Success as i32),
+            Value::I32(THREAD_ABORTED_EXIT_CODE as i32)
+        )
+    );
+}
+
+#[test]
+fn test_thread_join_unknown_thread() {
+    let mut guest = Guest::new();
+    let srch = Value::I32(Errno::Srch as i32);
+
+    // Never spawned
+    assert_eq!(guest.join(Value::I32(1234)).0, srch);
+
+    // Already joined
+    let (tid, task) = guest.spawn(0);
+    task();
+    assert_eq!(guest.join(tid.clone()).0, Value::I32(Errno::Success as i32));
+    assert_eq!(guest.join(tid).0, srch);
+}
+
+#[test]
+fn test_thread_join_v1_unknown_thread() {
+    let mut guest = Guest::new();
+    let success = Value::I32(Errno::Success as i32);
+    let srch = Value::I32(Errno::Srch as i32);
+
+    // Never spawned
+    assert_eq!(guest.call("join_v1", &[Value::I32(1234)]), srch);
+    assert_eq!(guest.call("join_v1", &[Value::I32(0)]), srch);
+
+    // Finished before being joined, and then already joined
+    let (tid, task) = guest.spawn(0);
+    task();
+    assert_eq!(guest.call("join_v1", &[tid.clone()]), success);
+    assert_eq!(guest.call("join_v1", &[tid.clone()]), success);
+
+    // Only threads that were spawned count as finished
+    let next = Value::I32(tid.unwrap_i32() + 1);
+    assert_eq!(guest.call("join_v1", &[next]), srch);
+}
+
+#[test]
+fn test_thread_detach() {
+    let mut guest = Guest::new();
+    let success = Value::I32(Errno::Success as i32);
+    let srch = Value::I32(Errno::Srch as i32);
+
+    // A running thread leaves nothing to join once it exits
+    let (tid, task) = guest.spawn(3);
+    assert_eq!(guest.call("detach", &[tid.clone()]), success);
+    assert_eq!(guest.call("detach", &[tid.clone()]), srch);
+    task();
+    assert_eq!(guest.join(tid).0, srch);
+
+    // Neither does a thread that already exited
+    let (tid, task) = guest.spawn(3);
+    task();
+    assert_eq!(guest.call("detach", &[tid.clone()]), success);
+    assert_eq!(guest.join(tid).0, srch);
+
+    assert_eq!(guest.call("detach", &[Value::I32(1234)]), srch);
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "wasix")]
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use wasmer::{Store, Value};
+use wasmer_wasi::types::wasi::Errno;
+use wasmer_wasi::{
+    PluggableRuntimeImplementation, VirtualBus, VirtualNetworking, WasiRuntimeImplementation,
+    WasiState, WasiThreadError, WasiThreadId,
+};
+
+mod common;
+use common::{call, instantiate};
+
+/// Runtime that holds on to the threads it is asked to spawn, so they keep
+/// counting as running until the test runs them
+#[derive(Default)]
+struct DeferredThreads {
+    inner: PluggableRuntimeImplementation,
+    pending: Arc<Mutex<Vec<Box<dyn FnOnce() + Send + 'static>>>>,
+}
+
+impl fmt::Debug for DeferredThreads {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeferredThreads")
+            .field("pending", &self.pending.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl WasiRuntimeImplementation for DeferredThreads {
+    fn bus(&self) -> &(dyn VirtualBus) {
+        self.inner.bus()
+    }
+
+    fn networking(&self) -> &(dyn VirtualNetworking) {
+        self.inner.networking()
+    }
+
+    fn thread_generate_id(&self) -> WasiThreadId {
+        self.inner.thread_generate_id()
+    }
+
+    fn thread_spawn(
+        &self,
+        callback: Box<dyn FnOnce() + Send + 'static>,
+    ) -> Result<(), WasiThreadError> {
+        self.pending.lock().unwrap().push(callback);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_thread_limit() {
+    const MODULE: &[u8] = br#"
+    (module
+        (import "wasix_32v1" "thread_spawn" (func $thread_spawn (param i32 i32 i64 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 0) "_thread_start")
+        (func (export "spawn") (result i32)
+            (call $thread_spawn (i32.const 0) (i32.const 13) (i64.const 0) (i32.const 0) (i32.const 16))
+        )
+    )
+    "#;
+
+    let mut store = Store::default();
+    let runtime = DeferredThreads::default();
+    let pending = runtime.pending.clone();
+    let (instance, _, _) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name")
+            .max_threads(4)
+            .runtime(runtime),
+    );
+
+    let mut spawn = || call(&mut store, &instance, "spawn", &[]);
+    let success = Value::I32(Errno::Success as i32);
+    let again = Value::I32(Errno::Again as i32);
+
+    for _ in 0..4 {
+        assert_eq!(spawn(), success);
+    }
+    assert_eq!(spawn(), again);
+    assert_eq!(spawn(), again);
+    assert_eq!(pending.lock().unwrap().len(), 4);
+
+    // Once a thread has exited there is room for another one
+    let thread = pending.lock().unwrap().pop().unwrap();
+    thread();
+    assert_eq!(spawn(), success);
+    assert_eq!(spawn(), again);
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "wasix")]
+
+use std::time::{Duration, Instant};
+
+use wasmer::{Store, Value};
+use wasmer_wasi::WasiState;
+
+mod common;
+use common::{call_ok, instantiate};
+
+#[test]
+fn test_short_thread_sleep() {
+    const MODULE: &[u8] = br#"
+    (module
+        (import "wasix_32v1" "thread_sleep" (func $thread_sleep (param i64) (result i32)))
+        (memory (export "memory") 1)
+        (func (export "sleep") (param i64) (result i32)
+            (call $thread_sleep (local.get 0))
+        )
+    )
+    "#;
+
+    let mut store = Store::default();
+    let (instance, _, _) = instantiate(&mut store, MODULE, &mut WasiState::new("command-name"));
+
+    let duration = Duration::from_micros(100);
+    let mut elapsed = (0..11)
+        .map(|_| {
+            let start = Instant::now();
+            let params = [Value::I64(duration.as_nanos() as i64)];
+            call_ok(&mut store, &instance, "sleep", &params);
+            start.elapsed()
+        })
+        .collect::<Vec<_>>();
+    elapsed.sort();
+
+    // Sleeps are never cut short, and don't get rounded up to the
+    // granularity used to wait for long sleeps either
+    assert!(elapsed[0] >= duration);
+    assert!(elapsed[elapsed.len() / 2] < Duration::from_millis(5));
+}
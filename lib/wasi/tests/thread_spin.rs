@@ -0,0 +1,124 @@
+#![cfg(feature = "wasix")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use wasmer::{Store, Value};
+use wasmer_wasi::types::wasi::{Errno, Signal};
+use wasmer_wasi::{
+    PluggableRuntimeImplementation, VirtualBus, VirtualNetworking, WasiError,
+    WasiRuntimeImplementation, WasiState, WasiThreadId,
+};
+
+mod common;
+use common::{call, instantiate};
+
+/// Runtime with a chosen spin limit, counting how many times threads went
+/// idle (which only happens when they sleep)
+#[derive(Debug)]
+struct SpinRuntime {
+    inner: PluggableRuntimeImplementation,
+    spin_limit: Duration,
+    yields: Arc<AtomicUsize>,
+}
+
+impl WasiRuntimeImplementation for SpinRuntime {
+    fn bus(&self) -> &(dyn VirtualBus) {
+        self.inner.bus()
+    }
+
+    fn networking(&self) -> &(dyn VirtualNetworking) {
+        self.inner.networking()
+    }
+
+    fn thread_generate_id(&self) -> WasiThreadId {
+        self.inner.thread_generate_id()
+    }
+
+    fn thread_spin_limit(&self) -> Duration {
+        self.spin_limit
+    }
+
+    fn yield_now(&self, _id: WasiThreadId) -> Result<(), WasiError> {
+        self.yields.fetch_add(1, Ordering::SeqCst);
+        std::thread::yield_now();
+        Ok(())
+    }
+}
+
+/// Calls `thread_spin()` for `duration`, with a signal pending if `interrupt`
+/// is set, and returns its result, how long it took and whether the thread
+/// went to sleep
+fn spin(spin_limit: Duration, duration: Duration, interrupt: bool) -> (Value, Duration, bool) {
+    const MODULE: &[u8] = br#"
+    (module
+        (import "wasix_32v1" "thread_spin" (func $thread_spin (param i64) (result i32)))
+        (memory (export "memory") 1)
+        (func (export "spin") (param i64) (result i32)
+            (call $thread_spin (local.get 0))
+        )
+    )
+    "#;
+
+    let mut store = Store::default();
+    let yields = Arc::new(AtomicUsize::new(0));
+    let runtime = SpinRuntime {
+        inner: PluggableRuntimeImplementation::default(),
+        spin_limit,
+        yields: yields.clone(),
+    };
+    let (instance, _, wasi_env) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name").runtime(runtime),
+    );
+    if interrupt {
+        wasi_env.env.as_ref(&store).signal(Signal::Sigint);
+    }
+
+    let start = Instant::now();
+    let params = [Value::I64(duration.as_nanos() as i64)];
+    let ret = call(&mut store, &instance, "spin", &params);
+    let elapsed = start.elapsed();
+    (ret, elapsed, yields.load(Ordering::SeqCst) > 0)
+}
+
+#[test]
+fn test_thread_spin_returns_promptly() {
+    let limit = Duration::from_micros(50);
+    let duration = Duration::from_micros(20);
+    let (ret, elapsed, slept) = spin(limit, duration, false);
+    assert_eq!(ret, Value::I32(Errno::Success as i32));
+    assert!(elapsed >= duration);
+    assert!(elapsed < Duration::from_millis(10));
+    assert!(!slept);
+}
+
+#[test]
+fn test_thread_spin_respects_limit() {
+    // Longer than the limit, the rest is slept
+    let limit = Duration::from_micros(50);
+    let duration = Duration::from_millis(2);
+    let (ret, elapsed, slept) = spin(limit, duration, false);
+    assert_eq!(ret, Value::I32(Errno::Success as i32));
+    assert!(elapsed >= duration);
+    assert!(slept);
+
+    // No spinning at all
+    let duration = Duration::from_micros(20);
+    let (ret, elapsed, slept) = spin(Duration::ZERO, duration, false);
+    assert_eq!(ret, Value::I32(Errno::Success as i32));
+    assert!(elapsed >= duration);
+    assert!(slept);
+}
+
+#[test]
+fn test_thread_spin_is_interrupted_by_signals() {
+    // Both the spinning and the sleeping give up once a signal is pending
+    for limit in [Duration::from_secs(10), Duration::ZERO] {
+        let (ret, elapsed, _) = spin(limit, Duration::from_secs(10), true);
+        assert_eq!(ret, Value::I32(Errno::Intr as i32));
+        assert!(elapsed < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,201 @@
+#![cfg(feature = "wasix")]
+
+use std::io::{self, Read};
+use std::sync::{Arc, Mutex};
+
+use wasmer::{Instance, Store, Value};
+use wasmer_wasi::types::wasi::Errno;
+use wasmer_wasi::{
+    Pipe, PluggableRuntimeImplementation, VirtualBus, VirtualNetworking, WasiRuntimeImplementation,
+    WasiState, WasiThreadId, WasiTtyClear, WasiTtyState,
+};
+
+mod common;
+use common::call;
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "tty_clear" (func $tty_clear (param i32) (result i32)))
+    (import "wasix_32v1" "tty_move_cursor" (func $tty_move_cursor (param i32 i32) (result i32)))
+    (memory (export "memory") 1)
+    (func (export "clear") (param $what i32) (result i32)
+        (call $tty_clear (local.get $what))
+    )
+    (func (export "move_cursor") (param $row i32) (param $col i32) (result i32)
+        (call $tty_move_cursor (local.get $row) (local.get $col))
+    )
+)
+"#;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Request {
+    Clear(WasiTtyClear),
+    MoveCursor(u32, u32),
+}
+
+/// Terminal frontend that handles the requests itself rather than reading
+/// escape codes
+#[derive(Debug, Default)]
+struct WebTerminal {
+    inner: PluggableRuntimeImplementation,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl WasiRuntimeImplementation for WebTerminal {
+    fn bus(&self) -> &(dyn VirtualBus) {
+        self.inner.bus()
+    }
+
+    fn networking(&self) -> &(dyn VirtualNetworking) {
+        self.inner.networking()
+    }
+
+    fn thread_generate_id(&self) -> WasiThreadId {
+        self.inner.thread_generate_id()
+    }
+
+    fn tty_get(&self) -> WasiTtyState {
+        WasiTtyState {
+            stdout_tty: true,
+            ..self.inner.tty_get()
+        }
+    }
+
+    fn stdout(&self, _data: &[u8]) -> io::Result<()> {
+        panic!("no escape codes should be written");
+    }
+
+    fn tty_clear(&self, clear: WasiTtyClear) -> io::Result<()> {
+        self.requests.lock().unwrap().push(Request::Clear(clear));
+        Ok(())
+    }
+
+    fn tty_move_cursor(&self, row: u32, col: u32) -> io::Result<()> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push(Request::MoveCursor(row, col));
+        Ok(())
+    }
+}
+
+fn instantiate<R>(store: &mut Store, runtime: R) -> Instance
+where
+    R: WasiRuntimeImplementation + Send + Sync + 'static,
+{
+    let (instance, _, _) = common::instantiate(
+        store,
+        MODULE,
+        WasiState::new("command-name").runtime(runtime),
+    );
+    instance
+}
+
+/// Runtime whose standard output goes to `stdout`, a terminal or not
+fn sink_runtime(stdout: &Pipe, stdout_tty: bool) -> PluggableRuntimeImplementation {
+    let mut runtime = PluggableRuntimeImplementation::default();
+    runtime.set_stdout_sink(stdout.clone());
+    runtime.tty_set(WasiTtyState {
+        stdout_tty,
+        ..runtime.tty_get()
+    });
+    runtime
+}
+
+#[test]
+fn test_tty_clear_writes_escape_codes() {
+    let mut stdout = Pipe::new();
+    let runtime = sink_runtime(&stdout, true);
+    let mut store = Store::default();
+    let instance = instantiate(&mut store, runtime);
+    let success = Value::I32(Errno::Success as i32);
+
+    assert_eq!(
+        call(&mut store, &instance, "clear", &[Value::I32(0)]),
+        success
+    );
+    assert_eq!(
+        call(&mut store, &instance, "clear", &[Value::I32(1)]),
+        success
+    );
+    assert_eq!(
+        call(
+            &mut store,
+            &instance,
+            "move_cursor",
+            &[Value::I32(4), Value::I32(0)]
+        ),
+        success
+    );
+
+    let mut output = String::new();
+    stdout.read_to_string(&mut output).unwrap();
+    assert_eq!(output, "\x1B[H\x1B[2J\x1B[3J\x1B[5;1H");
+}
+
+#[test]
+fn test_tty_clear_reaches_custom_terminal() {
+    let runtime = WebTerminal::default();
+    let requests = runtime.requests.clone();
+    let mut store = Store::default();
+    let instance = instantiate(&mut store, runtime);
+    let success = Value::I32(Errno::Success as i32);
+
+    assert_eq!(
+        call(&mut store, &instance, "clear", &[Value::I32(1)]),
+        success
+    );
+    assert_eq!(
+        call(&mut store, &instance, "clear", &[Value::I32(0)]),
+        success
+    );
+    assert_eq!(
+        call(
+            &mut store,
+            &instance,
+            "move_cursor",
+            &[Value::I32(2), Value::I32(7)]
+        ),
+        success
+    );
+    assert_eq!(
+        call(&mut store, &instance, "clear", &[Value::I32(2)]),
+        Value::I32(Errno::Inval as i32)
+    );
+
+    assert_eq!(
+        *requests.lock().unwrap(),
+        vec![
+            Request::Clear(WasiTtyClear::Scrollback),
+            Request::Clear(WasiTtyClear::Screen),
+            Request::MoveCursor(2, 7),
+        ]
+    );
+}
+
+#[test]
+fn test_tty_clear_skipped_without_terminal() {
+    let mut stdout = Pipe::new();
+    let runtime = sink_runtime(&stdout, false);
+    let mut store = Store::default();
+    let instance = instantiate(&mut store, runtime);
+    let success = Value::I32(Errno::Success as i32);
+
+    assert_eq!(
+        call(&mut store, &instance, "clear", &[Value::I32(0)]),
+        success
+    );
+    assert_eq!(
+        call(
+            &mut store,
+            &instance,
+            "move_cursor",
+            &[Value::I32(4), Value::I32(0)]
+        ),
+        success
+    );
+
+    let mut output = String::new();
+    stdout.read_to_string(&mut output).unwrap();
+    assert_eq!(output, "");
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "wasix")]
+
+use std::io::Read;
+
+use wasmer::{Instance, Memory, Store};
+use wasmer_wasi::{
+    Pipe, PluggableRuntimeImplementation, WasiRuntimeImplementation, WasiState, WasiTtyState,
+};
+
+mod common;
+use common::{call_ok, instantiate};
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "fd_read" (func $fd_read (param i32 i32 i32 i32) (result i32)))
+    (memory (export "memory") 1)
+    ;; Reads up to 64 bytes into offset 64, the length is written at offset 16
+    (func (export "read") (result i32)
+        (i32.store (i32.const 0) (i32.const 64))
+        (i32.store (i32.const 4) (i32.const 64))
+        (call $fd_read (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 16))
+    )
+)
+"#;
+
+/// Lets the program read from its standard input and returns what it got
+fn read_stdin(store: &mut Store, instance: &Instance, memory: &Memory) -> Vec<u8> {
+    call_ok(store, instance, "read", &[]);
+    let view = memory.view(&*store);
+    let mut len = [0u8; 4];
+    view.read(16, &mut len).unwrap();
+    let mut data = vec![0u8; u32::from_le_bytes(len) as usize];
+    view.read(64, &mut data).unwrap();
+    data
+}
+
+#[test]
+fn test_tty_input_reaches_stdin() {
+    let mut stdout = Pipe::new();
+    let mut runtime = PluggableRuntimeImplementation::default();
+    runtime.set_stdout_sink(stdout.clone());
+    runtime.tty_set(WasiTtyState {
+        stdout_tty: true,
+        ..runtime.tty_get()
+    });
+
+    let mut store = Store::default();
+    let (instance, memory, wasi_env) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name")
+            .stdin(Box::new(Pipe::new()))
+            .runtime(runtime),
+    );
+    let env = wasi_env.env.as_ref(&store).clone();
+    let mut echoed = || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).unwrap();
+        output
+    };
+
+    // Typed input is echoed in cooked mode
+    env.tty_input(b"ls -l\r").unwrap();
+    assert_eq!(read_stdin(&mut store, &instance, &memory), b"ls -l\r");
+    assert_eq!(echoed(), b"ls -l\r");
+
+    // Keys come through exactly in raw mode, without being echoed
+    env.tty_raw_mode();
+    env.tty_input(b"\x1B[A\x03q").unwrap();
+    assert_eq!(read_stdin(&mut store, &instance, &memory), b"\x1B[A\x03q");
+    assert_eq!(echoed(), b"");
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "wasix")]
+
+use std::io::Read;
+
+use wasmer::{Store, Value};
+use wasmer_wasi::types::wasi::Errno;
+use wasmer_wasi::{
+    Pipe, PluggableRuntimeImplementation, WasiRuntimeImplementation, WasiState, WasiTtyState,
+};
+
+mod common;
+use common::{call, instantiate};
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "tty_set_mode" (func $tty_set_mode (param i32 i32) (result i32)))
+    (memory (export "memory") 1)
+    (func (export "set_mode") (param $mode i32) (param $enabled i32) (result i32)
+        (call $tty_set_mode (local.get $mode) (local.get $enabled))
+    )
+)
+"#;
+
+fn read(pipe: &mut Pipe) -> String {
+    let mut output = String::new();
+    pipe.read_to_string(&mut output).unwrap();
+    output
+}
+
+#[test]
+fn test_tty_modes_are_turned_off_on_exit() {
+    let mut stdout = Pipe::new();
+    let mut runtime = PluggableRuntimeImplementation::default();
+    runtime.set_stdout_sink(stdout.clone());
+    runtime.tty_set(WasiTtyState {
+        stdout_tty: true,
+        ..runtime.tty_get()
+    });
+
+    let mut store = Store::default();
+    let (instance, _, wasi_env) = instantiate(
+        &mut store,
+        MODULE,
+        WasiState::new("command-name").runtime(runtime),
+    );
+
+    let mut set_mode = |mode: i32, enabled: i32| {
+        let params = [Value::I32(mode), Value::I32(enabled)];
+        call(&mut store, &instance, "set_mode", &params)
+    };
+    let success = Value::I32(Errno::Success as i32);
+
+    // Bracketed paste, mouse clicks and SGR mouse reports on, clicks off
+    assert_eq!(set_mode(0, 1), success);
+    assert_eq!(set_mode(1, 1), success);
+    assert_eq!(set_mode(4, 1), success);
+    assert_eq!(set_mode(1, 0), success);
+    assert_eq!(set_mode(9, 1), Value::I32(Errno::Inval as i32));
+    assert_eq!(
+        read(&mut stdout),
+        "\x1B[?2004h\x1B[?1000h\x1B[?1006h\x1B[?1000l"
+    );
+
+    // The modes that are still on are turned off once the program is done
+    let env = wasi_env.env.as_ref(&store).clone();
+    env.tty_restore();
+    assert_eq!(read(&mut stdout), "\x1B[?1006l\x1B[?2004l");
+    env.tty_restore();
+    assert_eq!(read(&mut stdout), "");
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "wasix")]
+
+use wasmer::{Store, Value};
+use wasmer_wasi::{WasiState, WasiTtyState};
+
+mod common;
+use common::{call_ok, instantiate};
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "tty_set_raw" (func $tty_set_raw (param i32) (result i32)))
+    (memory (export "memory") 1)
+    (func (export "set_raw") (param $raw i32) (result i32)
+        (call $tty_set_raw (local.get $raw))
+    )
+    (func (export "crash")
+        (drop (call $tty_set_raw (i32.const 1)))
+        unreachable
+    )
+)
+"#;
+
+#[test]
+fn test_tty_raw_mode_is_restored() {
+    let mut store = Store::default();
+    let (instance, _, wasi_env) =
+        instantiate(&mut store, MODULE, &mut WasiState::new("command-name"));
+
+    // Some state other than the default, which cooked mode alone would lose
+    let cooked = WasiTtyState {
+        stdin_tty: true,
+        echo: true,
+        line_buffered: false,
+        ..Default::default()
+    };
+    let env = wasi_env.env.as_ref(&store).clone();
+    env.runtime().tty_set(cooked.clone());
+
+    // Echo and line buffering go off together
+    call_ok(&mut store, &instance, "set_raw", &[Value::I32(1)]);
+    let tty = env.runtime().tty_get();
+    assert!(!tty.echo && !tty.line_buffered);
+    assert!(tty.stdin_tty);
+
+    call_ok(&mut store, &instance, "set_raw", &[Value::I32(0)]);
+    assert_eq!(env.runtime().tty_get(), cooked);
+
+    // The program traps while in raw mode, the host puts the TTY back
+    let crash = instance.exports.get_function("crash").unwrap();
+    assert!(crash.call(&mut store, &[]).is_err());
+    assert!(!env.runtime().tty_get().echo);
+    env.tty_restore();
+    assert_eq!(env.runtime().tty_get(), cooked);
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "wasix")]
+
+use std::time::{Duration, Instant};
+
+use wasmer::{Store, Value};
+use wasmer_wasi::types::wasi::{Errno, Signal};
+use wasmer_wasi::WasiState;
+
+mod common;
+use common::{call, instantiate};
+
+const MODULE: &[u8] = br#"
+(module
+    (import "wasix_32v1" "thread_sleep" (func $thread_sleep (param i64) (result i32)))
+    (import "wasix_32v1" "tty_get" (func $tty_get (param i32) (result i32)))
+    (memory (export "memory") 1)
+    (func (export "sleep") (result i32)
+        (call $thread_sleep (i64.const 10000000000))
+    )
+    (func (export "nap") (result i32)
+        (call $thread_sleep (i64.const 1000000))
+    )
+    ;; The columns and rows are read from offsets 0 and 4
+    (func (export "cols") (result i32)
+        (drop (call $tty_get (i32.const 0)))
+        (i32.load (i32.const 0))
+    )
+    (func (export "rows") (result i32)
+        (drop (call $tty_get (i32.const 0)))
+        (i32.load (i32.const 4))
+    )
+)
+"#;
+
+#[test]
+fn test_tty_resize_interrupts_and_reports_size() {
+    let mut store = Store::default();
+    let (instance, _, wasi_env) =
+        instantiate(&mut store, MODULE, &mut WasiState::new("command-name"));
+
+    assert_eq!(call(&mut store, &instance, "cols", &[]), Value::I32(80));
+    assert_eq!(call(&mut store, &instance, "rows", &[]), Value::I32(25));
+
+    let env = wasi_env.env.as_ref(&store).clone();
+    let resizer = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(100));
+        env.tty_resize(120, 40);
+    });
+    let start = Instant::now();
+    assert_eq!(
+        call(&mut store, &instance, "sleep", &[]),
+        Value::I32(Errno::Intr as i32)
+    );
+    assert!(start.elapsed() < Duration::from_secs(5));
+    resizer.join().unwrap();
+
+    // the signal only interrupts one call, and is still there to be taken
+    assert_eq!(
+        call(&mut store, &instance, "nap", &[]),
+        Value::I32(Errno::Success as i32)
+    );
+    let env = wasi_env.env.as_ref(&store);
+    assert!(matches!(env.take_signal(), Some(Signal::Sigwinch)));
+    assert!(env.take_signal().is_none());
+
+    // Resizes that weren't taken yet are reported once
+    env.tty_resize(100, 30);
+    env.tty_resize(120, 40);
+    assert!(matches!(env.take_signal(), Some(Signal::Sigwinch)));
+    assert!(env.take_signal().is_none());
+
+    assert_eq!(call(&mut store, &instance, "cols", &[]), Value::I32(120));
+    assert_eq!(call(&mut store, &instance, "rows", &[]), Value::I32(40));
+}
@@ -0,0 +1,138 @@
+use wasmer::{Module, Store};
+use wasmer_wasi::{Pipe, WasiError, WasiState};
+
+mod common;
+use common::instantiate;
+
+mod sys {
+    #[test]
+    fn test_legacy_import_names() {
+        super::test_legacy_import_names()
+    }
+
+    #[test]
+    fn test_preview1_import_names() {
+        super::test_preview1_import_names()
+    }
+
+    #[test]
+    fn test_mixed_import_names() {
+        super::test_mixed_import_names()
+    }
+
+    #[test]
+    fn test_preview2_imports_are_reported() {
+        super::test_preview2_imports_are_reported()
+    }
+}
+
+#[cfg(feature = "js")]
+mod js {
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_legacy_import_names() {
+        super::test_legacy_import_names()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_preview1_import_names() {
+        super::test_preview1_import_names()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_mixed_import_names() {
+        super::test_mixed_import_names()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_preview2_imports_are_reported() {
+        super::test_preview2_imports_are_reported()
+    }
+}
+
+/// Instantiates a module writing "hello" to stdout with `fd_write`, using
+/// the given import declarations, and runs it
+fn run_hello(imports: &str) -> String {
+    let mut store = Store::default();
+    let wat = format!(
+        r#"
+    (module
+        {}
+        (memory 1)
+        (export "memory" (memory 0))
+        (data (i32.const 8) "hello")
+        (func $main (export "_start")
+            (i32.store (i32.const 0) (i32.const 8))
+            (i32.store (i32.const 4) (i32.const 5))
+            (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 20))
+            drop
+        )
+    )
+    "#,
+        imports
+    );
+
+    let mut stdout = Pipe::default();
+    let (instance, _, _) = instantiate(
+        &mut store,
+        wat.as_bytes(),
+        WasiState::new("command-name").stdout(Box::new(stdout.clone())),
+    );
+
+    let start = instance.exports.get_function("_start").unwrap();
+    start.call(&mut store, &[]).unwrap();
+
+    let mut out = String::new();
+    std::io::Read::read_to_string(&mut stdout, &mut out).unwrap();
+    out
+}
+
+fn test_legacy_import_names() {
+    let out = run_hello(
+        r#"(import "wasi_unstable" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))"#,
+    );
+    assert_eq!(out, "hello");
+}
+
+fn test_preview1_import_names() {
+    let out = run_hello(
+        r#"(import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))"#,
+    );
+    assert_eq!(out, "hello");
+}
+
+fn test_mixed_import_names() {
+    // An object built against the old target linked into a newer module
+    let out = run_hello(
+        r#"(import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (import "wasi_unstable" "sched_yield" (func $sched_yield (result i32)))"#,
+    );
+    assert_eq!(out, "hello");
+}
+
+fn test_preview2_imports_are_reported() {
+    let mut store = Store::default();
+    let module = Module::new(
+        &store,
+        br#"
+    (module
+        (import "wasi:cli/environment@0.2.0" "get-arguments" (func (param i32)))
+        (import "wasi_snapshot_preview1" "proc_exit" (func (param i32)))
+        (memory (export "memory") 1)
+    )
+    "#,
+    )
+    .unwrap();
+
+    let wasi_env = WasiState::new("command-name").finalize(&mut store).unwrap();
+    let err = wasi_env.import_object(&mut store, &module).unwrap_err();
+    assert!(matches!(
+        err,
+        WasiError::UnsupportedWasiVersion("WASI preview2")
+    ));
+    assert_eq!(
+        err.to_string(),
+        "this module targets WASI preview2, which this runtime build doesn't support"
+    );
+}
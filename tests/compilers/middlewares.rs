@@ -4,6 +4,7 @@ use std::sync::Arc;
 use wasmer::wasmparser::Operator;
 use wasmer::FunctionEnv;
 use wasmer::*;
+use wasmer_types::ModuleInfo;
 
 #[derive(Debug)]
 struct Add2MulGen {
@@ -94,6 +95,31 @@ impl<'a> FunctionMiddleware<'a> for Fusion {
     }
 }
 
+/// Fails the compilation once all the functions were compiled
+#[derive(Debug)]
+struct RejectModuleGen;
+
+#[derive(Debug)]
+struct PassThrough;
+
+impl ModuleMiddleware for RejectModuleGen {
+    fn generate_function_middleware<'a>(
+        &self,
+        _: LocalFunctionIndex,
+    ) -> Box<dyn FunctionMiddleware<'a> + 'a> {
+        Box::new(PassThrough)
+    }
+
+    fn finalize_module_info(&self, module_info: &ModuleInfo) -> Result<(), MiddlewareError> {
+        Err(MiddlewareError::new(
+            "reject_module",
+            format!("{} functions were compiled", module_info.functions.len()),
+        ))
+    }
+}
+
+impl<'a> FunctionMiddleware<'a> for PassThrough {}
+
 #[compiler_test(middlewares)]
 fn middleware_basic(mut config: crate::Config) -> Result<()> {
     config.set_middlewares(vec![
@@ -216,3 +242,22 @@ fn middleware_chain_order_2(mut config: crate::Config) -> Result<()> {
     assert_eq!(result, 48);
     Ok(())
 }
+
+#[compiler_test(middlewares)]
+fn middleware_finalize_module_info_error(mut config: crate::Config) -> Result<()> {
+    config.set_middlewares(vec![Arc::new(RejectModuleGen) as Arc<dyn ModuleMiddleware>]);
+    let store = config.store();
+    let wat = r#"(module
+        (func (export "add") (param i32 i32) (result i32)
+           (i32.add (local.get 0)
+                    (local.get 1)))
+)"#;
+    match Module::new(&store, wat) {
+        Err(CompileError::Wasm(WasmError::Middleware(error))) => {
+            assert_eq!(error.name, "reject_module");
+            assert_eq!(error.message, "1 functions were compiled");
+        }
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+    Ok(())
+}
@@ -319,6 +319,63 @@ fn test_wasmer_run_pirita_works() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[cfg(feature = "webc_runner")]
+#[test]
+fn test_wasmer_run_pirita_stdout_to_file() -> anyhow::Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let python_wasmer_path = temp_dir.path().join("python.wasmer");
+    std::fs::copy(wasi_test_python_path(), &python_wasmer_path)?;
+    let stdout_path = temp_dir.path().join("stdout.txt");
+
+    let output = Command::new(get_wasmer_path())
+        .arg("run")
+        .arg(&python_wasmer_path)
+        .arg("--stdout")
+        .arg(&stdout_path)
+        .arg("--")
+        .arg("-c")
+        .arg("print(\"hello\")")
+        .output()?;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(output.stdout.is_empty());
+    assert_eq!(std::fs::read_to_string(&stdout_path)?, "hello\n");
+
+    // Showing the output as well is not supported for packages
+    let output = Command::new(get_wasmer_path())
+        .arg("run")
+        .arg(&python_wasmer_path)
+        .arg("--stdout")
+        .arg(&stdout_path)
+        .arg("--tee")
+        .output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--tee is not supported"));
+
+    Ok(())
+}
+
+#[cfg(feature = "webc_runner")]
+#[test]
+fn test_wasmer_run_pirita_list_exports_is_rejected() -> anyhow::Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let python_wasmer_path = temp_dir.path().join("python.wasmer");
+    std::fs::copy(wasi_test_python_path(), &python_wasmer_path)?;
+
+    let output = Command::new(get_wasmer_path())
+        .arg("run")
+        .arg(&python_wasmer_path)
+        .arg("--list-exports")
+        .output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--list-exports is not supported"));
+
+    Ok(())
+}
+
 #[cfg(feature = "webc_runner")]
 #[test]
 fn test_wasmer_run_pirita_url_works() -> anyhow::Result<()> {
@@ -694,6 +751,562 @@ fn run_invoke_works_with_nomain_wasi() -> anyhow::Result<()> {
     Ok(())
 }
 
+// This test verifies that "wasmer run --invoke" on a WASI reactor prints the
+// typed results of the function, and exits the way the function asks to.
+#[test]
+fn run_invoke_wasi_reactor_returns_results() -> anyhow::Result<()> {
+    let wasi_wat = "
+    (module
+        (import \"wasi_snapshot_preview1\" \"fd_write\"
+          (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (import \"wasi_snapshot_preview1\" \"proc_exit\"
+          (func $proc_exit (param i32)))
+        (memory 1)
+        (export \"memory\" (memory 0))
+        (data (i32.const 64) \"ready\\n\")
+        (func (export \"_initialize\")
+          (i32.store (i32.const 0) (i32.const 64))
+          (i32.store (i32.const 4) (i32.const 6))
+          (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 32))))
+        (func (export \"divide\") (param i64 f64) (result i64 f64)
+          (local.get 0)
+          (f64.div (local.get 1) (f64.const 2)))
+        (func (export \"quit\") (param i32)
+          (call $proc_exit (local.get 0)))
+      )
+    ";
+
+    let random = rand::random::<u64>();
+    let module_file = std::env::temp_dir().join(&format!("{random}.wat"));
+    std::fs::write(&module_file, wasi_wat.as_bytes()).unwrap();
+
+    let output = Command::new(get_wasmer_path())
+        .arg("run")
+        .arg("--invoke")
+        .arg("divide")
+        .arg(&module_file)
+        .arg("7")
+        .arg("5")
+        .output()?;
+    let stderr = std::str::from_utf8(&output.stderr).unwrap();
+    assert!(output.status.success(), "{stderr}");
+    assert_eq!(
+        std::str::from_utf8(&output.stdout).unwrap(),
+        "ready\n7 2.5\n"
+    );
+
+    let output = Command::new(get_wasmer_path())
+        .arg("run")
+        .arg("--invoke")
+        .arg("quit")
+        .arg(&module_file)
+        .arg("3")
+        .output()?;
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(std::str::from_utf8(&output.stdout).unwrap(), "ready\n");
+
+    std::fs::remove_file(&module_file).unwrap();
+    Ok(())
+}
+
+// This test verifies that "wasmer run --pty module.wat" makes the
+// module see a terminal on its stdout even though it is piped.
+#[test]
+fn run_pty_makes_stdout_a_terminal() -> anyhow::Result<()> {
+    // Prints whether stdout is a character device, which is what
+    // `isatty()` checks
+    let wasi_wat = "
+    (module
+        (import \"wasi_snapshot_preview1\" \"fd_fdstat_get\"
+          (func $fd_fdstat_get (param i32 i32) (result i32)))
+        (import \"wasi_snapshot_preview1\" \"fd_write\"
+          (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (memory 1)
+        (export \"memory\" (memory 0))
+        (data (i32.const 64) \"interactive\\n\")
+        (data (i32.const 80) \"piped\\n\")
+        (func $_start
+          (drop (call $fd_fdstat_get (i32.const 1) (i32.const 0)))
+          (if (i32.eq (i32.load8_u (i32.const 0)) (i32.const 2))
+            (then
+              (i32.store (i32.const 32) (i32.const 64))
+              (i32.store (i32.const 36) (i32.const 12)))
+            (else
+              (i32.store (i32.const 32) (i32.const 80))
+              (i32.store (i32.const 36) (i32.const 6))))
+          (drop (call $fd_write (i32.const 1) (i32.const 32) (i32.const 1) (i32.const 40))))
+        (export \"_start\" (func $_start))
+      )
+    ";
+
+    let random = rand::random::<u64>();
+    let module_file = std::env::temp_dir().join(&format!("{random}.wat"));
+    std::fs::write(&module_file, wasi_wat.as_bytes()).unwrap();
+
+    let output = Command::new(get_wasmer_path())
+        .arg("run")
+        .arg(&module_file)
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(std::str::from_utf8(&output.stdout).unwrap(), "piped\n");
+
+    let output = Command::new(get_wasmer_path())
+        .arg("run")
+        .arg("--pty")
+        .arg(&module_file)
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(
+        std::str::from_utf8(&output.stdout).unwrap(),
+        "interactive\n"
+    );
+
+    std::fs::remove_file(&module_file).unwrap();
+    Ok(())
+}
+
+// This test verifies that "wasmer run --env-file vars.env module.wat"
+// passes the variables of the file, with --env taking precedence.
+#[test]
+fn run_env_file_sets_variables() -> anyhow::Result<()> {
+    // Prints its environment, each variable followed by a NUL
+    let wasi_wat = "
+    (module
+        (import \"wasi_snapshot_preview1\" \"environ_sizes_get\"
+          (func $environ_sizes_get (param i32 i32) (result i32)))
+        (import \"wasi_snapshot_preview1\" \"environ_get\"
+          (func $environ_get (param i32 i32) (result i32)))
+        (import \"wasi_snapshot_preview1\" \"fd_write\"
+          (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (memory 1)
+        (export \"memory\" (memory 0))
+        (func $_start
+          (drop (call $environ_sizes_get (i32.const 0) (i32.const 4)))
+          (drop (call $environ_get (i32.const 1024) (i32.const 2048)))
+          (i32.store (i32.const 16) (i32.const 2048))
+          (i32.store (i32.const 20) (i32.load (i32.const 4)))
+          (drop (call $fd_write (i32.const 1) (i32.const 16) (i32.const 1) (i32.const 24))))
+        (export \"_start\" (func $_start))
+      )
+    ";
+
+    let random = rand::random::<u64>();
+    let module_file = std::env::temp_dir().join(&format!("{random}.wat"));
+    std::fs::write(&module_file, wasi_wat.as_bytes()).unwrap();
+    let env_file = std::env::temp_dir().join(&format!("{random}.env"));
+    std::fs::write(
+        &env_file,
+        "# settings\nGREETING=\"hello world\"\nNAME=file\n",
+    )
+    .unwrap();
+
+    let output = Command::new(get_wasmer_path())
+        .arg("run")
+        .arg("--env-file")
+        .arg(&env_file)
+        .arg("--env")
+        .arg("NAME=flag")
+        .arg(&module_file)
+        .output()?;
+    assert!(output.status.success());
+    let stdout = std::str::from_utf8(&output.stdout).unwrap();
+    let vars = stdout.split_terminator('\0').collect::<Vec<_>>();
+    assert_eq!(vars, ["GREETING=hello world", "NAME=flag"]);
+
+    std::fs::remove_file(&module_file).unwrap();
+    std::fs::remove_file(&env_file).unwrap();
+    Ok(())
+}
+
+// This test verifies that "wasmer run --stdin input.txt cat.wat" feeds the
+// file to the program, and that a missing file is reported up front.
+#[test]
+fn run_stdin_reads_from_file() -> anyhow::Result<()> {
+    // Copies stdin to stdout, like `cat`
+    let wasi_wat = "
+    (module
+        (import \"wasi_snapshot_preview1\" \"fd_read\"
+          (func $fd_read (param i32 i32 i32 i32) (result i32)))
+        (import \"wasi_snapshot_preview1\" \"fd_write\"
+          (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (memory 1)
+        (export \"memory\" (memory 0))
+        (func $_start
+          (i32.store (i32.const 0) (i32.const 1024))
+          (i32.store (i32.const 4) (i32.const 1024))
+          (block $done
+            (loop $copy
+              (br_if $done (call $fd_read (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 8)))
+              (br_if $done (i32.eqz (i32.load (i32.const 8))))
+              (i32.store (i32.const 16) (i32.const 1024))
+              (i32.store (i32.const 20) (i32.load (i32.const 8)))
+              (drop (call $fd_write (i32.const 1) (i32.const 16) (i32.const 1) (i32.const 24)))
+              (br $copy))))
+        (export \"_start\" (func $_start))
+      )
+    ";
+
+    let random = rand::random::<u64>();
+    let module_file = std::env::temp_dir().join(&format!("{random}.wat"));
+    std::fs::write(&module_file, wasi_wat.as_bytes()).unwrap();
+    let input_file = std::env::temp_dir().join(&format!("{random}.txt"));
+    let input = "first line\nsecond line\n".repeat(200);
+    std::fs::write(&input_file, &input).unwrap();
+
+    let output = Command::new(get_wasmer_path())
+        .arg("run")
+        .arg("--stdin")
+        .arg(&input_file)
+        .arg(&module_file)
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(std::str::from_utf8(&output.stdout).unwrap(), input);
+
+    std::fs::remove_file(&input_file).unwrap();
+    let output = Command::new(get_wasmer_path())
+        .arg("run")
+        .arg("--stdin")
+        .arg(&input_file)
+        .arg(&module_file)
+        .output()?;
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = std::str::from_utf8(&output.stderr).unwrap();
+    assert!(stderr.contains("could not open stdin file"), "{stderr}");
+
+    std::fs::remove_file(&module_file).unwrap();
+    Ok(())
+}
+
+// This test verifies that "wasmer run --stdout out.txt --stderr err.txt"
+// captures both streams, and that --tee still shows them.
+#[test]
+fn run_stdout_stderr_capture() -> anyhow::Result<()> {
+    // Writes "to stdout" to fd 1 and "to stderr" to fd 2
+    let wasi_wat = "
+    (module
+        (import \"wasi_snapshot_preview1\" \"fd_write\"
+          (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (memory 1)
+        (export \"memory\" (memory 0))
+        (data (i32.const 64) \"to stdout\\n\")
+        (data (i32.const 80) \"to stderr\\n\")
+        (func $_start
+          (i32.store (i32.const 0) (i32.const 64))
+          (i32.store (i32.const 4) (i32.const 10))
+          (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 32)))
+          (i32.store (i32.const 0) (i32.const 80))
+          (i32.store (i32.const 4) (i32.const 10))
+          (drop (call $fd_write (i32.const 2) (i32.const 0) (i32.const 1) (i32.const 32))))
+        (export \"_start\" (func $_start))
+      )
+    ";
+
+    let random = rand::random::<u64>();
+    let module_file = std::env::temp_dir().join(&format!("{random}.wat"));
+    std::fs::write(&module_file, wasi_wat.as_bytes()).unwrap();
+    let stdout_file = std::env::temp_dir().join(&format!("{random}.stdout"));
+    let stderr_file = std::env::temp_dir().join(&format!("{random}.stderr"));
+    // Left over contents are truncated
+    std::fs::write(&stdout_file, "stale contents that are longer").unwrap();
+
+    let output = Command::new(get_wasmer_path())
+        .arg("run")
+        .arg("--stdout")
+        .arg(&stdout_file)
+        .arg("--stderr")
+        .arg(&stderr_file)
+        .arg(&module_file)
+        .output()?;
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+    assert_eq!(std::fs::read(&stdout_file)?, b"to stdout\n");
+    assert_eq!(std::fs::read(&stderr_file)?, b"to stderr\n");
+
+    let output = Command::new(get_wasmer_path())
+        .arg("run")
+        .arg("--stdout")
+        .arg(&stdout_file)
+        .arg("--stderr")
+        .arg(&stderr_file)
+        .arg("--tee")
+        .arg(&module_file)
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"to stdout\n");
+    assert_eq!(output.stderr, b"to stderr\n");
+    assert_eq!(std::fs::read(&stdout_file)?, b"to stdout\n");
+    assert_eq!(std::fs::read(&stderr_file)?, b"to stderr\n");
+
+    std::fs::remove_file(&module_file).unwrap();
+    std::fs::remove_file(&stdout_file).unwrap();
+    std::fs::remove_file(&stderr_file).unwrap();
+    Ok(())
+}
+
+// This test verifies that when a program prints why it is aborting and then
+// traps, "wasmer run" reports the message along with the trap.
+#[test]
+fn run_abort_reports_message() -> anyhow::Result<()> {
+    // Writes a panic message to stderr and aborts the way Rust programs do
+    let wasi_wat = "
+    (module
+        (import \"wasi_snapshot_preview1\" \"fd_write\"
+          (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (memory 1)
+        (export \"memory\" (memory 0))
+        (data (i32.const 64) \"thread 'main' panicked at 'boom'\\n\")
+        (func $_start
+          (i32.store (i32.const 0) (i32.const 64))
+          (i32.store (i32.const 4) (i32.const 33))
+          (drop (call $fd_write (i32.const 2) (i32.const 0) (i32.const 1) (i32.const 32)))
+          unreachable)
+        (export \"_start\" (func $_start))
+      )
+    ";
+
+    let random = rand::random::<u64>();
+    let module_file = std::env::temp_dir().join(&format!("{random}.wat"));
+    std::fs::write(&module_file, wasi_wat.as_bytes()).unwrap();
+
+    let output = Command::new(get_wasmer_path())
+        .arg("run")
+        .arg(&module_file)
+        .output()?;
+    assert!(!output.status.success());
+    let stderr = std::str::from_utf8(&output.stderr).unwrap();
+    assert!(
+        stderr.contains("guest aborted: thread 'main' panicked at 'boom'"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("unreachable"), "{stderr}");
+
+    std::fs::remove_file(&module_file).unwrap();
+    Ok(())
+}
+
+// This test verifies that a trap is reported as is when what the program
+// printed last doesn't explain an abort.
+#[test]
+fn run_trap_without_abort_message() -> anyhow::Result<()> {
+    let wasi_wat = "
+    (module
+        (import \"wasi_snapshot_preview1\" \"fd_write\"
+          (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (memory 1)
+        (export \"memory\" (memory 0))
+        (data (i32.const 64) \"loading config\\n\")
+        (func $_start
+          (i32.store (i32.const 0) (i32.const 64))
+          (i32.store (i32.const 4) (i32.const 15))
+          (drop (call $fd_write (i32.const 2) (i32.const 0) (i32.const 1) (i32.const 32)))
+          unreachable)
+        (export \"_start\" (func $_start))
+      )
+    ";
+
+    let random = rand::random::<u64>();
+    let module_file = std::env::temp_dir().join(&format!("{random}.wat"));
+    std::fs::write(&module_file, wasi_wat.as_bytes()).unwrap();
+
+    let output = Command::new(get_wasmer_path())
+        .arg("run")
+        .arg(&module_file)
+        .output()?;
+    assert!(!output.status.success());
+    let stderr = std::str::from_utf8(&output.stderr).unwrap();
+    assert!(!stderr.contains("guest aborted"), "{stderr}");
+    assert!(stderr.contains("unreachable"), "{stderr}");
+
+    std::fs::remove_file(&module_file).unwrap();
+    Ok(())
+}
+
+// This test verifies that "wasmer run --list-exports" prints the exports
+// with their types, without instantiating the module.
+#[test]
+fn run_list_exports_prints_signatures() -> anyhow::Result<()> {
+    // The import can't be satisfied, so the module is never instantiated
+    let wat = "
+    (module
+        (import \"env\" \"missing\" (func $missing))
+        (memory (export \"memory\") 1)
+        (global (export \"counter\") (mut i32) (i32.const 0))
+        (func (export \"add\") (param i32 i32) (result i32)
+          (i32.add (local.get 0) (local.get 1)))
+        (func (export \"split\") (param i64 f32) (result f64 i32)
+          (call $missing)
+          (f64.const 0)
+          (i32.const 0))
+        (func (export \"nothing\"))
+      )
+    ";
+
+    let random = rand::random::<u64>();
+    let module_file = std::env::temp_dir().join(&format!("{random}.wat"));
+    std::fs::write(&module_file, wat.as_bytes()).unwrap();
+
+    let output = Command::new(get_wasmer_path())
+        .arg("run")
+        .arg("--list-exports")
+        .arg(&module_file)
+        .output()?;
+    let stderr = std::str::from_utf8(&output.stderr).unwrap();
+    assert!(output.status.success(), "{stderr}");
+    let stdout = std::str::from_utf8(&output.stdout).unwrap();
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 5, "{stdout}");
+    assert!(lines[0].starts_with("memory \"memory\": "), "{stdout}");
+    assert_eq!(
+        &lines[1..],
+        [
+            "global \"counter\": I32 (mutable)",
+            "func \"add\": [I32, I32] -> [I32]",
+            "func \"split\": [I64, F32] -> [F64, I32]",
+            "func \"nothing\": [] -> []",
+        ]
+    );
+
+    std::fs::remove_file(&module_file).unwrap();
+    Ok(())
+}
+
+// This test verifies that "wasmer run -" runs a precompiled artifact that is
+// piped in.
+#[test]
+fn run_artifact_from_stdin() -> anyhow::Result<()> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let wasi_wat = "
+    (module
+        (import \"wasi_snapshot_preview1\" \"fd_write\"
+          (func $fd_write (param i32 i32 i32 i32) (result i32)))
+        (memory 1)
+        (export \"memory\" (memory 0))
+        (data (i32.const 64) \"hello from stdin\\n\")
+        (func $_start
+          (i32.store (i32.const 0) (i32.const 64))
+          (i32.store (i32.const 4) (i32.const 17))
+          (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 32))))
+        (export \"_start\" (func $_start))
+      )
+    ";
+
+    let random = rand::random::<u64>();
+    let module_file = std::env::temp_dir().join(&format!("{random}.wat"));
+    std::fs::write(&module_file, wasi_wat.as_bytes()).unwrap();
+    let artifact_file = std::env::temp_dir().join(&format!("{random}.wasmu"));
+
+    let output = Command::new(get_wasmer_path())
+        .arg("compile")
+        .arg(&module_file)
+        .arg("-o")
+        .arg(&artifact_file)
+        .output()?;
+    assert!(output.status.success());
+
+    for file in [&artifact_file, &module_file] {
+        let mut child = Command::new(get_wasmer_path())
+            .arg("run")
+            .arg("-")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(&std::fs::read(file)?)?;
+        let output = child.wait_with_output()?;
+        let stderr = std::str::from_utf8(&output.stderr).unwrap();
+        assert!(output.status.success(), "{stderr}");
+        assert_eq!(
+            std::str::from_utf8(&output.stdout).unwrap(),
+            "hello from stdin\n"
+        );
+    }
+
+    std::fs::remove_file(&module_file).unwrap();
+    std::fs::remove_file(&artifact_file).unwrap();
+    Ok(())
+}
+
+/// Runs `wat` without naming a function to call
+fn run_without_entrypoint(wat: &str, args: &[&str]) -> anyhow::Result<std::process::Output> {
+    let random = rand::random::<u64>();
+    let module_file = std::env::temp_dir().join(&format!("{random}.wat"));
+    std::fs::write(&module_file, wat.as_bytes()).unwrap();
+    let output = Command::new(get_wasmer_path())
+        .arg("run")
+        .arg(&module_file)
+        .args(args)
+        .output()?;
+    std::fs::remove_file(&module_file).unwrap();
+    Ok(output)
+}
+
+// This test verifies that a reactor module without `_start` is initialized
+// and then has its `main` function run.
+#[test]
+fn run_reactor_initializes_then_runs_main() -> anyhow::Result<()> {
+    let wat = "
+    (module
+        (global $ready (mut i32) (i32.const 0))
+        (func (export \"_initialize\")
+          (global.set $ready (i32.const 42)))
+        (func (export \"main\") (result i32)
+          (global.get $ready))
+        (func (export \"helper\") (result i32)
+          (i32.const 7))
+      )
+    ";
+    let output = run_without_entrypoint(wat, &[])?;
+    let stderr = std::str::from_utf8(&output.stderr).unwrap();
+    assert!(output.status.success(), "{stderr}");
+    assert_eq!(std::str::from_utf8(&output.stdout).unwrap(), "42\n");
+    Ok(())
+}
+
+// This test verifies that the only function a module exports is run, with
+// the arguments given to it.
+#[test]
+fn run_single_export_without_start() -> anyhow::Result<()> {
+    let wat = "
+    (module
+        (func (export \"double\") (param i32) (result i32)
+          (i32.mul (local.get 0) (i32.const 2)))
+      )
+    ";
+    let output = run_without_entrypoint(wat, &["21"])?;
+    let stderr = std::str::from_utf8(&output.stderr).unwrap();
+    assert!(output.status.success(), "{stderr}");
+    assert_eq!(std::str::from_utf8(&output.stdout).unwrap(), "42\n");
+    Ok(())
+}
+
+// This test verifies that the candidates are listed when there is no
+// obvious function to run.
+#[test]
+fn run_ambiguous_entrypoint_lists_candidates() -> anyhow::Result<()> {
+    let wat = "
+    (module
+        (func (export \"first\"))
+        (func (export \"second\"))
+      )
+    ";
+    let output = run_without_entrypoint(wat, &[])?;
+    assert!(!output.status.success());
+    let stderr = std::str::from_utf8(&output.stderr).unwrap();
+    assert!(
+        stderr.contains("Candidates: `first`, `second`."),
+        "{stderr}"
+    );
+    Ok(())
+}
+
 #[test]
 fn run_no_start_wasm_report_error() -> anyhow::Result<()> {
     let output = Command::new(get_wasmer_path())